@@ -0,0 +1,120 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadDirectError},
+    prelude::*,
+    reflect::TypePath,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::registry::{Registry, REGISTRY_PATH};
+use crate::data::levels::LevelDefinition;
+use crate::tiled::generator::get_generated_map_seed;
+
+/// The level manifest
+///
+/// Levels are listed on the level select screen in manifest order
+#[derive(Debug, TypePath, Asset, Deserialize)]
+pub struct Levels {
+    pub levels: Vec<LevelDefinition>,
+}
+
+impl Levels {
+    /// Gets the level that plays the given map
+    ///
+    /// Generated maps belong to the first generated level
+    pub fn get_level_by_map(&self, map: impl AsRef<str>) -> Option<&LevelDefinition> {
+        let map = map.as_ref();
+        if get_generated_map_seed(map).is_some() {
+            return self.levels.iter().find(|level| level.generated);
+        }
+
+        self.levels
+            .iter()
+            .find(|level| !level.generated && level.map == map)
+    }
+
+    /// Checks the manifest for mistakes
+    pub fn validate(&self) -> Result<(), LevelsAssetLoaderError> {
+        if self.levels.is_empty() {
+            return Err(LevelsAssetLoaderError::NoLevels);
+        }
+
+        for (idx, level) in self.levels.iter().enumerate() {
+            if self.levels[..idx]
+                .iter()
+                .any(|other| other.name == level.name)
+            {
+                return Err(LevelsAssetLoaderError::DuplicateLevel(level.name.clone()));
+            }
+
+            if !level.generated && level.map.is_empty() {
+                return Err(LevelsAssetLoaderError::MissingMap(level.name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct LevelsLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelsAssetLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not load levels file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A [RON](ron) Error
+    #[error("Could not parse levels: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+
+    #[error("Could not load registry: {0}")]
+    Registry(#[from] Box<LoadDirectError>),
+
+    #[error("No levels are defined")]
+    NoLevels,
+
+    #[error("Level {0} is defined more than once")]
+    DuplicateLevel(String),
+
+    #[error("Level {0} has no map")]
+    MissingMap(String),
+}
+
+impl AssetLoader for LevelsLoader {
+    type Asset = Levels;
+    type Settings = ();
+    type Error = LevelsAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let path = load_context.path().to_owned();
+        info!("Loading levels: {}", path.display());
+
+        // Resource types are defined by the registry
+        load_context
+            .loader()
+            .direct()
+            .load::<Registry>(REGISTRY_PATH)
+            .await
+            .map_err(Box::new)?;
+
+        let levels = ron::de::from_bytes::<Levels>(&bytes)?;
+        levels.validate()?;
+
+        info!("Loaded {} levels: {}", levels.levels.len(), path.display());
+        Ok(levels)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["levels.ron"];
+        EXTENSIONS
+    }
+}
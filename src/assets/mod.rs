@@ -1 +1,4 @@
+pub mod levels;
+pub mod recipes;
+pub mod registry;
 pub mod tiled;
@@ -0,0 +1,87 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadDirectError},
+    prelude::*,
+    reflect::TypePath,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::registry::{Registry, REGISTRY_PATH};
+use crate::data::recipes::Recipe;
+
+#[derive(Debug, TypePath, Asset, Deserialize)]
+pub struct Recipes {
+    pub recipes: Vec<Recipe>,
+}
+
+impl Recipes {
+    /// Gets the Recipe after the named Recipe, wrapping around
+    pub fn next(&self, name: Option<impl AsRef<str>>) -> Option<&Recipe> {
+        let idx = name
+            .and_then(|name| {
+                self.recipes
+                    .iter()
+                    .position(|recipe| recipe.name == name.as_ref())
+            })
+            .map(|idx| idx + 1)
+            .unwrap_or_default();
+        self.recipes.get(idx).or_else(|| self.recipes.first())
+    }
+}
+
+pub struct RecipesLoader;
+
+#[derive(Debug, Error)]
+pub enum RecipesAssetLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not load recipes file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A [RON](ron) Error
+    #[error("Could not parse recipes: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+
+    #[error("Could not load registry: {0}")]
+    Registry(#[from] Box<LoadDirectError>),
+}
+
+impl AssetLoader for RecipesLoader {
+    type Asset = Recipes;
+    type Settings = ();
+    type Error = RecipesAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let path = load_context.path().to_owned();
+        info!("Loading recipes: {}", path.display());
+
+        // Resource types are defined by the registry
+        load_context
+            .loader()
+            .direct()
+            .load::<Registry>(REGISTRY_PATH)
+            .await
+            .map_err(Box::new)?;
+
+        let recipes = ron::de::from_bytes::<Recipes>(&bytes)?;
+
+        info!(
+            "Loaded {} recipes: {}",
+            recipes.recipes.len(),
+            path.display()
+        );
+        Ok(recipes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["recipes.ron"];
+        EXTENSIONS
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt},
+    prelude::*,
+    reflect::TypePath,
+};
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::data::{
+    items::ItemType,
+    registry::{ItemDefinition, ResourceDefinition, SpriteDefinition},
+    resources::ResourceType,
+};
+use crate::tiled::{TILE_HEIGHT, TILE_WIDTH};
+
+/// Registry asset path
+pub const REGISTRY_PATH: &str = "game.registry.ron";
+
+/// Resource and Item definitions
+///
+/// Loaded as an asset and then inserted as a resource once loaded
+#[derive(Debug, Clone, TypePath, Asset, Resource, Deserialize)]
+pub struct Registry {
+    pub resources: Vec<ResourceDefinition>,
+    pub items: Vec<ItemDefinition>,
+}
+
+impl Registry {
+    #[inline]
+    pub fn get_resource(&self, resource_type: ResourceType) -> Option<&ResourceDefinition> {
+        self.resources
+            .iter()
+            .find(|definition| definition.id == resource_type)
+    }
+
+    /// Gets the definition for an Item
+    ///
+    /// Every Item type is required to have a definition
+    #[inline]
+    pub fn get_item(&self, item_type: ItemType) -> &ItemDefinition {
+        self.items
+            .iter()
+            .find(|definition| definition.id == item_type)
+            .unwrap()
+    }
+
+    /// Checks the definitions against what the game supports
+    pub fn validate(&self) -> Result<(), RegistryAssetLoaderError> {
+        for (idx, definition) in self.resources.iter().enumerate() {
+            if self.resources[..idx]
+                .iter()
+                .any(|other| other.id == definition.id)
+            {
+                return Err(RegistryAssetLoaderError::DuplicateResource(definition.id));
+            }
+
+            if !definition.sprite.is_valid() {
+                return Err(RegistryAssetLoaderError::InvalidResourceSprite(
+                    definition.id,
+                ));
+            }
+        }
+
+        for item_type in ItemType::iter() {
+            let mut definitions = self
+                .items
+                .iter()
+                .filter(|definition| definition.id == item_type);
+
+            let Some(definition) = definitions.next() else {
+                return Err(RegistryAssetLoaderError::MissingItem(item_type));
+            };
+
+            if definitions.next().is_some() {
+                return Err(RegistryAssetLoaderError::DuplicateItem(item_type));
+            }
+
+            if !item_type.supports_placement(&definition.placement) {
+                return Err(RegistryAssetLoaderError::InvalidPlacement(item_type));
+            }
+
+            if !item_type.supports_footprint(&definition.footprint) {
+                return Err(RegistryAssetLoaderError::InvalidFootprint(item_type));
+            }
+
+            if !definition.sprite.is_valid() {
+                return Err(RegistryAssetLoaderError::InvalidItemSprite(item_type));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct RegistryLoader;
+
+#[derive(Debug, Error)]
+pub enum RegistryAssetLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not load registry file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A [RON](ron) Error
+    #[error("Could not parse registry: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+
+    #[error("Resource {0} is defined more than once")]
+    DuplicateResource(ResourceType),
+
+    #[error("Item {0} is not defined")]
+    MissingItem(ItemType),
+
+    #[error("Item {0} is defined more than once")]
+    DuplicateItem(ItemType),
+
+    #[error("Item {0} does not support its placement")]
+    InvalidPlacement(ItemType),
+
+    #[error("Item {0} does not support its footprint")]
+    InvalidFootprint(ItemType),
+
+    #[error(
+        "Resource {0} has a sprite index outside of its sprite sheet or an invalid frame duration"
+    )]
+    InvalidResourceSprite(ResourceType),
+
+    #[error(
+        "Item {0} has a sprite index outside of its sprite sheet or an invalid frame duration"
+    )]
+    InvalidItemSprite(ItemType),
+}
+
+fn load_sprite(sprite: &mut SpriteDefinition, load_context: &mut bevy::asset::LoadContext<'_>) {
+    sprite.image_handle = load_context.load(sprite.image.clone());
+
+    // sprites sharing a sprite sheet share the layout
+    let label = format!("atlas/{}/{}x{}", sprite.image, sprite.columns, sprite.rows);
+    sprite.atlas_handle = if load_context.has_labeled_asset(label.clone()) {
+        load_context.get_label_handle(label)
+    } else {
+        load_context.add_labeled_asset(
+            label,
+            TextureAtlasLayout::from_grid(
+                UVec2::new(TILE_WIDTH, TILE_HEIGHT),
+                sprite.columns,
+                sprite.rows,
+                None,
+                None,
+            ),
+        )
+    };
+}
+
+impl AssetLoader for RegistryLoader {
+    type Asset = Registry;
+    type Settings = ();
+    type Error = RegistryAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let path = load_context.path().to_owned();
+        info!("Loading registry: {}", path.display());
+
+        let mut registry = ron::de::from_bytes::<Registry>(&bytes)?;
+        registry.validate()?;
+
+        for definition in &mut registry.resources {
+            load_sprite(&mut definition.sprite, load_context);
+        }
+
+        for definition in &mut registry.items {
+            load_sprite(&mut definition.sprite, load_context);
+        }
+
+        info!(
+            "Loaded {} resources and {} items: {}",
+            registry.resources.len(),
+            registry.items.len(),
+            path.display()
+        );
+        Ok(registry)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["registry.ron"];
+        EXTENSIONS
+    }
+}
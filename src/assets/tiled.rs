@@ -1,27 +1,89 @@
 use std::collections::HashMap;
 use std::io::{Cursor, Error, ErrorKind, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use bevy::{
     asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt},
     prelude::*,
     reflect::TypePath,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::TextureFormatPixelInfo,
+    },
 };
 use bevy_ecs_tilemap::prelude::*;
 use thiserror::Error;
 
+use crate::tiled::{
+    export::get_tileset_sources, get_tile_animation, get_tilesets, TiledMapBounds, TILE_HEIGHT,
+    TILE_WIDTH,
+};
+use crate::tilemap::TileAnimation;
+
 #[derive(TypePath, Asset)]
 pub struct TiledMap {
     pub name: String,
 
     pub map: tiled::Map,
 
+    /// The area of the map that is played on
+    pub bounds: TiledMapBounds,
+
     // maps Tileset name to TilemapTexture
     pub tilemap_textures: HashMap<String, TilemapTexture>,
+
+    /// External tileset paths by tileset name, for exporting the map
+    pub tileset_sources: HashMap<String, PathBuf>,
+}
+
+impl TiledMap {
+    /// Gets a tileset by name, including tilesets only used by Object templates
+    pub fn get_tileset(&self, tileset: &str) -> Option<&tiled::Tileset> {
+        get_tilesets(&self.map)
+            .into_iter()
+            .find(|other| other.name == tileset)
+    }
+
+    /// Gets the animation of a tile by its tileset name and id
+    pub fn get_tile_animation(&self, tileset: &str, tile_id: u32) -> Option<TileAnimation> {
+        self.get_tileset(tileset)
+            .and_then(|tileset| get_tile_animation(tileset, tile_id))
+    }
+}
+
+/// Gets the tile grid (columns, rows) of a tileset's tilemap texture
+///
+/// Image collections use the grid of their packed atlas
+pub fn get_tileset_texture_grid(tileset: &tiled::Tileset) -> UVec2 {
+    match tileset.image {
+        Some(_) => UVec2::new(
+            tileset.columns.max(1),
+            tileset.tilecount.div_ceil(tileset.columns.max(1)),
+        ),
+        None => get_collection_atlas_grid(
+            tileset
+                .tiles()
+                .filter(|(_, tile)| tile.image.is_some())
+                .map(|(tile_id, _)| tile_id + 1)
+                .max()
+                .unwrap_or(1),
+        ),
+    }
 }
 
+/// Collection atlases are packed into a square-ish grid of tile cells
+fn get_collection_atlas_grid(cells: u32) -> UVec2 {
+    let columns = (cells as f32).sqrt().ceil() as u32;
+    UVec2::new(columns, cells.div_ceil(columns))
+}
+
+/// Reads the map from the bytes already loaded by the AssetLoader
+/// and every other file it references (tilesets, templates, etc)
+/// through the LoadContext, so that they are tracked as dependencies
 struct BytesResourceReader<'a, 'ctx> {
+    path: PathBuf,
     bytes: Arc<[u8]>,
     load_context: &'a mut bevy::asset::LoadContext<'ctx>,
 }
@@ -29,6 +91,7 @@ struct BytesResourceReader<'a, 'ctx> {
 impl<'a, 'ctx> BytesResourceReader<'a, 'ctx> {
     fn new(bytes: &[u8], load_context: &'a mut bevy::asset::LoadContext<'ctx>) -> Self {
         Self {
+            path: load_context.path().to_owned(),
             bytes: Arc::from(bytes),
             load_context,
         }
@@ -40,19 +103,100 @@ impl<'a, 'ctx> tiled::ResourceReader for BytesResourceReader<'a, 'ctx> {
     type Error = std::io::Error;
 
     fn read_from(&mut self, path: &Path) -> std::result::Result<Self::Resource, Self::Error> {
-        if let Some(extension) = path.extension() {
-            // TSX support adapted from https://github.com/StarArawn/bevy_ecs_tilemap/pull/429
-            if extension == "tsx" {
-                let future = self.load_context.read_asset_bytes(path.to_owned());
-                let data = futures_lite::future::block_on(future)
-                    .map_err(|err| Error::new(ErrorKind::NotFound, err))?;
-                return Ok(Box::new(Cursor::new(data.clone())));
-            }
+        if path == self.path {
+            return Ok(Box::new(Cursor::new(self.bytes.clone())));
         }
-        Ok(Box::new(Cursor::new(self.bytes.clone())))
+
+        // TSX support adapted from https://github.com/StarArawn/bevy_ecs_tilemap/pull/429
+        let future = self.load_context.read_asset_bytes(path.to_owned());
+        let data = futures_lite::future::block_on(future)
+            .map_err(|err| Error::new(ErrorKind::NotFound, err))?;
+        Ok(Box::new(Cursor::new(data)))
     }
 }
 
+/// Packs the images of an image collection tileset into a single atlas image
+///
+/// Each tile is placed in the cell matching its tile id, so tile ids can be used
+/// as texture indices just like with single image tilesets. Tile images must be
+/// exactly one tile in size, this is checked when the map is validated
+async fn load_collection_atlas(
+    tileset: &tiled::Tileset,
+    load_context: &mut bevy::asset::LoadContext<'_>,
+) -> Result<Image, TiledAssetLoaderError> {
+    let mut images = Vec::new();
+    for (tile_id, tile) in tileset.tiles() {
+        let Some(tile_image) = tile.image.as_ref() else {
+            continue;
+        };
+
+        // direct loads are dependencies of the map, so editing an image reloads it
+        let image = load_context
+            .loader()
+            .direct()
+            .load::<Image>(AssetPath::from(tile_image.source.clone()))
+            .await
+            .map_err(|err| std::io::Error::other(format!("Could not load tile image: {err}")))?
+            .take()
+            .convert(TextureFormat::Rgba8UnormSrgb)
+            .ok_or_else(|| {
+                std::io::Error::other(format!(
+                    "Unsupported tile image format: {}",
+                    tile_image.source.display()
+                ))
+            })?;
+
+        let size = image.size();
+        if size.x != TILE_WIDTH || size.y != TILE_HEIGHT {
+            return Err(std::io::Error::other(format!(
+                "Tileset {} tile {} image has invalid size ({}x{})",
+                tileset.name, tile_id, size.x, size.y
+            ))
+            .into());
+        }
+
+        images.push((tile_id, image));
+    }
+
+    let cells = images
+        .iter()
+        .map(|(tile_id, _)| tile_id + 1)
+        .max()
+        .unwrap_or(1);
+    let UVec2 {
+        x: columns,
+        y: rows,
+    } = get_collection_atlas_grid(cells);
+
+    let pixel_size = TextureFormat::Rgba8UnormSrgb.pixel_size();
+    let atlas_width = columns * TILE_WIDTH;
+    let row_bytes = TILE_WIDTH as usize * pixel_size;
+
+    let mut data = vec![0; (atlas_width * rows * TILE_HEIGHT) as usize * pixel_size];
+    for (tile_id, image) in images {
+        let cell_x = (tile_id % columns) * TILE_WIDTH;
+        let cell_y = (tile_id / columns) * TILE_HEIGHT;
+
+        for y in 0..TILE_HEIGHT {
+            let src = y as usize * row_bytes;
+            let dst = ((cell_y + y) * atlas_width + cell_x) as usize * pixel_size;
+            data[dst..dst + row_bytes].copy_from_slice(&image.data[src..src + row_bytes]);
+        }
+    }
+
+    Ok(Image::new(
+        Extent3d {
+            width: atlas_width,
+            height: rows * TILE_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    ))
+}
+
 pub struct TiledLoader;
 
 #[derive(Debug, Error)]
@@ -83,31 +227,38 @@ impl AssetLoader for TiledLoader {
             tiled::DefaultResourceCache::new(),
             BytesResourceReader::new(&bytes, load_context),
         );
-        let map = loader.load_tmx_map(&path).map_err(|e| {
-            std::io::Error::new(ErrorKind::Other, format!("Could not load TMX map: {e}"))
-        })?;
+        let map = loader
+            .load_tmx_map(&path)
+            .map_err(|e| std::io::Error::other(format!("Could not load TMX map: {e}")))?;
+        let tileset_sources = get_tileset_sources(loader.cache());
 
         let mut tilemap_textures = HashMap::default();
-        for tileset in map.tilesets() {
-            let tilemap_image = tileset.image.as_ref().unwrap_or_else(|| {
-                panic!(
-                    "Image collection in tileset '{}' is incompatible with atlas feature",
-                    tileset.name
-                )
-            });
-
-            // https://github.com/StarArawn/bevy_ecs_tilemap/pull/525
-            let asset_path = AssetPath::from(tilemap_image.source.clone());
-            let texture = load_context.load(asset_path.clone());
-
-            let tilemap_texture = TilemapTexture::Single(texture.clone());
+        // Object templates may bring in tilesets the map doesn't reference itself
+        for tileset in get_tilesets(&map) {
+            let texture = match tileset.image.as_ref() {
+                Some(tilemap_image) => {
+                    // https://github.com/StarArawn/bevy_ecs_tilemap/pull/525
+                    let asset_path = AssetPath::from(tilemap_image.source.clone());
+                    load_context.load(asset_path)
+                }
+                // image collections are incompatible with the atlas feature,
+                // so their images are packed into one
+                None => {
+                    let atlas = load_collection_atlas(tileset, load_context).await?;
+                    load_context.add_labeled_asset(format!("collection/{}", tileset.name), atlas)
+                }
+            };
+
+            let tilemap_texture = TilemapTexture::Single(texture);
             tilemap_textures.insert(tileset.name.clone(), tilemap_texture);
         }
 
         let asset_map = TiledMap {
             name: path.display().to_string(),
+            bounds: TiledMapBounds::new(&map),
             map,
             tilemap_textures,
+            tileset_sources,
         };
 
         info!("Loaded map: {}", path.display());
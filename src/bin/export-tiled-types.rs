@@ -0,0 +1,100 @@
+//! Tiled project property type exporter
+//!
+//! Writes the game's enums and Object / Tile classes into a .tiled-project
+//! so Tiled can offer them as dropdowns
+//!
+//! Usage: export-tiled-types [--registry <game.registry.ron>] [--check] [<project.tiled-project>]
+
+#![deny(warnings)]
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use bevy_jam_factory::{
+    assets::registry::Registry,
+    tiled::project::{update_project, write_project},
+};
+
+const USAGE: &str =
+    "Usage: export-tiled-types [--registry <game.registry.ron>] [--check] [<project.tiled-project>]";
+const DEFAULT_REGISTRY_PATH: &str = "assets/game.registry.ron";
+const DEFAULT_PROJECT_PATH: &str = "maps/tilemap.tiled-project";
+
+/// Loads the Resource and Item definitions that the property types are generated from
+fn load_registry(path: &Path) -> Result<Registry, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let registry = ron::from_str::<Registry>(&contents).map_err(|err| err.to_string())?;
+    registry.validate().map_err(|err| err.to_string())?;
+    Ok(registry)
+}
+
+/// Generates the updated project contents
+fn export_project(path: &Path, registry: &Registry) -> Result<(String, String), String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut project =
+        serde_json::from_str::<serde_json::Value>(&contents).map_err(|err| err.to_string())?;
+
+    update_project(&mut project, registry).map_err(|err| err.to_string())?;
+    let updated = write_project(&project).map_err(|err| err.to_string())?;
+
+    Ok((contents, updated))
+}
+
+fn main() -> ExitCode {
+    let mut registry_path = DEFAULT_REGISTRY_PATH.to_owned();
+    let mut project_path = None;
+    let mut check = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--registry" {
+            let Some(path) = args.next() else {
+                eprintln!("{}", USAGE);
+                return ExitCode::from(2);
+            };
+            registry_path = path;
+        } else if arg == "--check" {
+            check = true;
+        } else if project_path.is_none() {
+            project_path = Some(arg);
+        } else {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    }
+    let project_path = project_path.unwrap_or_else(|| DEFAULT_PROJECT_PATH.to_owned());
+
+    let registry = match load_registry(Path::new(&registry_path)) {
+        Ok(registry) => registry,
+        Err(err) => {
+            eprintln!("{}: could not load registry: {}", registry_path, err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let (contents, updated) = match export_project(Path::new(&project_path), &registry) {
+        Ok(project) => project,
+        Err(err) => {
+            eprintln!("{}: could not update project: {}", project_path, err);
+            return ExitCode::from(2);
+        }
+    };
+
+    if contents == updated {
+        println!("{}: up to date", project_path);
+        return ExitCode::SUCCESS;
+    }
+
+    if check {
+        println!("{}: out of date", project_path);
+        return ExitCode::FAILURE;
+    }
+
+    if let Err(err) = std::fs::write(&project_path, updated) {
+        eprintln!("{}: could not write project: {}", project_path, err);
+        return ExitCode::from(2);
+    }
+
+    println!("{}: updated", project_path);
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,92 @@
+//! Headless Tiled map validator
+//!
+//! Checks TMX maps against the same rules the game uses when loading them
+//!
+//! Usage: validate-map [--registry <game.registry.ron>] <map.tmx>...
+
+#![deny(warnings)]
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use bevy_jam_factory::{assets::registry::Registry, tiled::validate_map};
+
+const USAGE: &str = "Usage: validate-map [--registry <game.registry.ron>] <map.tmx>...";
+const DEFAULT_REGISTRY_PATH: &str = "assets/game.registry.ron";
+
+/// Loads the Resource and Item definitions that maps are checked against
+fn load_registry(path: &Path) -> Result<Registry, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let registry = ron::from_str::<Registry>(&contents).map_err(|err| err.to_string())?;
+    registry.validate().map_err(|err| err.to_string())?;
+    Ok(registry)
+}
+
+/// Validates a single map, printing a report
+///
+/// Returns true if the map is valid
+fn validate_map_file(path: &Path, registry: &Registry) -> bool {
+    let mut loader = tiled::Loader::new();
+    let map = match loader.load_tmx_map(path) {
+        Ok(map) => map,
+        Err(err) => {
+            println!("{}: could not load TMX map: {}", path.display(), err);
+            return false;
+        }
+    };
+
+    let errors = validate_map(&map, registry);
+    if errors.is_empty() {
+        println!("{}: OK", path.display());
+        return true;
+    }
+
+    println!("{}: {} error(s)", path.display(), errors.len());
+    for error in errors {
+        println!("  {}", error);
+    }
+
+    false
+}
+
+fn main() -> ExitCode {
+    let mut registry_path = DEFAULT_REGISTRY_PATH.to_owned();
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--registry" {
+            let Some(path) = args.next() else {
+                eprintln!("{}", USAGE);
+                return ExitCode::from(2);
+            };
+            registry_path = path;
+        } else {
+            paths.push(arg);
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    }
+
+    let registry = match load_registry(Path::new(&registry_path)) {
+        Ok(registry) => registry,
+        Err(err) => {
+            eprintln!("{}: could not load registry: {}", registry_path, err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut valid = true;
+    for path in paths {
+        valid &= validate_map_file(Path::new(&path), &registry);
+    }
+
+    if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
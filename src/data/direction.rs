@@ -0,0 +1,108 @@
+//! Cardinal directions for directional Items (Conveyors, etc)
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::{helpers::square_grid::neighbors::SquareDirection, prelude::*};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::Display,
+    strum::EnumIter,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+pub enum Direction {
+    North,
+    #[default]
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    #[inline]
+    pub fn rotate_clockwise(&self) -> Self {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    #[inline]
+    pub fn opposite(&self) -> Self {
+        self.rotate_clockwise().rotate_clockwise()
+    }
+
+    /// Gets the direction an East-facing tile faces after a clockwise rotation (in degrees)
+    ///
+    /// Only quarter turns are supported
+    #[inline]
+    pub fn from_rotation(degrees: f32) -> Option<Self> {
+        if degrees % 90.0 != 0.0 {
+            return None;
+        }
+
+        let quarter_turns = (degrees / 90.0).rem_euclid(4.0) as usize;
+        Some((0..quarter_turns).fold(Self::East, |direction, _| direction.rotate_clockwise()))
+    }
+
+    /// Gets the clockwise rotation (in degrees) that turns an East-facing tile in this direction
+    #[inline]
+    pub fn rotation(&self) -> f32 {
+        match self {
+            Self::North => 270.0,
+            Self::East => 0.0,
+            Self::South => 90.0,
+            Self::West => 180.0,
+        }
+    }
+
+    /// Gets the neighboring tile position in this direction, if it's on the map
+    #[inline]
+    pub fn offset(&self, position: &TilePos, map_size: &TilemapSize) -> Option<TilePos> {
+        position.square_offset(&SquareDirection::from(*self), map_size)
+    }
+
+    /// Gets the tile flip that orients an East-facing tile in this direction
+    #[inline]
+    pub fn tile_flip(&self) -> TileFlip {
+        match self {
+            Self::North => TileFlip {
+                x: false,
+                y: true,
+                d: true,
+            },
+            Self::East => TileFlip::default(),
+            Self::South => TileFlip {
+                x: true,
+                y: false,
+                d: true,
+            },
+            Self::West => TileFlip {
+                x: true,
+                y: true,
+                d: false,
+            },
+        }
+    }
+}
+
+impl From<Direction> for SquareDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::North => SquareDirection::North,
+            Direction::East => SquareDirection::East,
+            Direction::South => SquareDirection::South,
+            Direction::West => SquareDirection::West,
+        }
+    }
+}
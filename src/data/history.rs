@@ -0,0 +1,186 @@
+//! Undo / redo history
+//!
+//! Every player action that changes the world (placing an Item, deconstructing Items)
+//! is recorded as the list of changes it made. Changes know how to invert themselves,
+//! so undoing an action is applying its inverted changes in reverse order.
+//!
+//! Removed Tiles, Objects, and Items are recorded with everything needed to put them back
+//! the way they were, the same as they would be saved.
+
+use std::collections::VecDeque;
+
+use bevy_ecs_tilemap::prelude::*;
+
+use super::items::ItemType;
+use super::save::{SavedItem, SavedObject};
+use super::tiles::TileData;
+
+/// How many actions can be undone
+pub const HISTORY_LIMIT: usize = 100;
+
+/// A Tile layer tile
+#[derive(Debug, Clone)]
+pub struct SavedTile {
+    pub position: TilePos,
+
+    /// The tileset the texture index is from
+    pub tileset: String,
+
+    pub texture_index: u32,
+    pub flip: TileFlip,
+    pub visible: bool,
+    pub data: TileData,
+}
+
+/// A single reversible change to the world
+#[derive(Debug, Clone)]
+pub enum Change {
+    SpawnTile(SavedTile),
+    DespawnTile(SavedTile),
+    SpawnObject(SavedObject),
+    DespawnObject(SavedObject),
+    SpawnItem(SavedItem),
+    DespawnItem(SavedItem),
+
+    /// Items added to (or removed from) the Inventory
+    Inventory(ItemType, i32),
+}
+
+impl Change {
+    /// Gets the change that reverses this one
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::SpawnTile(tile) => Self::DespawnTile(tile.clone()),
+            Self::DespawnTile(tile) => Self::SpawnTile(tile.clone()),
+            Self::SpawnObject(object) => Self::DespawnObject(object.clone()),
+            Self::DespawnObject(object) => Self::SpawnObject(object.clone()),
+            Self::SpawnItem(item) => Self::DespawnItem(item.clone()),
+            Self::DespawnItem(item) => Self::SpawnItem(item.clone()),
+            Self::Inventory(item_type, amount) => Self::Inventory(*item_type, -amount),
+        }
+    }
+}
+
+/// A player action, undone and redone as a unit
+#[derive(Debug, Clone)]
+pub struct Action {
+    /// What the action was, for logging
+    pub name: String,
+
+    /// Changes in the order they were made
+    pub changes: Vec<Change>,
+}
+
+impl Action {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            changes: Vec::default(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    #[inline]
+    pub fn push(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    /// Gets the action that reverses this one
+    pub fn inverse(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            changes: self.changes.iter().rev().map(Change::inverse).collect(),
+        }
+    }
+}
+
+/// Actions that can be undone and redone
+///
+/// Recording a new action drops everything that could be redone
+#[derive(Debug, Default)]
+pub struct ActionHistory {
+    undo: VecDeque<Action>,
+    redo: Vec<Action>,
+}
+
+impl ActionHistory {
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Records a new action
+    ///
+    /// Empty actions aren't recorded
+    pub fn record(&mut self, action: Action) {
+        if action.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+        self.push_undo(action);
+    }
+
+    /// Takes the most recent action to undo
+    ///
+    /// Apply its inverse, then return what was applied with undone()
+    #[inline]
+    pub fn pop_undo(&mut self) -> Option<Action> {
+        self.undo.pop_back()
+    }
+
+    /// Takes the most recently undone action to redo
+    ///
+    /// Apply it, then return what was applied with redone()
+    #[inline]
+    pub fn pop_redo(&mut self) -> Option<Action> {
+        self.redo.pop()
+    }
+
+    /// Puts an action that couldn't be undone back
+    #[inline]
+    pub fn cancel_undo(&mut self, action: Action) {
+        self.undo.push_back(action);
+    }
+
+    /// Puts an action that couldn't be redone back
+    #[inline]
+    pub fn cancel_redo(&mut self, action: Action) {
+        self.redo.push(action);
+    }
+
+    /// Makes an undone action available to redo
+    ///
+    /// Takes the inverse that was applied
+    #[inline]
+    pub fn undone(&mut self, applied: &Action) {
+        self.redo.push(applied.inverse());
+    }
+
+    /// Makes a redone action available to undo
+    #[inline]
+    pub fn redone(&mut self, applied: Action) {
+        self.push_undo(applied);
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push_undo(&mut self, action: Action) {
+        self.undo.push_back(action);
+        while self.undo.len() > HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+}
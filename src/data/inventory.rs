@@ -3,24 +3,22 @@
 use std::collections::HashMap;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use super::items::ItemType;
 use super::resources::ResourceType;
+use crate::assets::registry::Registry;
 use crate::plugins::game::inventory::InventoryUpdatedEvent;
 
-#[derive(Debug, Default, Reflect)]
+#[derive(Debug, Default, Clone, Reflect, Serialize, Deserialize)]
 pub struct InventoryData {
     resources: HashMap<ResourceType, u32>,
     items: HashMap<ItemType, u32>,
 }
 
 impl InventoryData {
-    // TODO: temp hack to get some stuff in the inventory for testing
-    pub fn new_test() -> Self {
-        Self {
-            resources: HashMap::from([(ResourceType::Iron, 100)]),
-            items: HashMap::from([(ItemType::Harvester, 1), (ItemType::Conveyor, 10)]),
-        }
+    pub fn new(resources: HashMap<ResourceType, u32>, items: HashMap<ItemType, u32>) -> Self {
+        Self { resources, items }
     }
 
     #[inline]
@@ -33,18 +31,24 @@ impl InventoryData {
         &self.items
     }
 
-    /// Adds an Item to the Inventory
+    /// Adds an Item to the Inventory, up to the Item stack size
     ///
     /// Sends an InventoryUpdatedEvent event after adding the Item
-    #[allow(dead_code)]
+    /// Returns false if the Inventory is already holding a full stack
     pub fn add_item(
         &mut self,
         item_type: ItemType,
+        registry: &Registry,
         inventory_updated_events: &mut EventWriter<InventoryUpdatedEvent>,
-    ) {
+    ) -> bool {
         let amount = self.items.entry(item_type).or_default();
+        if *amount >= registry.get_item(item_type).stack_size {
+            return false;
+        }
+
         *amount += 1;
         inventory_updated_events.send_default();
+        true
     }
 
     /// Removes an Item from the Inventory
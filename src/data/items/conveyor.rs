@@ -1,10 +1,21 @@
 //! Conveyor game Item
 
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
 use super::{ItemData, ItemType};
+use crate::data::{direction::Direction, resources::ResourceType};
 
-#[allow(dead_code)]
-#[derive(Debug)]
-pub struct ConveyorData {}
+/// Number of resources a single Conveyor can hold
+pub const CONVEYOR_CAPACITY: usize = 4;
+
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
+pub struct ConveyorData {
+    pub direction: Direction,
+    slots: VecDeque<ResourceType>,
+}
 
 impl ItemData for ConveyorData {
     #[inline]
@@ -13,4 +24,44 @@ impl ItemData for ConveyorData {
     }
 }
 
-impl ConveyorData {}
+impl ConveyorData {
+    pub fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            slots: VecDeque::with_capacity(CONVEYOR_CAPACITY),
+        }
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.slots.len() >= CONVEYOR_CAPACITY
+    }
+
+    #[inline]
+    pub fn free_slots(&self) -> usize {
+        CONVEYOR_CAPACITY.saturating_sub(self.slots.len())
+    }
+
+    /// Adds a resource to the back of the Conveyor
+    ///
+    /// Returns false if the Conveyor is full
+    pub fn push(&mut self, resource_type: ResourceType) -> bool {
+        if self.is_full() {
+            return false;
+        }
+
+        self.slots.push_back(resource_type);
+        true
+    }
+
+    /// Gets the resource at the front of the Conveyor
+    #[inline]
+    pub fn peek(&self) -> Option<ResourceType> {
+        self.slots.front().copied()
+    }
+
+    /// Removes the resource at the front of the Conveyor
+    pub fn pop(&mut self) -> Option<ResourceType> {
+        self.slots.pop_front()
+    }
+}
@@ -1,10 +1,29 @@
 //! Crafter game Item
 
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
 use super::{ItemData, ItemType};
+use crate::data::{recipes::Recipe, resources::ResourceType};
+
+/// Number of crafts worth of inputs a Crafter will buffer
+const CRAFTER_INPUT_CRAFTS: u32 = 2;
+
+/// Number of crafts worth of outputs a Crafter will buffer
+const CRAFTER_OUTPUT_CRAFTS: u32 = 2;
 
-#[allow(dead_code)]
-#[derive(Debug)]
-pub struct CrafterData {}
+#[derive(Debug, Default, Clone, Reflect, Serialize, Deserialize)]
+pub struct CrafterData {
+    recipe: Option<Recipe>,
+    inputs: HashMap<ResourceType, u32>,
+
+    /// Seconds into the current craft, if crafting
+    progress: Option<f32>,
+
+    pub output: u32,
+}
 
 impl ItemData for CrafterData {
     #[inline]
@@ -13,4 +32,99 @@ impl ItemData for CrafterData {
     }
 }
 
-impl CrafterData {}
+impl CrafterData {
+    pub fn new(
+        recipe: Option<Recipe>,
+        inputs: HashMap<ResourceType, u32>,
+        progress: Option<f32>,
+        output: u32,
+    ) -> Self {
+        Self {
+            recipe,
+            inputs,
+            progress,
+            output,
+        }
+    }
+
+    #[inline]
+    pub fn get_recipe(&self) -> Option<&Recipe> {
+        self.recipe.as_ref()
+    }
+
+    /// Changes the selected Recipe
+    ///
+    /// This discards any buffered inputs and the current craft
+    pub fn set_recipe(&mut self, recipe: Option<Recipe>) {
+        self.recipe = recipe;
+        self.inputs.clear();
+        self.progress = None;
+    }
+
+    #[inline]
+    pub fn is_crafting(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    #[inline]
+    pub fn get_output_type(&self) -> Option<ResourceType> {
+        self.recipe.as_ref().map(|recipe| recipe.output)
+    }
+
+    /// Checks if the Crafter has room for the given resource
+    pub fn accepts(&self, resource_type: ResourceType) -> bool {
+        let Some(recipe) = &self.recipe else {
+            return false;
+        };
+
+        let needed = recipe.get_input_amount(resource_type);
+        let current = self.inputs.get(&resource_type).copied().unwrap_or_default();
+        current < needed * CRAFTER_INPUT_CRAFTS
+    }
+
+    /// Adds an input resource to the Crafter
+    ///
+    /// Returns false if the Crafter can't accept the resource
+    pub fn push(&mut self, resource_type: ResourceType) -> bool {
+        if !self.accepts(resource_type) {
+            return false;
+        }
+
+        *self.inputs.entry(resource_type).or_default() += 1;
+        true
+    }
+
+    /// Advances crafting by the given number of seconds
+    ///
+    /// Returns true if a craft finished
+    pub fn craft(&mut self, dt: f32) -> bool {
+        let Some(recipe) = &self.recipe else {
+            return false;
+        };
+
+        let Some(progress) = &mut self.progress else {
+            // try and start a new craft
+            let has_room =
+                self.output + recipe.output_amount <= recipe.output_amount * CRAFTER_OUTPUT_CRAFTS;
+            let has_inputs = recipe.inputs.iter().all(|(resource_type, amount)| {
+                self.inputs.get(resource_type).copied().unwrap_or_default() >= *amount
+            });
+            if has_room && has_inputs {
+                for (resource_type, amount) in &recipe.inputs {
+                    *self.inputs.entry(*resource_type).or_default() -= amount;
+                }
+                self.progress = Some(0.0);
+            }
+            return false;
+        };
+
+        *progress += dt;
+        if *progress < recipe.duration {
+            return false;
+        }
+
+        self.progress = None;
+        self.output += recipe.output_amount;
+        true
+    }
+}
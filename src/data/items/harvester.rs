@@ -1,10 +1,17 @@
 //! Harvester game Item
 
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
 use super::{ItemData, ItemType, ObjectData};
+use crate::data::resources::ResourceType;
+
+/// How many harvested units a Harvester holds before it stops harvesting
+pub const HARVESTER_CAPACITY: u32 = 10;
 
-#[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
 pub struct HarvesterData {
+    pub r#type: ResourceType,
     pub current: u32,
     pub remaining: u32,
 }
@@ -20,7 +27,8 @@ impl From<&ObjectData> for HarvesterData {
     fn from(object: &ObjectData) -> Self {
         // TODO: should be an if let when we have more branches
         match object {
-            ObjectData::Resources { amount, .. } => Self {
+            ObjectData::Resources { r#type, amount, .. } => Self {
+                r#type: *r#type,
                 current: 0,
                 remaining: *amount,
             },
@@ -32,3 +40,29 @@ impl From<&ObjectData> for HarvesterData {
         }
     }
 }
+
+impl HarvesterData {
+    #[inline]
+    pub fn is_depleted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.current >= HARVESTER_CAPACITY
+    }
+
+    /// Moves up to rate units from the remaining amount into the current amount
+    ///
+    /// Nothing is harvested while the current amount is at capacity
+    ///
+    /// Returns the number of units harvested
+    pub fn harvest(&mut self, rate: u32) -> u32 {
+        let amount = rate
+            .min(self.remaining)
+            .min(HARVESTER_CAPACITY.saturating_sub(self.current));
+        self.remaining -= amount;
+        self.current += amount;
+        amount
+    }
+}
@@ -6,24 +6,53 @@ pub mod crafter;
 pub mod harvester;
 
 use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use super::direction::Direction;
 use super::inventory::InventoryData;
-use super::objects::{ObjectData, ObjectType};
+use super::objects::ObjectData;
+use super::registry::Placement;
 use crate::plugins::game::{inventory::InventoryUpdatedEvent, items::SpawnItemEvent};
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, strum::EnumString, strum::Display, Reflect)]
+use crate::tilemap::TileFootprint;
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+    strum::EnumIter,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
 pub enum ItemType {
     Harvester,
     Conveyor,
     Crafter,
 }
 
+// Item behaviour lives in code, everything else about an Item
+// (name, sprite, placement, stack size) is in the Registry
 impl ItemType {
-    /// Checks to see if this Item can be dropped on the given Object
-    pub fn can_drop_on_object(&self, object_type: ObjectType) -> bool {
+    /// Checks that this Item's behaviour works with the given placement
+    pub fn supports_placement(&self, placement: &Placement) -> bool {
+        match self {
+            Self::Harvester => matches!(placement, Placement::Objects(_)),
+            Self::Conveyor | Self::Crafter => *placement == Placement::Tiles,
+        }
+    }
+
+    /// Checks that this Item's behaviour works with the given footprint
+    pub fn supports_footprint(&self, footprint: &TileFootprint) -> bool {
         match self {
-            Self::Harvester => object_type == ObjectType::Resources,
-            Self::Conveyor | Self::Crafter => false,
+            // conveyors only feed the single tile they're facing
+            Self::Conveyor => *footprint == TileFootprint::default(),
+            Self::Harvester | Self::Crafter => true,
         }
     }
 
@@ -35,18 +64,23 @@ impl ItemType {
     /// # Panics
     ///
     /// This will panic if this Item is dropped on an invalid Object
-    // TODO: pass in state to know where we were dropped
     pub fn on_drop_object(
         &self,
         inventory: &mut InventoryData,
         inventory_updated_events: &mut EventWriter<InventoryUpdatedEvent>,
         object: &ObjectData,
+        position: TilePos,
+        footprint: TileFootprint,
         spawn_item_events: &mut EventWriter<SpawnItemEvent>,
     ) -> bool {
         let replace = match self {
             Self::Harvester => {
                 let harvester_data = harvester::HarvesterData::from(object);
-                spawn_item_events.send(SpawnItemEvent::Harvester(harvester_data));
+                spawn_item_events.send(SpawnItemEvent::Harvester(
+                    position,
+                    footprint,
+                    harvester_data,
+                ));
 
                 true
             }
@@ -58,15 +92,6 @@ impl ItemType {
         replace
     }
 
-    // TODO: pass in state to determine if we can drop here
-    pub fn can_drop_on_tile(&self) -> bool {
-        match self {
-            Self::Conveyor => true,
-            Self::Crafter => true,
-            Self::Harvester => false,
-        }
-    }
-
     /// Creates an instance of this Item in the game at a Tile
     ///
     /// Removes an instance of this Item from the Inventory
@@ -75,21 +100,27 @@ impl ItemType {
     /// # Panics
     ///
     /// This will panic if this Item is dropped on an invalid Tile
+    #[allow(clippy::too_many_arguments)]
     pub fn on_drop_tile(
         &self,
         _commands: &mut Commands,
         inventory: &mut InventoryData,
         inventory_updated_events: &mut EventWriter<InventoryUpdatedEvent>,
+        position: TilePos,
+        footprint: TileFootprint,
+        direction: Direction,
         spawn_item_events: &mut EventWriter<SpawnItemEvent>,
     ) -> bool {
         let replace = match self {
             Self::Conveyor => {
-                spawn_item_events.send(SpawnItemEvent::Conveyor);
+                let conveyor_data = conveyor::ConveyorData::new(direction);
+                spawn_item_events.send(SpawnItemEvent::Conveyor(position, conveyor_data));
 
                 false
             }
             Self::Crafter => {
-                spawn_item_events.send(SpawnItemEvent::Crafter);
+                let crafter_data = crafter::CrafterData::default();
+                spawn_item_events.send(SpawnItemEvent::Crafter(position, footprint, crafter_data));
 
                 false
             }
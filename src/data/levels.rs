@@ -0,0 +1,24 @@
+//! Level definitions
+
+use serde::Deserialize;
+
+use super::inventory::InventoryData;
+
+/// A playable map
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelDefinition {
+    /// Shown on the level select screen
+    pub name: String,
+
+    /// Map asset path, unused by generated levels
+    #[serde(default)]
+    pub map: String,
+
+    /// The map is generated from a new random seed every time the level is started
+    #[serde(default)]
+    pub generated: bool,
+
+    /// What the Inventory starts with
+    #[serde(default)]
+    pub inventory: InventoryData,
+}
@@ -1,4 +1,11 @@
+pub mod direction;
+pub mod history;
 pub mod inventory;
 pub mod items;
+pub mod levels;
 pub mod objects;
+pub mod recipes;
+pub mod registry;
 pub mod resources;
+pub mod save;
+pub mod tiles;
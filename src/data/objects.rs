@@ -4,20 +4,45 @@
 
 use std::str::FromStr;
 
+use serde::{Deserialize, Serialize};
+
+use super::direction::Direction;
 use super::resources::ResourceType;
-use crate::tiled::{require_object_int_property, require_object_string_property};
+use crate::tiled::{get_enum_property_value, require_property, FromTiledProperty};
+
+pub const RESOURCE_TYPE_PROPERTY: &str = "ResourceType";
+pub const AMOUNT_PROPERTY: &str = "Amount";
 
-#[derive(Debug, Clone, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    strum::EnumString,
+    strum::Display,
+    strum::EnumIter,
+    Deserialize,
+)]
 pub enum ObjectType {
     Resources,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, strum::Display)]
+impl FromTiledProperty for ObjectType {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        get_enum_property_value(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, strum::Display, Serialize, Deserialize)]
 pub enum ObjectData {
     Resources {
         id: u32,
         r#type: ResourceType,
         amount: u32,
+
+        /// The way the Object faces, from its Tiled rotation
+        direction: Direction,
     },
 }
 
@@ -32,23 +57,25 @@ impl ObjectData {
             )
         };
 
+        // unsupported rotations are reported by map validation
+        let direction = Direction::from_rotation(object.rotation).unwrap_or_default();
+
         match r#type {
             ObjectType::Resources => {
-                let resource_type = require_object_string_property(object, "ResourceType")?;
-                let Ok(resource_type) = ResourceType::from_str(resource_type) else {
-                    anyhow::bail!(
-                        "Resource {} has invalid ResourceType {:?}",
-                        object.id(),
-                        resource_type,
-                    )
-                };
+                // this is checked against the Registry during validation
+                let resource_type =
+                    require_property::<ResourceType>(&object.properties, RESOURCE_TYPE_PROPERTY)
+                        .map_err(|err| anyhow::anyhow!("Object {} {}", object.id(), err))?;
 
-                let amount = require_object_int_property(object, "Amount")?.max(0);
+                let amount = require_property::<i32>(&object.properties, AMOUNT_PROPERTY)
+                    .map_err(|err| anyhow::anyhow!("Object {} {}", object.id(), err))?
+                    .max(0);
 
                 Ok(Self::Resources {
                     id: object.id(),
                     r#type: resource_type,
                     amount: amount as u32,
+                    direction,
                 })
             }
         }
@@ -61,6 +88,20 @@ impl ObjectData {
         }
     }
 
+    #[inline]
+    pub fn get_direction(&self) -> Direction {
+        match self {
+            Self::Resources { direction, .. } => *direction,
+        }
+    }
+
+    #[inline]
+    pub fn set_direction(&mut self, new_direction: Direction) {
+        match self {
+            Self::Resources { direction, .. } => *direction = new_direction,
+        }
+    }
+
     #[inline]
     pub fn get_type(&self) -> ObjectType {
         match self {
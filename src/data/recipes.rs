@@ -0,0 +1,37 @@
+//! Crafting recipes
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::resources::ResourceType;
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+
+    /// Resources consumed per-craft
+    pub inputs: HashMap<ResourceType, u32>,
+
+    pub output: ResourceType,
+
+    /// Resources produced per-craft
+    #[serde(default = "default_output_amount")]
+    pub output_amount: u32,
+
+    /// Craft duration in seconds
+    pub duration: f32,
+}
+
+fn default_output_amount() -> u32 {
+    1
+}
+
+impl Recipe {
+    /// Gets how many of the given resource a single craft consumes
+    #[inline]
+    pub fn get_input_amount(&self, resource_type: ResourceType) -> u32 {
+        self.inputs.get(&resource_type).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,143 @@
+//! Resource and Item definitions
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::items::ItemType;
+use super::objects::ObjectType;
+use super::resources::ResourceType;
+use super::tiles::{Terrain, TileData};
+use crate::tilemap::{TileAnimation, TileAnimationFrame, TileFootprint};
+
+/// A single frame of a sprite animation
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub struct SpriteFrame {
+    /// Sprite index into the sprite sheet
+    pub index: u32,
+
+    /// How long the frame is shown, in seconds
+    pub duration: f32,
+}
+
+/// A single sprite out of a sprite sheet
+///
+/// Sprite sheets are a grid of TILE_WIDTH x TILE_HEIGHT sprites
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpriteDefinition {
+    /// Sprite sheet asset path
+    pub image: String,
+    pub columns: u32,
+    pub rows: u32,
+
+    /// Sprite index into the sprite sheet
+    #[serde(default)]
+    pub index: u32,
+
+    /// Frames to cycle through while the sprite is in the world
+    #[serde(default)]
+    pub animation: Vec<SpriteFrame>,
+
+    #[serde(skip)]
+    pub image_handle: Handle<Image>,
+
+    #[serde(skip)]
+    pub atlas_handle: Handle<TextureAtlasLayout>,
+}
+
+impl SpriteDefinition {
+    /// Gets the animation for the sprite, if it has one
+    pub fn get_animation(&self) -> Option<TileAnimation> {
+        TileAnimation::new(
+            self.index,
+            self.animation
+                .iter()
+                .map(|frame| TileAnimationFrame {
+                    texture_index: frame.index,
+                    duration: Duration::from_secs_f32(frame.duration),
+                })
+                .collect(),
+        )
+    }
+
+    /// Checks that every sprite index fits in the sprite sheet
+    /// and every frame has a valid duration
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        let count = self.columns * self.rows;
+        self.index < count
+            && self.animation.iter().all(|frame| {
+                frame.index < count && frame.duration.is_finite() && frame.duration >= 0.0
+            })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResourceDefinition {
+    #[serde(deserialize_with = "ResourceType::deserialize_definition")]
+    pub id: ResourceType,
+    pub name: String,
+    pub sprite: SpriteDefinition,
+
+    /// Maximum amount of this Resource the Inventory can hold
+    pub stack_size: u32,
+}
+
+/// Where an Item can be placed
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum Placement {
+    /// On empty Tiles
+    Tiles,
+
+    /// Replacing Objects of the given types
+    Objects(Vec<ObjectType>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemDefinition {
+    pub id: ItemType,
+    pub name: String,
+    pub sprite: SpriteDefinition,
+
+    /// Maximum number of this Item the Inventory can hold
+    pub stack_size: u32,
+
+    pub placement: Placement,
+
+    /// The tiles covered when placed on Tiles
+    ///
+    /// Items placed on Objects cover the Object instead
+    #[serde(default)]
+    pub footprint: TileFootprint,
+
+    /// The Terrain the Item can be built on when placed on Tiles
+    #[serde(default = "default_item_terrain")]
+    pub terrain: Vec<Terrain>,
+}
+
+fn default_item_terrain() -> Vec<Terrain> {
+    vec![Terrain::Ground]
+}
+
+impl ItemDefinition {
+    /// Checks to see if this Item can be dropped on the given Object
+    #[inline]
+    pub fn can_drop_on_object(&self, object_type: ObjectType) -> bool {
+        match &self.placement {
+            Placement::Objects(object_types) => object_types.contains(&object_type),
+            Placement::Tiles => false,
+        }
+    }
+
+    #[inline]
+    pub fn can_drop_on_tile(&self) -> bool {
+        self.placement == Placement::Tiles
+    }
+
+    /// Checks to see if this Item can be built on the given Tile
+    #[inline]
+    pub fn can_build_on(&self, tile: &TileData) -> bool {
+        tile.buildable && self.terrain.contains(&tile.terrain)
+    }
+}
@@ -1,8 +1,117 @@
 //! Game Resources
 
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
 use bevy::prelude::*;
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::tiled::FromTiledProperty;
+
+/// Resource type id
+///
+/// Resource types are defined in the Registry so this is just an id.
+/// Ids are interned so that they can be copied around as cheaply as an enum.
+///
+/// Only the Registry defines new ids, everything else (saves, recipes, levels, maps)
+/// looks them up and rejects ids the Registry doesn't define
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub struct ResourceType(&'static str);
+
+fn ids() -> MutexGuard<'static, HashSet<&'static str>> {
+    static IDS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    IDS.get_or_init(Default::default).lock().unwrap()
+}
+
+impl ResourceType {
+    /// Defines a Resource type id
+    ///
+    /// Ids are leaked to make them 'static, so this is only for the Registry
+    pub fn new(id: impl AsRef<str>) -> Self {
+        let id = id.as_ref();
+        let mut ids = ids();
+        if let Some(id) = ids.get(id) {
+            return Self(id);
+        }
+
+        // there are only ever as many of these as the Registry defines
+        let id: &'static str = Box::leak(id.to_owned().into_boxed_str());
+        ids.insert(id);
+        Self(id)
+    }
+
+    /// Looks up a Resource type id defined by the Registry
+    pub fn get(id: impl AsRef<str>) -> Option<Self> {
+        ids().get(id.as_ref()).copied().map(Self)
+    }
+
+    /// Deserializes an id as a definition of a new Resource type
+    pub fn deserialize_definition<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::new)
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// Resource types are Tiled enums generated from the Registry
+impl FromTiledProperty for ResourceType {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        let tiled::PropertyValue::StringValue(id) = value else {
+            anyhow::bail!("expected ResourceType, got {:?}", value);
+        };
+
+        if id.is_empty() {
+            anyhow::bail!("ResourceType is empty");
+        }
+
+        Self::get(id).ok_or_else(|| anyhow::anyhow!("unknown ResourceType {}", id))
+    }
+}
+
+impl std::fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Serialize for ResourceType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ResourceType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ResourceTypeVisitor;
+
+        impl<'de> Visitor<'de> for ResourceTypeVisitor {
+            type Value = ResourceType;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a resource type id")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                ResourceType::get(value)
+                    .ok_or_else(|| E::custom(format!("unknown resource type {}", value)))
+            }
+        }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, strum::EnumString, strum::Display, Reflect)]
-pub enum ResourceType {
-    Iron,
+        deserializer.deserialize_str(ResourceTypeVisitor)
+    }
 }
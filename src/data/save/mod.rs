@@ -0,0 +1,201 @@
+//! Saved game data
+//!
+//! Every save carries the version of the schema it was written with.
+//! Older saves are parsed with their frozen schema (see the v* modules)
+//! and migrated forward one version at a time until they reach the current schema.
+//!
+//! To change the shape of a save:
+//!
+//! * Copy the current SaveData into a new v* module
+//! * Saved types are read with the copy in the oldest v* module they have the same shape in,
+//!   copy any nested types that don't have one yet so they never follow changes to the live types
+//! * Add a migrate() from that version to the next
+//! * Bump SAVE_VERSION and add the new version to SaveData::parse()
+//! * Add a fixture save for the new version to tests/fixtures/saves
+
+pub mod v1;
+pub mod v2;
+pub mod v3;
+pub mod v4;
+pub mod v5;
+
+use std::path::Path;
+
+use bevy_ecs_tilemap::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::direction::Direction;
+use super::inventory::InventoryData;
+use super::items::{
+    conveyor::ConveyorData, crafter::CrafterData, harvester::HarvesterData, ItemType,
+};
+use super::objects::ObjectData;
+use crate::tilemap::TileFootprint;
+
+/// The current save format version
+///
+/// Bump this whenever the shape of the saved data changes
+pub const SAVE_VERSION: u32 = 6;
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    /// An [IO](std::io) Error
+    #[error("Could not access save file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A [RON](ron) serialization Error
+    #[error("Could not write save: {0}")]
+    Serialize(#[from] ron::Error),
+
+    /// A [RON](ron) deserialization Error
+    #[error("Could not parse save: {0}")]
+    Deserialize(#[from] ron::error::SpannedError),
+
+    #[error("Save version {0} is not supported (current version is {SAVE_VERSION})")]
+    UnsupportedVersion(u32),
+}
+
+/// A saved Object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedObject {
+    pub position: TilePos,
+    pub footprint: TileFootprint,
+
+    /// The tileset the texture index is from
+    ///
+    /// None for saves from before layers could mix tilesets,
+    /// these use the first tileset of the Object layer
+    pub tileset: Option<String>,
+
+    pub texture_index: u32,
+    pub flip: TileFlip,
+    pub visible: bool,
+    pub data: ObjectData,
+}
+
+/// A saved Item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SavedItem {
+    Harvester {
+        position: TilePos,
+        footprint: TileFootprint,
+        data: HarvesterData,
+    },
+    Conveyor {
+        position: TilePos,
+        data: ConveyorData,
+    },
+    Crafter {
+        position: TilePos,
+        footprint: TileFootprint,
+        data: CrafterData,
+    },
+}
+
+impl SavedItem {
+    #[inline]
+    pub fn get_type(&self) -> ItemType {
+        match self {
+            Self::Harvester { .. } => ItemType::Harvester,
+            Self::Conveyor { .. } => ItemType::Conveyor,
+            Self::Crafter { .. } => ItemType::Crafter,
+        }
+    }
+
+    #[inline]
+    pub fn get_position(&self) -> TilePos {
+        match self {
+            Self::Harvester { position, .. }
+            | Self::Conveyor { position, .. }
+            | Self::Crafter { position, .. } => *position,
+        }
+    }
+
+    /// Gets the tiles covered by the Item
+    ///
+    /// Conveyors always cover a single tile
+    #[inline]
+    pub fn get_footprint(&self) -> TileFootprint {
+        match self {
+            Self::Harvester { footprint, .. } | Self::Crafter { footprint, .. } => *footprint,
+            Self::Conveyor { .. } => TileFootprint::default(),
+        }
+    }
+}
+
+/// Just enough of a save to know how to parse the rest of it
+#[derive(Debug, Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+/// Everything needed to resume a game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+
+    /// Asset path of the map the game is played on
+    pub map: String,
+
+    pub inventory: InventoryData,
+    pub objects: Vec<SavedObject>,
+    pub items: Vec<SavedItem>,
+
+    /// Camera (x, y) translation
+    pub camera: (f32, f32),
+
+    /// The direction newly placed Items will face
+    pub placement_direction: Direction,
+}
+
+impl SaveData {
+    /// Parses a save of any supported version
+    ///
+    /// Older saves are migrated up to the current version
+    pub fn parse(contents: impl AsRef<str>) -> Result<Self, SaveError> {
+        let contents = contents.as_ref();
+
+        let version = ron::from_str::<SaveVersion>(contents)?.version;
+        let save = match version {
+            1 => ron::from_str::<v1::SaveData>(contents)?
+                .migrate()
+                .migrate()
+                .migrate()
+                .migrate()
+                .migrate(),
+            2 => ron::from_str::<v2::SaveData>(contents)?
+                .migrate()
+                .migrate()
+                .migrate()
+                .migrate(),
+            3 => ron::from_str::<v3::SaveData>(contents)?
+                .migrate()
+                .migrate()
+                .migrate(),
+            4 => ron::from_str::<v4::SaveData>(contents)?.migrate().migrate(),
+            5 => ron::from_str::<v5::SaveData>(contents)?.migrate(),
+            SAVE_VERSION => ron::from_str::<Self>(contents)?,
+            _ => return Err(SaveError::UnsupportedVersion(version)),
+        };
+
+        Ok(save)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(contents)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, contents)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+//! Version 1 saves
+//!
+//! Version 2 added the placement direction
+//!
+//! Items and the Inventory haven't changed shape since version 1,
+//! so every version up to the current one reads them with the types here
+
+use std::collections::{HashMap, VecDeque};
+
+use bevy_ecs_tilemap::prelude::*;
+use serde::{
+    de::{DeserializeSeed, EnumAccess, VariantAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use super::v2::{self, SavedObject};
+use crate::data::{
+    direction::Direction,
+    inventory,
+    items::{conveyor, crafter, harvester, ItemType},
+    recipes, resources,
+};
+use crate::tilemap::TileFootprint;
+
+/// Resource type id, saved as a bare identifier (Iron, not "Iron")
+///
+/// Ids still have to be defined by the Registry
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct ResourceType(resources::ResourceType);
+
+impl ResourceType {
+    pub fn migrate(self) -> resources::ResourceType {
+        self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for ResourceType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ResourceTypeVisitor;
+
+        impl<'de> Visitor<'de> for ResourceTypeVisitor {
+            type Value = ResourceType;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a resource type id")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                resources::ResourceType::get(value)
+                    .map(ResourceType)
+                    .ok_or_else(|| E::custom(format!("unknown resource type {}", value)))
+            }
+
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: EnumAccess<'de>,
+            {
+                let (resource_type, variant) = data.variant_seed(ResourceTypeId)?;
+                variant.unit_variant()?;
+                Ok(resource_type)
+            }
+        }
+
+        struct ResourceTypeId;
+
+        impl<'de> DeserializeSeed<'de> for ResourceTypeId {
+            type Value = ResourceType;
+
+            fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_identifier(ResourceTypeVisitor)
+            }
+        }
+
+        // RON only reads bare identifiers everywhere as enum variants
+        deserializer.deserialize_enum("ResourceType", &[], ResourceTypeVisitor)
+    }
+}
+
+fn migrate_resources<T>(
+    resources: impl IntoIterator<Item = (ResourceType, T)>,
+) -> HashMap<resources::ResourceType, T> {
+    resources
+        .into_iter()
+        .map(|(resource_type, amount)| (resource_type.migrate(), amount))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InventoryData {
+    pub resources: HashMap<ResourceType, u32>,
+    pub items: HashMap<ItemType, u32>,
+}
+
+impl InventoryData {
+    pub fn migrate(self) -> inventory::InventoryData {
+        inventory::InventoryData::new(migrate_resources(self.resources), self.items)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarvesterData {
+    pub r#type: ResourceType,
+    pub current: u32,
+    pub remaining: u32,
+}
+
+impl HarvesterData {
+    pub fn migrate(self) -> harvester::HarvesterData {
+        harvester::HarvesterData {
+            r#type: self.r#type.migrate(),
+            current: self.current,
+            remaining: self.remaining,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConveyorData {
+    pub direction: Direction,
+    pub slots: VecDeque<ResourceType>,
+}
+
+impl ConveyorData {
+    pub fn migrate(self) -> conveyor::ConveyorData {
+        let mut data = conveyor::ConveyorData::new(self.direction);
+        for resource_type in self.slots {
+            data.push(resource_type.migrate());
+        }
+        data
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    pub inputs: HashMap<ResourceType, u32>,
+    pub output: ResourceType,
+    #[serde(default = "default_output_amount")]
+    pub output_amount: u32,
+    pub duration: f32,
+}
+
+fn default_output_amount() -> u32 {
+    1
+}
+
+impl Recipe {
+    pub fn migrate(self) -> recipes::Recipe {
+        recipes::Recipe {
+            name: self.name,
+            inputs: migrate_resources(self.inputs),
+            output: self.output.migrate(),
+            output_amount: self.output_amount,
+            duration: self.duration,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CrafterData {
+    pub recipe: Option<Recipe>,
+    pub inputs: HashMap<ResourceType, u32>,
+    pub progress: Option<f32>,
+    pub output: u32,
+}
+
+impl CrafterData {
+    pub fn migrate(self) -> crafter::CrafterData {
+        crafter::CrafterData::new(
+            self.recipe.map(Recipe::migrate),
+            migrate_resources(self.inputs),
+            self.progress,
+            self.output,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub enum SavedItem {
+    Harvester {
+        position: TilePos,
+        footprint: TileFootprint,
+        data: HarvesterData,
+    },
+    Conveyor {
+        position: TilePos,
+        data: ConveyorData,
+    },
+    Crafter {
+        position: TilePos,
+        footprint: TileFootprint,
+        data: CrafterData,
+    },
+}
+
+impl SavedItem {
+    pub fn migrate(self) -> super::SavedItem {
+        match self {
+            Self::Harvester {
+                position,
+                footprint,
+                data,
+            } => super::SavedItem::Harvester {
+                position,
+                footprint,
+                data: data.migrate(),
+            },
+            Self::Conveyor { position, data } => super::SavedItem::Conveyor {
+                position,
+                data: data.migrate(),
+            },
+            Self::Crafter {
+                position,
+                footprint,
+                data,
+            } => super::SavedItem::Crafter {
+                position,
+                footprint,
+                data: data.migrate(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveData {
+    pub inventory: InventoryData,
+    pub objects: Vec<SavedObject>,
+    pub items: Vec<SavedItem>,
+    pub camera: (f32, f32),
+}
+
+impl SaveData {
+    pub fn migrate(self) -> v2::SaveData {
+        v2::SaveData {
+            inventory: self.inventory,
+            objects: self.objects,
+            items: self.items,
+            camera: self.camera,
+            placement_direction: Direction::default(),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Version 2 saves
+//!
+//! Version 3 added the tileset of each Object
+
+use bevy_ecs_tilemap::prelude::*;
+use serde::Deserialize;
+
+use super::v1::{InventoryData, SavedItem};
+use super::v3::{self, ObjectData};
+use crate::data::direction::Direction;
+use crate::tilemap::TileFootprint;
+
+#[derive(Debug, Deserialize)]
+pub struct SavedObject {
+    pub position: TilePos,
+    pub footprint: TileFootprint,
+    pub texture_index: u32,
+    pub visible: bool,
+    pub data: ObjectData,
+}
+
+impl SavedObject {
+    pub fn migrate(self) -> v3::SavedObject {
+        v3::SavedObject {
+            position: self.position,
+            footprint: self.footprint,
+            // layers could only use a single tileset
+            tileset: None,
+            texture_index: self.texture_index,
+            visible: self.visible,
+            data: self.data,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveData {
+    pub inventory: InventoryData,
+    pub objects: Vec<SavedObject>,
+    pub items: Vec<SavedItem>,
+    pub camera: (f32, f32),
+    pub placement_direction: Direction,
+}
+
+impl SaveData {
+    pub fn migrate(self) -> v3::SaveData {
+        v3::SaveData {
+            inventory: self.inventory,
+            objects: self.objects.into_iter().map(SavedObject::migrate).collect(),
+            items: self.items,
+            camera: self.camera,
+            placement_direction: self.placement_direction,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Version 3 saves
+//!
+//! Version 4 added Object orientation (tile flip and facing direction)
+
+use bevy_ecs_tilemap::prelude::*;
+use serde::Deserialize;
+
+use super::v1::{InventoryData, ResourceType, SavedItem};
+use super::v4;
+use crate::data::direction::Direction;
+use crate::tilemap::TileFootprint;
+
+#[derive(Debug, Deserialize)]
+pub enum ObjectData {
+    Resources {
+        id: u32,
+        r#type: ResourceType,
+        amount: u32,
+    },
+}
+
+impl ObjectData {
+    pub fn migrate(self) -> v4::ObjectData {
+        match self {
+            Self::Resources { id, r#type, amount } => v4::ObjectData::Resources {
+                id,
+                r#type,
+                amount,
+                // Objects could not be rotated
+                direction: Direction::default(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavedObject {
+    pub position: TilePos,
+    pub footprint: TileFootprint,
+    pub tileset: Option<String>,
+    pub texture_index: u32,
+    pub visible: bool,
+    pub data: ObjectData,
+}
+
+impl SavedObject {
+    pub fn migrate(self) -> v4::SavedObject {
+        v4::SavedObject {
+            position: self.position,
+            footprint: self.footprint,
+            tileset: self.tileset,
+            texture_index: self.texture_index,
+            flip: TileFlip::default(),
+            visible: self.visible,
+            data: self.data.migrate(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveData {
+    pub inventory: InventoryData,
+    pub objects: Vec<SavedObject>,
+    pub items: Vec<SavedItem>,
+    pub camera: (f32, f32),
+    pub placement_direction: Direction,
+}
+
+impl SaveData {
+    pub fn migrate(self) -> v4::SaveData {
+        v4::SaveData {
+            inventory: self.inventory,
+            objects: self.objects.into_iter().map(SavedObject::migrate).collect(),
+            items: self.items,
+            camera: self.camera,
+            placement_direction: self.placement_direction,
+        }
+    }
+}
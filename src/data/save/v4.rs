@@ -0,0 +1,89 @@
+//! Version 4 saves
+//!
+//! Version 5 added the map the game was played on
+
+use bevy_ecs_tilemap::prelude::*;
+use serde::Deserialize;
+
+use super::v1::{InventoryData, ResourceType, SavedItem};
+use super::v5;
+use crate::data::{direction::Direction, objects};
+use crate::tilemap::TileFootprint;
+
+/// The only map there was before levels
+const MAP: &str = "map.tmx";
+
+#[derive(Debug, Deserialize)]
+pub enum ObjectData {
+    Resources {
+        id: u32,
+        r#type: ResourceType,
+        amount: u32,
+        direction: Direction,
+    },
+}
+
+impl ObjectData {
+    pub fn migrate(self) -> objects::ObjectData {
+        match self {
+            Self::Resources {
+                id,
+                r#type,
+                amount,
+                direction,
+            } => objects::ObjectData::Resources {
+                id,
+                r#type: r#type.migrate(),
+                amount,
+                direction,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavedObject {
+    pub position: TilePos,
+    pub footprint: TileFootprint,
+    pub tileset: Option<String>,
+    pub texture_index: u32,
+    pub flip: TileFlip,
+    pub visible: bool,
+    pub data: ObjectData,
+}
+
+impl SavedObject {
+    pub fn migrate(self) -> super::SavedObject {
+        super::SavedObject {
+            position: self.position,
+            footprint: self.footprint,
+            tileset: self.tileset,
+            texture_index: self.texture_index,
+            flip: self.flip,
+            visible: self.visible,
+            data: self.data.migrate(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveData {
+    pub inventory: InventoryData,
+    pub objects: Vec<SavedObject>,
+    pub items: Vec<SavedItem>,
+    pub camera: (f32, f32),
+    pub placement_direction: Direction,
+}
+
+impl SaveData {
+    pub fn migrate(self) -> v5::SaveData {
+        v5::SaveData {
+            map: MAP.to_owned(),
+            inventory: self.inventory,
+            objects: self.objects,
+            items: self.items,
+            camera: self.camera,
+            placement_direction: self.placement_direction,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Version 5 saves
+//!
+//! Version 6 saves Resource types as strings ("Iron", not Iron)
+
+use serde::Deserialize;
+
+use super::v1::{InventoryData, SavedItem};
+use super::v4::SavedObject;
+use crate::data::direction::Direction;
+
+#[derive(Debug, Deserialize)]
+pub struct SaveData {
+    pub map: String,
+    pub inventory: InventoryData,
+    pub objects: Vec<SavedObject>,
+    pub items: Vec<SavedItem>,
+    pub camera: (f32, f32),
+    pub placement_direction: Direction,
+}
+
+impl SaveData {
+    pub fn migrate(self) -> super::SaveData {
+        super::SaveData {
+            version: 6,
+            map: self.map,
+            inventory: self.inventory.migrate(),
+            objects: self.objects.into_iter().map(SavedObject::migrate).collect(),
+            items: self.items.into_iter().map(SavedItem::migrate).collect(),
+            camera: self.camera,
+            placement_direction: self.placement_direction,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Game world Tiles
+
+use serde::{Deserialize, Serialize};
+
+use crate::tiled::{get_enum_property_value, get_property, FromTiledClass, FromTiledProperty};
+
+pub const BUILDABLE_PROPERTY: &str = "Buildable";
+pub const TERRAIN_PROPERTY: &str = "Terrain";
+pub const MOVE_COST_PROPERTY: &str = "MoveCost";
+
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+    strum::EnumIter,
+    Serialize,
+    Deserialize,
+)]
+pub enum Terrain {
+    #[default]
+    Ground,
+    Water,
+}
+
+impl FromTiledProperty for Terrain {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        get_enum_property_value(value)
+    }
+}
+
+/// Tile data read from Tiled tileset tile properties
+///
+/// Tiles without properties are buildable Ground
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TileData {
+    /// Whether Items can be built on the Tile at all
+    pub buildable: bool,
+
+    pub terrain: Terrain,
+
+    /// Cost of moving across the Tile
+    pub move_cost: u32,
+}
+
+impl Default for TileData {
+    fn default() -> Self {
+        Self {
+            buildable: true,
+            terrain: Terrain::default(),
+            move_cost: 1,
+        }
+    }
+}
+
+impl FromTiledClass for TileData {
+    const CLASS: &'static str = "Tile";
+
+    fn from_properties(properties: &tiled::Properties) -> anyhow::Result<Self> {
+        let mut data = Self::default();
+
+        if let Some(buildable) = get_property(properties, BUILDABLE_PROPERTY)? {
+            data.buildable = buildable;
+        }
+
+        if let Some(terrain) = get_property(properties, TERRAIN_PROPERTY)? {
+            data.terrain = terrain;
+        }
+
+        if let Some(move_cost) = get_property::<i32>(properties, MOVE_COST_PROPERTY)? {
+            if move_cost < 1 {
+                anyhow::bail!("has invalid MoveCost {}", move_cost);
+            }
+            data.move_cost = move_cost as u32;
+        }
+
+        Ok(data)
+    }
+}
+
+impl TileData {
+    pub fn new(properties: &tiled::Properties) -> anyhow::Result<Self> {
+        Self::from_properties(properties).map_err(|err| anyhow::anyhow!("Tile {}", err))
+    }
+
+    /// Gets the data for a Tiled tile, tiles without properties use the default
+    pub fn from_tile(tile: Option<tiled::Tile>) -> anyhow::Result<Self> {
+        match tile {
+            Some(tile) => Self::new(&tile.properties),
+            None => Ok(Self::default()),
+        }
+    }
+}
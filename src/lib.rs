@@ -0,0 +1,43 @@
+#![deny(warnings)]
+
+pub mod assets;
+mod audio;
+pub mod data;
+pub mod plugins;
+pub mod tiled;
+mod tilemap;
+mod ui;
+
+pub use tilemap::TileFootprint;
+
+use bevy::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, Reflect)]
+pub enum AppState {
+    //#[default]
+    Splash,
+    #[default]
+    MainMenu,
+    LevelSelect,
+    LoadAssets,
+    InGame,
+}
+
+#[inline]
+pub fn get_world_position_from_cursor_position(
+    cursor_position: Option<Vec2>,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    cursor_position
+        .and_then(|cursor_position| camera.viewport_to_world_2d(camera_transform, cursor_position))
+}
+
+pub fn cleanup_state<T>(mut commands: Commands, query: Query<Entity, With<T>>)
+where
+    T: Component,
+{
+    for e in &query {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -1,53 +1,21 @@
 #![deny(warnings)]
 
-mod assets;
-mod audio;
-mod data;
-mod plugins;
-mod tiled;
-mod tilemap;
-mod ui;
-
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 use bevy_mod_picking::prelude::*;
 use bevy_simple_scroll_view::ScrollViewPlugin;
 use bevy_tweening::TweeningPlugin;
 
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, Reflect)]
-pub enum AppState {
-    //#[default]
-    Splash,
-    #[default]
-    MainMenu,
-    LoadAssets,
-    InGame,
-}
-
-#[inline]
-pub fn get_world_position_from_cursor_position(
-    cursor_position: Option<Vec2>,
-    camera: &Camera,
-    camera_transform: &GlobalTransform,
-) -> Option<Vec2> {
-    cursor_position
-        .and_then(|cursor_position| camera.viewport_to_world_2d(camera_transform, cursor_position))
-}
-
-pub fn cleanup_state<T>(mut commands: Commands, query: Query<Entity, With<T>>)
-where
-    T: Component,
-{
-    for e in &query {
-        commands.entity(e).despawn_recursive();
-    }
-}
+use bevy_jam_factory::{plugins, AppState};
 
 const DEFAULT_RESOLUTION: (f32, f32) = (1280.0, 720.0);
 
 fn main() {
     let mut app = App::new();
 
+    // asset sources have to be registered before the AssetPlugin
+    app.add_plugins(plugins::map_generator::MapGeneratorPlugin);
+
     // core plugins
     app.add_plugins(
         DefaultPlugins
@@ -90,10 +58,12 @@ fn main() {
         plugins::tiled::TiledMapPlugin,
         plugins::tiled_picking::TiledPickingBackend,
         plugins::ui::UiPlugin,
+        plugins::loading::LoadingPlugin,
         plugins::audio::AudioPlugin,
         plugins::game_ui::GameUiPlugin,
         plugins::splash::SplashPlugin,
         plugins::main_menu::MainMenuPlugin,
+        plugins::level_select::LevelSelectPlugin,
         plugins::pause_menu::PauseMenuPlugin,
         plugins::game::GamePlugin,
         plugins::debug::DebugPlugin,
@@ -16,14 +16,6 @@ pub struct AudioAssets {
     pub music: Handle<AudioSource>,
 }
 
-impl AudioAssets {
-    // TODO: well this kinda sucks
-    #[inline]
-    pub fn is_loaded(&self, audio_assets: &Res<Assets<AudioSource>>) -> bool {
-        audio_assets.contains(&self.music)
-    }
-}
-
 #[derive(Debug, Default)]
 pub struct AudioPlugin;
 
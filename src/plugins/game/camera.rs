@@ -1,7 +1,7 @@
 use bevy::{ecs::query::QueryData, prelude::*, window::PrimaryWindow};
 
 use crate::plugins::{tiled::TiledMapTileLayer, ui::IsPointerCaptured};
-use crate::tilemap::TileMapSizeQuery;
+use crate::tilemap::{get_tilemap_world_bounds, TileMapQuery};
 
 #[derive(Component)]
 pub struct MainCamera;
@@ -42,7 +42,7 @@ pub(super) fn pan(
     time: Res<Time>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     mut camera_query: Query<CameraProjectionQueryMut, With<MainCamera>>,
-    tilemap_query: Query<TileMapSizeQuery, With<TiledMapTileLayer>>,
+    tilemap_query: Query<TileMapQuery, With<TiledMapTileLayer>>,
 ) {
     if is_pointer_captured.0 {
         return;
@@ -59,47 +59,48 @@ pub(super) fn pan(
     let Ok(tilemap) = tilemap_query.get_single() else {
         return;
     };
-    let map_half_width = (tilemap.size.x as f32 * tilemap.grid_size.x) / 2.0;
-    let map_half_height = (tilemap.size.y as f32 * tilemap.grid_size.y) / 2.0;
+
+    // infinite maps cover the bounds of their chunks
+    let map_bounds = get_tilemap_world_bounds(tilemap.size, tilemap.grid_size, tilemap.transform);
 
     let speed = CAMERA_SPEED * time.delta_seconds();
 
     if let Some(position) = window.cursor_position() {
         if position.x < window_movement_width {
             camera.transform.translation.x =
-                (camera.transform.translation.x - speed).max(view_half_width - map_half_width);
+                (camera.transform.translation.x - speed).max(map_bounds.min.x + view_half_width);
         } else if position.x > window.width() - window_movement_width {
             camera.transform.translation.x =
-                (camera.transform.translation.x + speed).min(map_half_width - view_half_width);
+                (camera.transform.translation.x + speed).min(map_bounds.max.x - view_half_width);
         }
 
         if position.y < window_movement_height {
             camera.transform.translation.y =
-                (camera.transform.translation.y + speed).min(map_half_height - view_half_height);
+                (camera.transform.translation.y + speed).min(map_bounds.max.y - view_half_height);
         } else if position.y > window.height() - window_movement_height {
             camera.transform.translation.y =
-                (camera.transform.translation.y - speed).max(view_half_height - map_half_height);
+                (camera.transform.translation.y - speed).max(map_bounds.min.y + view_half_height);
         }
     }
 
     // TODO: this could be useful for debugging
     /*if keys.pressed(KeyCode::ArrowRight) {
         camera.transform.translation.x =
-            (camera.transform.translation.x + speed).min(map_half_width - view_half_width);
+            (camera.transform.translation.x + speed).min(map_bounds.max.x - view_half_width);
     }
 
     if keys.pressed(KeyCode::ArrowLeft) {
         camera.transform.translation.x =
-            (camera.transform.translation.x - speed).max(view_half_width - map_half_width);
+            (camera.transform.translation.x - speed).max(map_bounds.min.x + view_half_width);
     }
 
     if keys.pressed(KeyCode::ArrowUp) {
         camera.transform.translation.y =
-            (camera.transform.translation.y + speed).min(map_half_height - view_half_height);
+            (camera.transform.translation.y + speed).min(map_bounds.max.y - view_half_height);
     }
 
     if keys.pressed(KeyCode::ArrowDown) {
         camera.transform.translation.y =
-            (camera.transform.translation.y - speed).max(view_half_height - map_half_height);
+            (camera.transform.translation.y - speed).max(map_bounds.min.y + view_half_height);
     }*/
 }
@@ -0,0 +1,356 @@
+//! In-game map editor
+//!
+//! Paints Tiles onto the Tile layer and places / deletes Resources Objects
+//! on the Object layer. Edits are made to the game world, "Save Map" exports
+//! them as TMX (see export::ExportMapEvent)
+
+use bevy::{ecs::system::SystemParam, prelude::*, window::PrimaryWindow};
+use bevy_ecs_tilemap::prelude::*;
+
+use super::camera::MainCamera;
+use super::items::{PlacementDirection, TransportNetwork};
+use super::objects::Object;
+use super::{GameAssets, ObjectInfo};
+use crate::assets::{registry::Registry, tiled::TiledMap};
+use crate::data::{objects::ObjectData, tiles::TileData};
+use crate::get_world_position_from_cursor_position;
+use crate::plugins::{
+    game_ui::{log::LogEvent, object_info::ObjectInfoWindow},
+    tiled::{
+        TiledLayerChunks, TiledLayerColor, TiledLayerTilesets, TiledLayersStorage,
+        TiledMapObjectLayer, TiledMapTileLayer,
+    },
+    ui::IsPointerCaptured,
+};
+use crate::tiled::get_tile_animation;
+use crate::tilemap::{
+    despawn_object, despawn_tile, get_tile_position, spawn_object, spawn_tile, TileAnimation,
+    TileFootprint, TileMapQueryMut,
+};
+
+/// The Resources amount of newly placed Objects
+const DEFAULT_RESOURCE_AMOUNT: u32 = 100;
+
+/// What clicking on the map does while editing
+#[derive(Debug, Default, Clone, PartialEq, Eq, Resource)]
+pub enum EditorTool {
+    /// Objects can be inspected (and edited) from the Object Info window
+    #[default]
+    Select,
+
+    /// Paint a tileset tile onto the Tile layer
+    PaintTile { tileset: String, tile_id: u32 },
+
+    /// Place a Resources Object using a tileset tile
+    PlaceObject { tileset: String, tile_id: u32 },
+
+    /// Delete Objects
+    Erase,
+}
+
+/// A change to an Object's data made in the Object Info window
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ObjectEdit {
+    /// Cycle through the Registry Resource types
+    NextResourceType,
+    PreviousResourceType,
+
+    /// Change the Resources amount by this much
+    Amount(i32),
+}
+
+/// Emit to change the data of the Object being shown in the Object Info window
+#[derive(Debug, Event)]
+pub struct EditObjectEvent(pub ObjectEdit);
+
+pub(super) fn enter(mut commands: Commands, mut log_events: EventWriter<LogEvent>) {
+    info!("entering map editor");
+
+    commands.init_resource::<EditorTool>();
+    log_events.send(LogEvent::new("Map editor enabled"));
+}
+
+pub(super) fn exit(mut commands: Commands, mut log_events: EventWriter<LogEvent>) {
+    info!("exiting map editor");
+
+    commands.remove_resource::<EditorTool>();
+    log_events.send(LogEvent::new("Map editor disabled"));
+}
+
+/// The layers (and their tiles) that the editor changes
+///
+/// Only finite Tile layers can be painted, streamed layers respawn their tiles
+#[allow(clippy::type_complexity)]
+#[derive(SystemParam)]
+pub(super) struct EditorLayerQueries<'w, 's> {
+    tile_layer_query: Query<
+        'w,
+        's,
+        (Entity, TileMapQueryMut, &'static TiledLayerTilesets),
+        (With<TiledMapTileLayer>, Without<TiledLayerChunks>),
+    >,
+    object_layer_query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut TileStorage,
+            &'static TiledLayerTilesets,
+            &'static TiledLayerColor,
+        ),
+        (With<TiledMapObjectLayer>, Without<TiledMapTileLayer>),
+    >,
+    tile_query: Query<
+        'w,
+        's,
+        (
+            &'static TilemapId,
+            &'static TileTextureIndex,
+            Option<&'static TileAnimation>,
+        ),
+    >,
+    object_query: Query<'w, 's, (&'static Object, &'static TilePos, &'static TileFootprint)>,
+}
+
+/// Uses the current editor tool on the tile under the cursor
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub(super) fn use_tool(
+    mut commands: Commands,
+    tool: Res<EditorTool>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    is_pointer_captured: Res<IsPointerCaptured>,
+    game_assets: Res<GameAssets>,
+    tiled_maps: Res<Assets<TiledMap>>,
+    registry: Res<Registry>,
+    network: Res<TransportNetwork>,
+    placement_direction: Res<PlacementDirection>,
+    mut log_events: EventWriter<LogEvent>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    storage_query: Query<&TiledLayersStorage>,
+    mut queries: EditorLayerQueries,
+    mut object_info_window_query: Query<&mut Visibility, With<ObjectInfoWindow>>,
+) {
+    if is_pointer_captured.0 || *tool == EditorTool::Select {
+        return;
+    }
+
+    // tiles are painted while the button is held, Objects are placed one at a time
+    if !matches!(*tool, EditorTool::PaintTile { .. }) && !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(tiled_map) = tiled_maps.get(&game_assets.map) else {
+        return;
+    };
+
+    let (Ok(window), Ok((camera, camera_transform))) =
+        (window_query.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+
+    let Ok((tile_layer, mut tile_map, tile_tilesets)) = queries.tile_layer_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Ok((object_layer, mut object_storage, object_tilesets, object_color)) =
+        queries.object_layer_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Some(position) =
+        get_world_position_from_cursor_position(window.cursor_position(), camera, camera_transform)
+            .and_then(|world_position| {
+                get_tile_position(
+                    world_position,
+                    tile_map.size,
+                    tile_map.grid_size,
+                    tile_map.r#type,
+                    tile_map.transform,
+                )
+            })
+    else {
+        return;
+    };
+
+    match &*tool {
+        EditorTool::Select => (),
+        EditorTool::PaintTile { tileset, tile_id } => {
+            // Items replace the tiles they are built on
+            if network.contains(&position) {
+                return;
+            }
+
+            let Some(tilemap) = tile_tilesets.get(tileset) else {
+                warn!("Tile layer doesn't use tileset {}", tileset);
+                return;
+            };
+
+            let Some(tiled_tileset) = tiled_map.get_tileset(tileset) else {
+                return;
+            };
+
+            if let Some(tile_entity) = tile_map.storage.get(&position) {
+                // animated tiles are compared by their base tile
+                let unchanged = queries.tile_query.get(tile_entity).is_ok_and(
+                    |(tile_tilemap, texture_index, animation)| {
+                        tile_tilemap.0 == tilemap
+                            && animation
+                                .map(|animation| animation.texture_index)
+                                .unwrap_or(texture_index.0)
+                                == *tile_id
+                    },
+                );
+                if unchanged {
+                    return;
+                }
+
+                despawn_tile(&mut commands, &mut tile_map.storage, tile_entity, position);
+            }
+
+            // validated, tileset tile properties are valid
+            let data = TileData::from_tile(tiled_tileset.get_tile(*tile_id)).unwrap_or_default();
+            let animation = get_tile_animation(tiled_tileset, *tile_id);
+            commands.entity(tile_layer).with_children(|parent| {
+                spawn_tile(
+                    parent,
+                    &mut tile_map.storage,
+                    tilemap,
+                    position,
+                    *tile_id,
+                    TileFlip::default(),
+                    tile_map.color.0,
+                    data,
+                    true,
+                    animation,
+                );
+            });
+        }
+        EditorTool::PlaceObject { tileset, tile_id } => {
+            if object_storage.get(&position).is_some() || network.contains(&position) {
+                log_events.send(LogEvent::new("Can't place an Object there"));
+                return;
+            }
+
+            let Some(tilemap) = object_tilesets.get(tileset) else {
+                warn!("Object layer doesn't use tileset {}", tileset);
+                return;
+            };
+
+            let Some(resource) = registry.resources.first() else {
+                return;
+            };
+
+            // Object ids are shared with the map, including Objects removed during play
+            let id = queries
+                .object_query
+                .iter()
+                .map(|(object, ..)| object.get_id())
+                .chain(
+                    storage_query
+                        .iter()
+                        .flat_map(|storage| storage.spawned_objects.iter().copied()),
+                )
+                .max()
+                .unwrap_or_default()
+                + 1;
+
+            let direction = placement_direction.0;
+            let data = ObjectData::Resources {
+                id,
+                r#type: resource.id,
+                amount: DEFAULT_RESOURCE_AMOUNT,
+                direction,
+            };
+            let animation = tiled_map.get_tile_animation(tileset, *tile_id);
+
+            let mut object_id = None;
+            commands.entity(object_layer).with_children(|parent| {
+                object_id = Some(spawn_object(
+                    parent,
+                    &mut object_storage,
+                    tilemap,
+                    position,
+                    TileFootprint::default(),
+                    *tile_id,
+                    direction.tile_flip(),
+                    object_color.0,
+                    true,
+                    data,
+                    animation,
+                ));
+            });
+
+            // new Objects are ready to be edited
+            if let Some(object_id) = object_id {
+                commands.insert_resource(ObjectInfo(object_id));
+                *object_info_window_query.single_mut() = Visibility::Visible;
+            }
+        }
+        EditorTool::Erase => {
+            let Some(object_entity) = object_storage.get(&position) else {
+                return;
+            };
+
+            let Ok((object, object_position, footprint)) = queries.object_query.get(object_entity)
+            else {
+                return;
+            };
+
+            log_events.send(LogEvent::new(format!("Deleted Object {}", object.get_id())));
+            despawn_object(
+                &mut commands,
+                &mut object_storage,
+                object_entity,
+                *object_position,
+                *footprint,
+            );
+        }
+    }
+}
+
+pub(super) fn edit_object_event_handler(
+    mut events: EventReader<EditObjectEvent>,
+    object_info: Option<Res<ObjectInfo>>,
+    registry: Res<Registry>,
+    mut object_query: Query<&mut Object>,
+) {
+    for event in events.read() {
+        let Some(mut object) = object_info
+            .as_ref()
+            .and_then(|object_info| object_query.get_mut(object_info.0).ok())
+        else {
+            continue;
+        };
+
+        match &mut object.0 {
+            ObjectData::Resources { r#type, amount, .. } => match event.0 {
+                ObjectEdit::NextResourceType | ObjectEdit::PreviousResourceType => {
+                    let count = registry.resources.len();
+                    if count == 0 {
+                        continue;
+                    }
+
+                    let idx = registry
+                        .resources
+                        .iter()
+                        .position(|resource| resource.id == *r#type)
+                        .unwrap_or_default();
+                    let idx = if event.0 == ObjectEdit::NextResourceType {
+                        (idx + 1) % count
+                    } else {
+                        (idx + count - 1) % count
+                    };
+                    *r#type = registry.resources[idx].id;
+                }
+                ObjectEdit::Amount(delta) => {
+                    *amount = amount.saturating_add_signed(delta);
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use super::objects::Object;
+use super::tiles::Tile;
+use super::{CurrentLevel, GameAssets};
+use crate::assets::{registry::Registry, tiled::TiledMap};
+use crate::plugins::{
+    game_ui::log::LogEvent,
+    tiled::{TiledLayerChunks, TiledLayerTilesets, TiledLayersStorage},
+};
+use crate::tiled::export::{
+    ExportedItem, ExportedObject, ExportedTile, ExportedWorld, MapExporter,
+};
+use crate::tilemap::{TileAnimation, TileFootprint};
+
+const EXPORT_DIR: &str = "exports";
+
+/// Exports are written outside of the assets, next to the saves
+const EXPORT_ASSET_DIR: &str = "../assets";
+
+/// Emit to export the current game world as a Tiled map
+#[derive(Debug, Default, Event)]
+pub struct ExportMapEvent;
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub(super) fn export_map_event_handler(
+    mut events: EventReader<ExportMapEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    current_level: Res<CurrentLevel>,
+    game_assets: Res<GameAssets>,
+    tiled_maps: Res<Assets<TiledMap>>,
+    registry: Res<Registry>,
+    storage_query: Query<&TiledLayersStorage>,
+    layer_query: Query<(&TiledLayerTilesets, Has<TiledLayerChunks>)>,
+    tile_query: Query<
+        (
+            &TilePos,
+            &TilemapId,
+            &TileTextureIndex,
+            &TileFlip,
+            Option<&TileAnimation>,
+        ),
+        With<Tile>,
+    >,
+    object_query: Query<(
+        &Object,
+        &TilePos,
+        &TileFootprint,
+        &TilemapId,
+        &TileTextureIndex,
+        &TileFlip,
+        &TileVisible,
+        Option<&TileAnimation>,
+    )>,
+    item_query: Query<
+        (
+            &TilePos,
+            &TileFootprint,
+            &TilemapId,
+            &TileTextureIndex,
+            Option<&TileAnimation>,
+        ),
+        Without<Object>,
+    >,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let (Some(tiled_map), Ok(layer_storage)) =
+        (tiled_maps.get(&game_assets.map), storage_query.get_single())
+    else {
+        warn!("No map to export");
+        return;
+    };
+
+    // streamed (infinite) layers only have the visible chunks spawned,
+    // so they are exported as they were loaded
+    let mut world = ExportedWorld::from_map(&tiled_map.map);
+    world.objects.clear();
+
+    // maps tileset tilemaps back to their Tiled layer and tileset
+    let mut tilemaps = HashMap::new();
+    for (layer_id, layer_entity) in &layer_storage.storage {
+        let Ok((tilesets, streamed)) = layer_query.get(*layer_entity) else {
+            continue;
+        };
+
+        if !streamed {
+            world.tiles.remove(layer_id);
+        }
+
+        for (tileset, tilemap) in &tilesets.0 {
+            tilemaps.insert(*tilemap, (*layer_id, tileset.as_str()));
+        }
+    }
+    let streamed_layers = world.tiles.keys().copied().collect::<HashSet<_>>();
+
+    for (position, tilemap_id, texture_index, flip, animation) in &tile_query {
+        let Some((layer_id, tileset)) = tilemaps.get(&tilemap_id.0) else {
+            continue;
+        };
+        if streamed_layers.contains(layer_id) {
+            continue;
+        }
+
+        world
+            .tiles
+            .entry(*layer_id)
+            .or_default()
+            .push(ExportedTile {
+                position: *position,
+                tileset: tileset.to_string(),
+                // animated tiles are exported as their base tile, not the current frame
+                tile_id: animation
+                    .map(|animation| animation.texture_index)
+                    .unwrap_or(texture_index.0),
+                flip: *flip,
+            });
+    }
+
+    for (object, position, footprint, tilemap_id, texture_index, flip, visible, animation) in
+        &object_query
+    {
+        let Some((layer_id, tileset)) = tilemaps.get(&tilemap_id.0) else {
+            continue;
+        };
+
+        world
+            .objects
+            .entry(*layer_id)
+            .or_default()
+            .push(ExportedObject {
+                position: *position,
+                footprint: *footprint,
+                tileset: tileset.to_string(),
+                tile_id: animation
+                    .map(|animation| animation.texture_index)
+                    .unwrap_or(texture_index.0),
+                flip: *flip,
+                visible: visible.0,
+                data: object.0.clone(),
+            });
+    }
+
+    let item_layers = layer_storage
+        .item_layers
+        .iter()
+        .map(|(item_type, layer_entity)| (*layer_entity, *item_type))
+        .collect::<HashMap<_, _>>();
+    for (position, footprint, tilemap_id, texture_index, animation) in &item_query {
+        let Some(item_type) = item_layers.get(&tilemap_id.0) else {
+            continue;
+        };
+
+        world.items.push(ExportedItem {
+            r#type: *item_type,
+            position: *position,
+            footprint: *footprint,
+            sprite_index: animation
+                .map(|animation| animation.texture_index)
+                .unwrap_or(texture_index.0),
+        });
+    }
+    world
+        .items
+        .sort_by_key(|item| (item.r#type as usize, item.position.x, item.position.y));
+
+    let tmx = MapExporter {
+        map: &tiled_map.map,
+        tileset_sources: &tiled_map.tileset_sources,
+        registry: &registry,
+        asset_dir: Path::new(EXPORT_ASSET_DIR),
+    }
+    .export(&world);
+
+    // generated map paths include their asset source
+    let file_name = Path::new(&current_level.map)
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "map.tmx".to_owned());
+    let path = Path::new(EXPORT_DIR).join(file_name);
+
+    let result = std::fs::create_dir_all(EXPORT_DIR).and_then(|_| std::fs::write(&path, tmx));
+    match result {
+        Ok(()) => {
+            info!("Exported map to {}", path.display());
+            log_events.send(LogEvent::new(format!("Map exported to {}", path.display())));
+        }
+        Err(err) => {
+            error!("Failed to export map to {}: {}", path.display(), err);
+            log_events.send(LogEvent::new(format!("Export failed: {}", err)));
+        }
+    }
+}
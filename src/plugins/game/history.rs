@@ -0,0 +1,602 @@
+//! Undo / redo of player actions
+//!
+//! Actions are recorded as they're made (see item_drop_event_handler and the selection actions)
+//! and are only undone or redone if every change can still be made to the world
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_tilemap::prelude::*;
+
+use super::inventory::{Inventory, InventoryUpdatedEvent};
+use super::items::{
+    conveyor::Conveyor, crafter::Crafter, harvester::Harvester, SpawnItemEvent, TransportNetwork,
+};
+use super::objects::Object;
+use super::tiles::Tile;
+use super::{GameAssets, ItemInfo};
+use crate::assets::{registry::Registry, tiled::TiledMap};
+use crate::data::{
+    history::{Action, ActionHistory, Change, SavedTile},
+    inventory::InventoryData,
+    objects::ObjectData,
+    save::{SavedItem, SavedObject},
+};
+use crate::plugins::{
+    game_ui::{item_info::ItemInfoWindow, log::LogEvent},
+    tiled::{
+        TiledLayerChunks, TiledLayerColor, TiledLayerTilesets, TiledMapItemLayer,
+        TiledMapObjectLayer, TiledMapTileLayer,
+    },
+};
+use crate::tilemap::{
+    despawn_item, despawn_object, despawn_tile, spawn_object, spawn_tile, TileAnimation,
+    TileFootprint,
+};
+
+/// Player action history
+#[derive(Debug, Default, Resource, Deref, DerefMut)]
+pub struct History(pub ActionHistory);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HistoryAction {
+    Undo,
+    Redo,
+}
+
+/// Emit to undo or redo the last action
+#[derive(Debug, Event)]
+pub struct HistoryEvent(pub HistoryAction);
+
+/// Records player actions, along with how Tiles, Objects, and Items were before they're removed
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct ActionRecorder<'w, 's> {
+    history: ResMut<'w, History>,
+    tile_query: Query<
+        'w,
+        's,
+        (
+            &'static Tile,
+            &'static TilePos,
+            &'static TilemapId,
+            &'static TileTextureIndex,
+            &'static TileFlip,
+            &'static TileVisible,
+            Option<&'static TileAnimation>,
+        ),
+    >,
+    object_query: Query<
+        'w,
+        's,
+        (
+            &'static Object,
+            &'static TilePos,
+            &'static TileFootprint,
+            &'static TilemapId,
+            &'static TileTextureIndex,
+            &'static TileFlip,
+            &'static TileVisible,
+            Option<&'static TileAnimation>,
+        ),
+    >,
+    item_query: Query<
+        'w,
+        's,
+        (
+            &'static TilePos,
+            &'static TileFootprint,
+            Option<&'static Harvester>,
+            Option<&'static Conveyor>,
+            Option<&'static Crafter>,
+        ),
+    >,
+    tilesets_query: Query<'w, 's, &'static TiledLayerTilesets>,
+}
+
+impl<'w, 's> ActionRecorder<'w, 's> {
+    /// Gets the tileset a tilemap renders, from whichever layer it belongs to
+    fn get_tileset(&self, tilemap_id: &TilemapId) -> Option<String> {
+        self.tilesets_query
+            .iter()
+            .find_map(|tilesets| tilesets.get_tileset(tilemap_id.0))
+            .map(String::from)
+    }
+
+    /// Gets a Tile as it would be put back
+    pub fn save_tile(&self, entity: Entity) -> Option<SavedTile> {
+        let (tile, position, tilemap_id, texture_index, flip, visible, animation) =
+            self.tile_query.get(entity).ok()?;
+
+        Some(SavedTile {
+            position: *position,
+            tileset: self.get_tileset(tilemap_id)?,
+            // animated tiles are put back as their base tile, not the current frame
+            texture_index: animation
+                .map(|animation| animation.texture_index)
+                .unwrap_or(texture_index.0),
+            flip: *flip,
+            visible: visible.0,
+            data: tile.0,
+        })
+    }
+
+    /// Gets an Object as it would be put back
+    pub fn save_object(&self, entity: Entity) -> Option<SavedObject> {
+        let (object, position, footprint, tilemap_id, texture_index, flip, visible, animation) =
+            self.object_query.get(entity).ok()?;
+
+        Some(SavedObject {
+            position: *position,
+            footprint: *footprint,
+            tileset: self.get_tileset(tilemap_id),
+            texture_index: animation
+                .map(|animation| animation.texture_index)
+                .unwrap_or(texture_index.0),
+            flip: *flip,
+            visible: visible.0,
+            data: object.0.clone(),
+        })
+    }
+
+    /// Gets an Item as it would be put back
+    pub fn save_item(&self, entity: Entity) -> Option<SavedItem> {
+        let (position, footprint, harvester, conveyor, crafter) =
+            self.item_query.get(entity).ok()?;
+
+        if let Some(harvester) = harvester {
+            Some(SavedItem::Harvester {
+                position: *position,
+                footprint: *footprint,
+                data: harvester.0.clone(),
+            })
+        } else if let Some(conveyor) = conveyor {
+            Some(SavedItem::Conveyor {
+                position: *position,
+                data: conveyor.0.clone(),
+            })
+        } else {
+            crafter.map(|crafter| SavedItem::Crafter {
+                position: *position,
+                footprint: *footprint,
+                data: crafter.0.clone(),
+            })
+        }
+    }
+
+    /// Records a new action that can be undone
+    #[inline]
+    pub fn record(&mut self, action: Action) {
+        self.history.record(action);
+    }
+}
+
+/// The layers changes are made to
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub(super) struct HistoryLayerQueries<'w, 's> {
+    tile_layer_query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut TileStorage,
+            &'static TiledLayerTilesets,
+            &'static TiledLayerColor,
+        ),
+        (
+            With<TiledMapTileLayer>,
+            Without<TiledLayerChunks>,
+            Without<TiledMapObjectLayer>,
+            Without<TiledMapItemLayer>,
+        ),
+    >,
+    object_layer_query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut TileStorage,
+            &'static TiledLayerTilesets,
+            &'static TiledLayerColor,
+        ),
+        (
+            With<TiledMapObjectLayer>,
+            Without<TiledMapTileLayer>,
+            Without<TiledMapItemLayer>,
+        ),
+    >,
+    item_layer_query: Query<
+        'w,
+        's,
+        (&'static TiledMapItemLayer, &'static mut TileStorage),
+        (Without<TiledMapTileLayer>, Without<TiledMapObjectLayer>),
+    >,
+}
+
+/// Tiles freed and taken by the changes checked so far
+#[derive(Debug, Default)]
+struct Occupancy {
+    freed: HashSet<TilePos>,
+    taken: HashSet<TilePos>,
+}
+
+impl Occupancy {
+    /// Checks that a tile is (or will be) empty
+    #[inline]
+    fn is_free(&self, position: &TilePos, empty: bool) -> bool {
+        (empty || self.freed.contains(position)) && !self.taken.contains(position)
+    }
+
+    /// Checks that a tile is (and will still be) filled
+    #[inline]
+    fn is_filled(&self, position: &TilePos) -> bool {
+        !self.freed.contains(position) && !self.taken.contains(position)
+    }
+}
+
+/// Checks that every change in an action can be made to the world as it is now
+///
+/// Changes are checked in order, so later changes can use what earlier changes freed up.
+/// Objects and Items share tiles, nothing is built on top of another
+fn can_apply(
+    action: &Action,
+    layers: &HistoryLayerQueries,
+    recorder: &ActionRecorder,
+    inventory: &InventoryData,
+    registry: &Registry,
+) -> bool {
+    let tile_layer = layers.tile_layer_query.get_single().ok();
+    let Ok((_, object_storage, object_tilesets, _)) = layers.object_layer_query.get_single() else {
+        return false;
+    };
+
+    let is_empty = |position: &TilePos| {
+        position.within_map_bounds(&object_storage.size)
+            && object_storage.get(position).is_none()
+            && layers
+                .item_layer_query
+                .iter()
+                .all(|(_, item_storage)| item_storage.get(position).is_none())
+    };
+
+    let mut tiles = Occupancy::default();
+    let mut buildings = Occupancy::default();
+    let mut inventory_changes = HashMap::new();
+
+    for change in &action.changes {
+        match change {
+            Change::SpawnTile(tile) => {
+                let Some((_, tile_storage, tile_tilesets, _)) = tile_layer else {
+                    return false;
+                };
+                let empty = tile.position.within_map_bounds(&tile_storage.size)
+                    && tile_storage.get(&tile.position).is_none();
+                if tile_tilesets.get(&tile.tileset).is_none()
+                    || !tiles.is_free(&tile.position, empty)
+                {
+                    return false;
+                }
+                tiles.taken.insert(tile.position);
+            }
+            Change::DespawnTile(tile) => {
+                let Some((_, tile_storage, ..)) = tile_layer else {
+                    return false;
+                };
+                let filled = tile.position.within_map_bounds(&tile_storage.size)
+                    && tile_storage.get(&tile.position).is_some();
+                if !filled || !tiles.is_filled(&tile.position) {
+                    return false;
+                }
+                tiles.freed.insert(tile.position);
+            }
+            Change::SpawnObject(object) => {
+                if object_tilesets.first().is_none() {
+                    return false;
+                }
+                for position in object.footprint.positions(object.position) {
+                    if !buildings.is_free(&position, is_empty(&position)) {
+                        return false;
+                    }
+                }
+                buildings
+                    .taken
+                    .extend(object.footprint.positions(object.position));
+            }
+            Change::DespawnObject(object) => {
+                // the Object has to be where it was left
+                let found = object.position.within_map_bounds(&object_storage.size)
+                    && object_storage
+                        .get(&object.position)
+                        .and_then(|entity| recorder.object_query.get(entity).ok())
+                        .is_some_and(|(_, position, ..)| *position == object.position);
+                if !found || !buildings.is_filled(&object.position) {
+                    return false;
+                }
+                buildings
+                    .freed
+                    .extend(object.footprint.positions(object.position));
+            }
+            Change::SpawnItem(item) => {
+                let footprint = item.get_footprint();
+                for position in footprint.positions(item.get_position()) {
+                    if !buildings.is_free(&position, is_empty(&position)) {
+                        return false;
+                    }
+                }
+                buildings
+                    .taken
+                    .extend(footprint.positions(item.get_position()));
+            }
+            Change::DespawnItem(item) => {
+                let position = item.get_position();
+                let found = position.within_map_bounds(&object_storage.size)
+                    && layers
+                        .item_layer_query
+                        .iter()
+                        .find(|(item_layer, _)| item_layer.0 == item.get_type())
+                        .and_then(|(_, item_storage)| item_storage.get(&position))
+                        .and_then(|entity| recorder.item_query.get(entity).ok())
+                        .is_some_and(|(item_position, ..)| *item_position == position);
+                if !found || !buildings.is_filled(&position) {
+                    return false;
+                }
+                buildings
+                    .freed
+                    .extend(item.get_footprint().positions(position));
+            }
+            Change::Inventory(item_type, amount) => {
+                let change = inventory_changes.entry(*item_type).or_insert(0_i64);
+                *change += *amount as i64;
+
+                let count = inventory
+                    .get_items()
+                    .get(item_type)
+                    .copied()
+                    .unwrap_or_default() as i64
+                    + *change;
+                if count < 0 || count > registry.get_item(*item_type).stack_size as i64 {
+                    return false;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+pub(super) fn history_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut history_events: EventWriter<HistoryEvent>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keys.just_pressed(KeyCode::KeyY) || (shift && keys.just_pressed(KeyCode::KeyZ)) {
+        history_events.send(HistoryEvent(HistoryAction::Redo));
+    } else if keys.just_pressed(KeyCode::KeyZ) {
+        history_events.send(HistoryEvent(HistoryAction::Undo));
+    }
+}
+
+/// Undoes or redoes the last action
+///
+/// Removed things are put back exactly as they were recorded. Things being removed
+/// are recorded as they are now, so redoing puts back any progress that was undone.
+/// An Object put back under an undone Harvester keeps only what the Harvester had left
+#[allow(clippy::too_many_arguments)]
+pub(super) fn history_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<HistoryEvent>,
+    mut recorder: ActionRecorder,
+    mut layers: HistoryLayerQueries,
+    mut inventory: ResMut<Inventory>,
+    mut network: ResMut<TransportNetwork>,
+    registry: Res<Registry>,
+    game_assets: Res<GameAssets>,
+    tiled_maps: Res<Assets<TiledMap>>,
+    item_info: Option<Res<ItemInfo>>,
+    mut inventory_updated_events: EventWriter<InventoryUpdatedEvent>,
+    mut spawn_item_events: EventWriter<SpawnItemEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    mut item_info_window_query: Query<&mut Visibility, With<ItemInfoWindow>>,
+) {
+    let tiled_map = tiled_maps.get(&game_assets.map);
+
+    for event in events.read() {
+        let (action, verb) = match event.0 {
+            HistoryAction::Undo => match recorder.history.pop_undo() {
+                Some(action) => (action.inverse(), "undo"),
+                None => {
+                    log_events.send(LogEvent::new("Nothing to undo"));
+                    continue;
+                }
+            },
+            HistoryAction::Redo => match recorder.history.pop_redo() {
+                Some(action) => (action, "redo"),
+                None => {
+                    log_events.send(LogEvent::new("Nothing to redo"));
+                    continue;
+                }
+            },
+        };
+
+        if !can_apply(&action, &layers, &recorder, &inventory.0, &registry) {
+            log_events.send(LogEvent::new(format!(
+                "Can't {} {}, something is in the way",
+                verb, action.name
+            )));
+            match event.0 {
+                HistoryAction::Undo => recorder.history.cancel_undo(action.inverse()),
+                HistoryAction::Redo => recorder.history.cancel_redo(action),
+            }
+            continue;
+        }
+
+        let mut applied = Action::new(action.name.clone());
+        let mut harvester_remaining = None;
+        for change in action.changes {
+            match change {
+                Change::SpawnTile(tile) => {
+                    let (tile_layer, mut tile_storage, tile_tilesets, tile_color) =
+                        layers.tile_layer_query.single_mut();
+                    let tilemap = tile_tilesets.get(&tile.tileset).unwrap();
+                    let animation = tiled_map.and_then(|tiled_map| {
+                        tiled_map.get_tile_animation(&tile.tileset, tile.texture_index)
+                    });
+
+                    commands.entity(tile_layer).with_children(|parent| {
+                        spawn_tile(
+                            parent,
+                            &mut tile_storage,
+                            tilemap,
+                            tile.position,
+                            tile.texture_index,
+                            tile.flip,
+                            tile_color.0,
+                            tile.data,
+                            tile.visible,
+                            animation,
+                        );
+                    });
+                    applied.push(Change::SpawnTile(tile));
+                }
+                Change::DespawnTile(tile) => {
+                    let (_, mut tile_storage, ..) = layers.tile_layer_query.single_mut();
+                    let entity = tile_storage.get(&tile.position).unwrap();
+                    let tile = recorder.save_tile(entity).unwrap_or(tile);
+
+                    despawn_tile(&mut commands, &mut tile_storage, entity, tile.position);
+                    applied.push(Change::DespawnTile(tile));
+                }
+                Change::SpawnObject(mut object) => {
+                    let (object_layer, mut object_storage, object_tilesets, object_color) =
+                        layers.object_layer_query.single_mut();
+                    let (tileset, tilemap) = match object
+                        .tileset
+                        .as_ref()
+                        .and_then(|tileset| object_tilesets.get(tileset))
+                    {
+                        Some(tilemap) => (object.tileset.clone(), tilemap),
+                        None => (
+                            object_tilesets.0.first().map(|(name, _)| name.clone()),
+                            object_tilesets.first().unwrap(),
+                        ),
+                    };
+                    let animation =
+                        tiled_map
+                            .zip(tileset.as_ref())
+                            .and_then(|(tiled_map, tileset)| {
+                                tiled_map.get_tile_animation(tileset, object.texture_index)
+                            });
+
+                    // what was harvested stays harvested
+                    if let Some((position, remaining)) = harvester_remaining {
+                        if position == object.position {
+                            match &mut object.data {
+                                ObjectData::Resources { amount, .. } => *amount = remaining,
+                            }
+                        }
+                    }
+
+                    commands.entity(object_layer).with_children(|parent| {
+                        spawn_object(
+                            parent,
+                            &mut object_storage,
+                            tilemap,
+                            object.position,
+                            object.footprint,
+                            object.texture_index,
+                            object.flip,
+                            object_color.0,
+                            object.visible,
+                            object.data.clone(),
+                            animation,
+                        );
+                    });
+                    applied.push(Change::SpawnObject(object));
+                }
+                Change::DespawnObject(object) => {
+                    let (_, mut object_storage, ..) = layers.object_layer_query.single_mut();
+                    let entity = object_storage.get(&object.position).unwrap();
+                    let object = recorder.save_object(entity).unwrap_or(object);
+
+                    despawn_object(
+                        &mut commands,
+                        &mut object_storage,
+                        entity,
+                        object.position,
+                        object.footprint,
+                    );
+                    applied.push(Change::DespawnObject(object));
+                }
+                Change::SpawnItem(item) => {
+                    spawn_item_events.send(item.clone().into());
+                    applied.push(Change::SpawnItem(item));
+                }
+                Change::DespawnItem(item) => {
+                    let (position, footprint) = (item.get_position(), item.get_footprint());
+                    let (_, mut item_storage) = layers
+                        .item_layer_query
+                        .iter_mut()
+                        .find(|(item_layer, _)| item_layer.0 == item.get_type())
+                        .unwrap();
+                    let entity = item_storage.get(&position).unwrap();
+                    let item = recorder.save_item(entity).unwrap_or(item);
+
+                    if let SavedItem::Harvester { data, .. } = &item {
+                        harvester_remaining = Some((position, data.remaining));
+                    }
+
+                    // the Item Info window can't show a removed Item
+                    if item_info
+                        .as_ref()
+                        .is_some_and(|item_info| item_info.0 == entity)
+                    {
+                        commands.remove_resource::<ItemInfo>();
+                        *item_info_window_query.single_mut() = Visibility::Hidden;
+                    }
+
+                    despawn_item(
+                        &mut commands,
+                        &mut item_storage,
+                        entity,
+                        position,
+                        footprint,
+                    );
+                    network.remove(position, footprint);
+                    applied.push(Change::DespawnItem(item));
+                }
+                Change::Inventory(item_type, amount) => {
+                    for _ in 0..amount.unsigned_abs() {
+                        if amount > 0 {
+                            inventory.0.add_item(
+                                item_type,
+                                &registry,
+                                &mut inventory_updated_events,
+                            );
+                        } else {
+                            inventory
+                                .0
+                                .remove_item(item_type, &mut inventory_updated_events);
+                        }
+                    }
+                    applied.push(Change::Inventory(item_type, amount));
+                }
+            }
+        }
+
+        match event.0 {
+            HistoryAction::Undo => {
+                log_events.send(LogEvent::new(format!("Undid {}", applied.name)));
+                recorder.history.undone(&applied);
+            }
+            HistoryAction::Redo => {
+                log_events.send(LogEvent::new(format!("Redid {}", applied.name)));
+                recorder.history.redone(applied);
+            }
+        }
+    }
+}
@@ -1,15 +1,68 @@
+use std::collections::HashSet;
+
 use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_ecs_tilemap::prelude::*;
 
 use super::camera::{CameraTransformQuery, MainCamera};
+use super::objects::Object;
+use super::selection::{Selected, Selection};
+use super::tiles::Tile;
 use super::TileDrag;
 use crate::get_world_position_from_cursor_position;
-use crate::plugins::ui::IsPointerCaptured;
+use crate::plugins::{
+    game_ui::log::LogEvent,
+    tiled::{TiledMapItemLayer, TiledMapObjectLayer, TiledMapTileLayer},
+    ui::IsPointerCaptured,
+};
+use crate::tilemap::{get_tile_position, TileMapQuery, TileMapQueryItem};
+
+/// Gets the tile under the cursor
+fn get_cursor_tile_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    tilemap: &TileMapQueryItem,
+) -> Option<TilePos> {
+    get_world_position_from_cursor_position(window.cursor_position(), camera, camera_transform)
+        .and_then(|world_position| {
+            get_tile_position(
+                world_position,
+                tilemap.size,
+                tilemap.grid_size,
+                tilemap.r#type,
+                tilemap.transform,
+            )
+        })
+}
 
+/// Gets every tile, Object, and Item inside of the drag rectangle
+///
+/// Objects and Items are included if any part of their footprint is inside
+fn get_drag_tiles<'a>(
+    tile_drag: &TileDrag,
+    storages: impl Iterator<Item = &'a TileStorage>,
+) -> HashSet<Entity> {
+    let (min, max) = tile_drag.get_area();
+
+    let mut tiles = HashSet::new();
+    for storage in storages {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                tiles.extend(storage.get(&TilePos { x, y }));
+            }
+        }
+    }
+    tiles
+}
+
+#[allow(clippy::type_complexity)]
 pub(super) fn start_drag(
     mut commands: Commands,
     is_pointer_captured: Res<IsPointerCaptured>,
+    selection: Option<Res<Selection>>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<CameraTransformQuery, With<MainCamera>>,
+    tilemap_query: Query<TileMapQuery, With<TiledMapTileLayer>>,
 ) {
     if is_pointer_captured.0 {
         return;
@@ -17,53 +70,92 @@ pub(super) fn start_drag(
 
     let camera = camera_query.single();
     let window = window_query.single();
+    let Ok(tilemap) = tilemap_query.get_single() else {
+        return;
+    };
 
-    if let Some(world_position) = get_world_position_from_cursor_position(
-        window.cursor_position(),
-        camera.camera,
-        camera.global_transform,
-    ) {
-        info!("start drag at {}", world_position);
+    // starting a new selection drops the previous one
+    if let Some(selection) = selection {
+        selection.clear(&mut commands);
+        commands.remove_resource::<Selection>();
+    }
+
+    if let Some(position) =
+        get_cursor_tile_position(window, camera.camera, camera.global_transform, &tilemap)
+    {
+        debug!("start drag at {:?}", position);
 
-        // TODO: figure out what entity to add here (there are multiple tilemap layers)
-        commands.insert_resource(TileDrag::new(Entity::PLACEHOLDER));
+        commands.insert_resource(TileDrag::new(position));
     }
 }
 
 pub(super) fn stop_drag(
     mut commands: Commands,
-    //is_pointer_captured: Res<IsPointerCaptured>,
-    tile_drag: Option<ResMut<TileDrag>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    camera_query: Query<CameraTransformQuery, With<MainCamera>>,
+    tile_drag: Option<Res<TileDrag>>,
+    mut log_events: EventWriter<LogEvent>,
+    tile_query: Query<(), With<Tile>>,
+    object_query: Query<(), With<Object>>,
 ) {
-    if tile_drag.is_none() {
+    let Some(tile_drag) = tile_drag else {
         return;
-    }
+    };
 
-    /*if is_pointer_captured.0 {
-        return;
-    }*/
+    debug!("stop drag at {:?}", tile_drag.end);
 
-    let camera = camera_query.single();
-    let window = window_query.single();
+    commands.remove_resource::<TileDrag>();
 
-    if let Some(world_position) = get_world_position_from_cursor_position(
-        window.cursor_position(),
-        camera.camera,
-        camera.global_transform,
-    ) {
-        info!("stop drag at {}", world_position);
+    // clicking without dragging doesn't select anything
+    if tile_drag.start == tile_drag.end {
+        for tile in &tile_drag.tiles {
+            if let Some(mut entity) = commands.get_entity(*tile) {
+                entity.remove::<Selected>();
+            }
+        }
+        return;
+    }
 
-        commands.remove_resource::<TileDrag>();
+    let (min, max) = tile_drag.get_area();
+    let mut selection = Selection {
+        min,
+        max,
+        ..default()
+    };
+    for tile in &tile_drag.tiles {
+        if tile_query.contains(*tile) {
+            selection.tiles.insert(*tile);
+        } else if object_query.contains(*tile) {
+            selection.objects.insert(*tile);
+        } else {
+            selection.items.insert(*tile);
+        }
     }
+
+    log_events.send(LogEvent::new(format!(
+        "Selected {} Tiles, {} Objects, {} Items",
+        selection.tiles.len(),
+        selection.objects.len(),
+        selection.items.len()
+    )));
+    commands.insert_resource(selection);
 }
 
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
 pub(super) fn drag(
+    mut commands: Commands,
     is_pointer_captured: Res<IsPointerCaptured>,
     tile_drag: Option<ResMut<TileDrag>>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    tilemap_query: Query<TileMapQuery, With<TiledMapTileLayer>>,
+    storage_query: Query<
+        &TileStorage,
+        Or<(
+            With<TiledMapTileLayer>,
+            With<TiledMapObjectLayer>,
+            With<TiledMapItemLayer>,
+        )>,
+    >,
 ) {
     let Some(mut tile_drag) = tile_drag else {
         return;
@@ -75,13 +167,31 @@ pub(super) fn drag(
 
     let (camera, camera_transform) = camera_query.single();
     let window = window_query.single();
+    let Ok(tilemap) = tilemap_query.get_single() else {
+        return;
+    };
 
-    if let Some(world_position) =
-        get_world_position_from_cursor_position(window.cursor_position(), camera, camera_transform)
-    {
-        info!("drag at {}", world_position);
+    let Some(position) = get_cursor_tile_position(window, camera, camera_transform, &tilemap)
+    else {
+        return;
+    };
 
-        // TODO: figure out what entity to add here (there are multiple tilemap layers)
-        tile_drag.tiles.insert(Entity::PLACEHOLDER);
+    // the first drag also selects the start tile
+    if position == tile_drag.end && !tile_drag.tiles.is_empty() {
+        return;
+    }
+    tile_drag.end = position;
+
+    let tiles = get_drag_tiles(&tile_drag, storage_query.iter());
+    for tile in tile_drag.tiles.difference(&tiles) {
+        if let Some(mut entity) = commands.get_entity(*tile) {
+            entity.remove::<Selected>();
+        }
+    }
+    for tile in tiles.difference(&tile_drag.tiles) {
+        if let Some(mut entity) = commands.get_entity(*tile) {
+            entity.insert(Selected);
+        }
     }
+    tile_drag.tiles = tiles;
 }
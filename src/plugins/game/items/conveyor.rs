@@ -1,5 +1,157 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use super::crafter::Crafter;
+use super::harvester::Harvester;
+use super::ItemOutputs;
+use crate::data::{items::conveyor::*, resources::ResourceType};
+use crate::tilemap::{spawn_item, TileFootprint};
+
+/// Conveyor Item data component
+#[derive(Debug, Component, Deref, DerefMut)]
+pub struct Conveyor(pub ConveyorData);
+
+pub(super) fn spawn(
+    commands: &mut Commands,
+    storage: &mut TileStorage,
+    tilemap_id: Entity,
+    position: TilePos,
+    texture_index: u32,
+    data: &ConveyorData,
+) -> Entity {
+    let mut item_id = Entity::PLACEHOLDER;
+    commands.entity(tilemap_id).with_children(|parent| {
+        item_id = spawn_item(
+            parent,
+            storage,
+            tilemap_id,
+            position,
+            TileFootprint::default(),
+            texture_index,
+            true,
+            Conveyor(data.clone()),
+        );
+    });
+
+    // orient the tile to match the conveyor direction
+    commands
+        .entity(item_id)
+        .insert((data.direction.tile_flip(), ItemOutputs::default()));
+
+    item_id
+}
+
+/// Moves resources out of Harvesters and Crafters and along Conveyors
+///
+/// Transfers are decided up front from the current network state
+/// so a resource moves at most one link per-tick and full Conveyors
+/// back-pressure everything behind them
+#[allow(clippy::type_complexity)]
+pub(crate) fn transport(
+    mut conveyor_query: Query<(Entity, &mut Conveyor, &ItemOutputs)>,
+    mut crafter_query: Query<(&mut Crafter, &ItemOutputs), Without<Conveyor>>,
+    mut harvester_query: Query<
+        (&mut Harvester, &ItemOutputs),
+        (Without<Conveyor>, Without<Crafter>),
+    >,
+) {
+    let mut free_slots = conveyor_query
+        .iter()
+        .map(|(entity, conveyor, _)| (entity, conveyor.free_slots()))
+        .collect::<HashMap<_, _>>();
+
+    let mut transfers = Vec::default();
+    for (entity, conveyor, outputs) in &conveyor_query {
+        let Some(resource_type) = conveyor.peek() else {
+            continue;
+        };
+
+        let target = outputs.iter().copied().find(|target| {
+            // crafters are checked again when the transfer happens
+            // since more than one conveyor can feed them
+            crafter_query
+                .get(*target)
+                .map(|(crafter, _)| crafter.accepts(resource_type))
+                .unwrap_or_else(|_| reserve_slot(*target, &mut free_slots))
+        });
+        if let Some(target) = target {
+            transfers.push((entity, resource_type, target));
+        }
+    }
+
+    for (from, resource_type, to) in transfers {
+        let pushed = if let Ok((mut crafter, _)) = crafter_query.get_mut(to) {
+            crafter.push(resource_type)
+        } else if let Ok((_, mut conveyor, _)) = conveyor_query.get_mut(to) {
+            conveyor.push(resource_type)
+        } else {
+            false
+        };
+
+        if pushed {
+            if let Ok((_, mut conveyor, _)) = conveyor_query.get_mut(from) {
+                conveyor.pop();
+            }
+        }
+    }
+
+    // producers feed whatever is left
+    for (mut crafter, outputs) in &mut crafter_query {
+        let Some(resource_type) = crafter.get_output_type() else {
+            continue;
+        };
+
+        if crafter.output == 0 {
+            continue;
+        }
+
+        if push_output(resource_type, outputs, &mut free_slots, &mut conveyor_query) {
+            crafter.output -= 1;
+        }
+    }
+
+    for (mut harvester, outputs) in &mut harvester_query {
+        if harvester.current == 0 {
+            continue;
+        }
+
+        if push_output(
+            harvester.r#type,
+            outputs,
+            &mut free_slots,
+            &mut conveyor_query,
+        ) {
+            harvester.current -= 1;
+        }
+    }
+}
+
+fn reserve_slot(target: Entity, free_slots: &mut HashMap<Entity, usize>) -> bool {
+    free_slots
+        .get_mut(&target)
+        .filter(|free| **free > 0)
+        .map(|free| *free -= 1)
+        .is_some()
+}
+
+fn push_output(
+    resource_type: ResourceType,
+    outputs: &ItemOutputs,
+    free_slots: &mut HashMap<Entity, usize>,
+    conveyor_query: &mut Query<(Entity, &mut Conveyor, &ItemOutputs)>,
+) -> bool {
+    let Some(target) = outputs
+        .iter()
+        .copied()
+        .find(|target| reserve_slot(*target, free_slots))
+    else {
+        return false;
+    };
 
-pub(super) fn spawn() {
-    info!("TODO: create conveyor");
+    conveyor_query
+        .get_mut(target)
+        .map(|(_, mut conveyor, _)| conveyor.push(resource_type))
+        .unwrap_or_default()
 }
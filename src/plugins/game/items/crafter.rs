@@ -1,5 +1,44 @@
 use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
 
-pub(super) fn spawn() {
-    info!("TODO: create crafter");
+use super::ItemOutputs;
+use crate::data::items::crafter::*;
+use crate::tilemap::{spawn_item, TileFootprint};
+
+/// Crafter Item data component
+#[derive(Debug, Component, Deref, DerefMut)]
+pub struct Crafter(pub CrafterData);
+
+pub(super) fn spawn(
+    commands: &mut Commands,
+    storage: &mut TileStorage,
+    tilemap_id: Entity,
+    position: TilePos,
+    footprint: TileFootprint,
+    texture_index: u32,
+    data: &CrafterData,
+) -> Entity {
+    let mut item_id = Entity::PLACEHOLDER;
+    commands.entity(tilemap_id).with_children(|parent| {
+        item_id = spawn_item(
+            parent,
+            storage,
+            tilemap_id,
+            position,
+            footprint,
+            texture_index,
+            true,
+            Crafter(data.clone()),
+        );
+    });
+
+    commands.entity(item_id).insert(ItemOutputs::default());
+
+    item_id
+}
+
+pub(crate) fn craft(time: Res<Time>, mut crafter_query: Query<&mut Crafter>) {
+    for mut crafter in &mut crafter_query {
+        crafter.craft(time.delta_seconds());
+    }
 }
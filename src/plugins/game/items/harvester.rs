@@ -1,7 +1,84 @@
 use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
 
+use super::ItemOutputs;
 use crate::data::items::harvester::*;
+use crate::plugins::game_ui::log::LogEvent;
+use crate::tilemap::{spawn_item, TileAnimation, TileFootprint};
 
-pub(super) fn spawn(_data: &HarvesterData) {
-    info!("TODO: create harvester");
+/// Units harvested per simulation tick
+const HARVEST_RATE: u32 = 1;
+
+/// Harvester Item data component
+#[derive(Debug, Component, Deref, DerefMut)]
+pub struct Harvester(pub HarvesterData);
+
+/// Emitted when a Harvester has harvested all of its resources
+#[derive(Debug, Event)]
+pub struct HarvesterDepletedEvent(pub Entity);
+
+pub(super) fn spawn(
+    commands: &mut Commands,
+    storage: &mut TileStorage,
+    tilemap_id: Entity,
+    position: TilePos,
+    footprint: TileFootprint,
+    texture_index: u32,
+    data: &HarvesterData,
+) -> Entity {
+    let mut item_id = Entity::PLACEHOLDER;
+    commands.entity(tilemap_id).with_children(|parent| {
+        item_id = spawn_item(
+            parent,
+            storage,
+            tilemap_id,
+            position,
+            footprint,
+            texture_index,
+            true,
+            Harvester(data.clone()),
+        );
+    });
+
+    commands.entity(item_id).insert(ItemOutputs::default());
+
+    item_id
+}
+
+pub(crate) fn harvest(
+    mut harvester_query: Query<(Entity, &mut Harvester, Option<&mut TileAnimation>)>,
+    mut depleted_events: EventWriter<HarvesterDepletedEvent>,
+) {
+    for (entity, mut harvester, animation) in &mut harvester_query {
+        // full Harvesters wait for a Conveyor to take from them
+        if harvester.is_depleted() || harvester.is_full() {
+            continue;
+        }
+
+        harvester.harvest(HARVEST_RATE);
+
+        if harvester.is_depleted() {
+            if let Some(mut animation) = animation {
+                animation.paused = true;
+            }
+            depleted_events.send(HarvesterDepletedEvent(entity));
+        }
+    }
+}
+
+pub(crate) fn harvester_depleted_event_handler(
+    mut events: EventReader<HarvesterDepletedEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    harvester_query: Query<&Harvester>,
+) {
+    for event in events.read() {
+        let Ok(harvester) = harvester_query.get(event.0) else {
+            continue;
+        };
+
+        log_events.send(LogEvent::new(format!(
+            "Harvester depleted ({} {})",
+            harvester.current, harvester.r#type
+        )));
+    }
 }
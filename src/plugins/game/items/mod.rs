@@ -1,23 +1,35 @@
-mod conveyor;
-mod crafter;
-mod harvester;
+pub mod conveyor;
+pub mod crafter;
+pub mod harvester;
 
-use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_ecs_tilemap::prelude::*;
 use bevy_mod_picking::prelude::*;
+use strum::IntoEnumIterator;
 
 use super::camera::MainCamera;
+use super::history::ActionRecorder;
 use super::inventory::{Inventory, InventoryUpdatedEvent};
 use super::objects::Object;
-use super::ItemInfo;
-use crate::data::items::{harvester::HarvesterData, ItemType};
+use super::tiles::Tile;
+use super::{GameAssets, ItemInfo};
+use crate::assets::{recipes::Recipes, registry::Registry};
+use crate::data::{
+    direction::Direction,
+    history::{Action, Change},
+    items::{conveyor::ConveyorData, crafter::CrafterData, harvester::HarvesterData, ItemType},
+    registry::ItemDefinition,
+    save::SavedItem,
+};
 use crate::get_world_position_from_cursor_position;
 use crate::plugins::{
     game_ui::{inventory::InventoryDragImage, item_info::ItemInfoWindow, log::LogEvent},
-    tiled::{TiledMapItemClickEvent, TiledMapObjectLayer, TiledMapTileLayer},
+    tiled::{TiledMapItemClickEvent, TiledMapItemLayer, TiledMapObjectLayer, TiledMapTileLayer},
 };
 use crate::tilemap::{
-    despawn_object, despawn_tile, get_tile_position, TileMapQuery, TileMapQueryMut,
+    despawn_object, despawn_tile, get_tile_position, TileFootprint, TileMapQuery, TileMapQueryMut,
 };
 use crate::ui::{check_click_event, simple_tween_ui_object, TweenId};
 
@@ -29,6 +41,63 @@ pub struct ItemDragObject(pub Entity);
 #[derive(Debug, Resource)]
 pub struct ItemDragTile(pub Entity);
 
+/// The Object or Tile an Item is being dragged over, if any
+#[derive(SystemParam)]
+pub(super) struct ItemDragTargets<'w> {
+    object: Option<Res<'w, ItemDragObject>>,
+    tile: Option<Res<'w, ItemDragTile>>,
+}
+
+/// Item transport network resource
+///
+/// Tracks every placed Item by position so that Item links
+/// can be updated without scanning the Item layers
+#[derive(Debug, Default, Resource)]
+pub struct TransportNetwork {
+    items: HashMap<TilePos, Entity>,
+
+    // positions that changed since the links were last updated
+    dirty: HashSet<TilePos>,
+}
+
+impl TransportNetwork {
+    #[inline]
+    pub fn get(&self, position: &TilePos) -> Option<Entity> {
+        self.items.get(position).copied()
+    }
+
+    #[inline]
+    pub fn contains(&self, position: &TilePos) -> bool {
+        self.items.contains_key(position)
+    }
+
+    /// Registers an Item at every position covered by its footprint
+    pub fn insert(&mut self, position: TilePos, footprint: TileFootprint, item: Entity) {
+        for position in footprint.positions(position) {
+            self.items.insert(position, item);
+            self.dirty.insert(position);
+        }
+    }
+
+    /// Unregisters an Item from every position covered by its footprint
+    pub fn remove(&mut self, position: TilePos, footprint: TileFootprint) {
+        for position in footprint.positions(position) {
+            self.items.remove(&position);
+            self.dirty.insert(position);
+        }
+    }
+}
+
+/// Item output links component
+///
+/// The Items that an Item can hand resources off to
+#[derive(Debug, Default, Component, Deref)]
+pub struct ItemOutputs(pub Vec<Entity>);
+
+/// The direction newly placed Items will face
+#[derive(Debug, Default, Reflect, Resource, Deref)]
+pub struct PlacementDirection(pub Direction);
+
 /// Emitted when an Item is being dragged
 #[derive(Debug, Event)]
 pub struct ItemDragEvent {
@@ -77,16 +146,82 @@ impl ItemDropEvent {
     }
 }
 
+/// Emitted to spawn an Item on the Item layer
 #[derive(Debug, Event)]
 pub enum SpawnItemEvent {
-    Harvester(HarvesterData),
-    Conveyor,
-    Crafter,
+    Harvester(TilePos, TileFootprint, HarvesterData),
+    Conveyor(TilePos, ConveyorData),
+    Crafter(TilePos, TileFootprint, CrafterData),
+}
+
+impl SpawnItemEvent {
+    #[inline]
+    pub fn get_type(&self) -> ItemType {
+        match self {
+            Self::Harvester(..) => ItemType::Harvester,
+            Self::Conveyor(..) => ItemType::Conveyor,
+            Self::Crafter(..) => ItemType::Crafter,
+        }
+    }
+}
+
+impl From<SavedItem> for SpawnItemEvent {
+    fn from(item: SavedItem) -> Self {
+        match item {
+            SavedItem::Harvester {
+                position,
+                footprint,
+                data,
+            } => Self::Harvester(position, footprint, data),
+            SavedItem::Conveyor { position, data } => Self::Conveyor(position, data),
+            SavedItem::Crafter {
+                position,
+                footprint,
+                data,
+            } => Self::Crafter(position, footprint, data),
+        }
+    }
 }
 
 const CAN_DROP_COLOR: Color = Color::srgba(0.0, 1.0, 0.0, 0.5);
 const NO_DROP_COLOR: Color = Color::srgba(1.0, 0.0, 0.0, 0.5);
 
+/// Checks that an Item dropped on the Tile at the given position
+/// stays on the map and doesn't overlap any Objects or other Items
+pub(super) fn can_place_item(
+    item: &ItemDefinition,
+    position: TilePos,
+    map_size: &TilemapSize,
+    object_storage: &TileStorage,
+    network: &TransportNetwork,
+) -> bool {
+    item.can_drop_on_tile()
+        && item.footprint.fits(position, map_size)
+        && item
+            .footprint
+            .positions(position)
+            .all(|position| object_storage.get(&position).is_none() && !network.contains(&position))
+}
+
+/// Checks that every Tile an Item dropped on the given position would cover
+/// allows the Item to be built on it
+///
+/// Footprint tiles that are off the map can't be built on
+pub(super) fn can_build_on_tiles(
+    item: &ItemDefinition,
+    position: TilePos,
+    tile_storage: &TileStorage,
+    tile_data_query: &Query<&Tile>,
+) -> bool {
+    item.footprint
+        .tiles(position, tile_storage)
+        .all(|tile_entity| {
+            tile_entity
+                .and_then(|tile_entity| tile_data_query.get(tile_entity).ok())
+                .is_some_and(|tile| item.can_build_on(tile))
+        })
+}
+
 // TODO: we might be able to simplify this by splitting it into
 // an object handler and a tile handler? would need to not consume the events for that
 // and would need to make sure we handle objects before tiles ...
@@ -101,14 +236,22 @@ pub(super) fn item_drag_event_handler(
     mut drag_tile: Option<ResMut<ItemDragTile>>,
     tilemap_layer_query: Query<TileMapQuery, With<TiledMapTileLayer>>,
     mut tile_query: Query<&mut TileColor, Without<Object>>,
+    tile_data_query: Query<&Tile>,
+    network: Res<TransportNetwork>,
+    registry: Res<Registry>,
 ) {
     let (camera, camera_transform) = camera_query.single();
 
+    // highlighted tiles are returned to their layer color
+    let object_layer_color = object_layer_query.single().color.0;
+    let tile_layer_color = tilemap_layer_query.single().color.0;
+
     // TODO: should we just deal with the first (or last?) event?
     // what does it even mean to have more than one of these ...
     for event in events.read() {
         // TODO: pretty sure this is missing some edge cases
 
+        let item = registry.get_item(event.item_type);
         let world_position = get_world_position_from_cursor_position(
             event.cursor_position,
             camera,
@@ -128,7 +271,7 @@ pub(super) fn item_drag_event_handler(
                     // reset and remove previous tile
                     if let Some(drag_tile) = &drag_tile {
                         let mut color = tile_query.get_mut(drag_tile.0).unwrap();
-                        color.0 = Color::default();
+                        color.0 = tile_layer_color;
                         commands.remove_resource::<ItemDragTile>();
                     }
 
@@ -136,10 +279,10 @@ pub(super) fn item_drag_event_handler(
                     if let Some(drag_object) = &mut drag_object {
                         if drag_object.0 != object_entity {
                             let (_, mut color) = object_query.get_mut(drag_object.0).unwrap();
-                            color.0 = Color::default();
+                            color.0 = object_layer_color;
 
                             let (object, mut color) = object_query.get_mut(object_entity).unwrap();
-                            color.0 = if event.item_type.can_drop_on_object(object.get_type()) {
+                            color.0 = if item.can_drop_on_object(object.get_type()) {
                                 CAN_DROP_COLOR
                             } else {
                                 NO_DROP_COLOR
@@ -148,7 +291,7 @@ pub(super) fn item_drag_event_handler(
                         }
                     } else {
                         let (object, mut color) = object_query.get_mut(object_entity).unwrap();
-                        color.0 = if event.item_type.can_drop_on_object(object.get_type()) {
+                        color.0 = if item.can_drop_on_object(object.get_type()) {
                             CAN_DROP_COLOR
                         } else {
                             NO_DROP_COLOR
@@ -172,7 +315,7 @@ pub(super) fn item_drag_event_handler(
                     // reset and remove previous object
                     if let Some(drag_object) = &drag_object {
                         let (_, mut color) = object_query.get_mut(drag_object.0).unwrap();
-                        color.0 = Color::default();
+                        color.0 = object_layer_color;
                         commands.remove_resource::<ItemDragObject>();
                     }
 
@@ -180,10 +323,21 @@ pub(super) fn item_drag_event_handler(
                     if let Some(drag_tile) = &mut drag_tile {
                         if drag_tile.0 != tile_entity {
                             let mut color = tile_query.get_mut(drag_tile.0).unwrap();
-                            color.0 = Color::default();
+                            color.0 = tile_layer_color;
 
                             let mut color = tile_query.get_mut(tile_entity).unwrap();
-                            color.0 = if event.item_type.can_drop_on_tile() {
+                            color.0 = if can_place_item(
+                                item,
+                                tile_position,
+                                tilemap.size,
+                                object_tilemap.storage,
+                                &network,
+                            ) && can_build_on_tiles(
+                                item,
+                                tile_position,
+                                tilemap.storage,
+                                &tile_data_query,
+                            ) {
                                 CAN_DROP_COLOR
                             } else {
                                 NO_DROP_COLOR
@@ -192,7 +346,18 @@ pub(super) fn item_drag_event_handler(
                         }
                     } else {
                         let mut color = tile_query.get_mut(tile_entity).unwrap();
-                        color.0 = if event.item_type.can_drop_on_tile() {
+                        color.0 = if can_place_item(
+                            item,
+                            tile_position,
+                            tilemap.size,
+                            object_tilemap.storage,
+                            &network,
+                        ) && can_build_on_tiles(
+                            item,
+                            tile_position,
+                            tilemap.storage,
+                            &tile_data_query,
+                        ) {
                             CAN_DROP_COLOR
                         } else {
                             NO_DROP_COLOR
@@ -215,8 +380,8 @@ pub(super) fn item_drop_event_handler(
     mut commands: Commands,
     mut events: EventReader<ItemDropEvent>,
     mut inventory: ResMut<Inventory>,
-    drag_object: Option<Res<ItemDragObject>>,
-    drag_tile: Option<Res<ItemDragTile>>,
+    drag_targets: ItemDragTargets,
+    mut recorder: ActionRecorder,
     mut inventory_updated_events: EventWriter<InventoryUpdatedEvent>,
     mut spawn_item_events: EventWriter<SpawnItemEvent>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
@@ -224,19 +389,28 @@ pub(super) fn item_drop_event_handler(
         Query<TileMapQueryMut, With<TiledMapObjectLayer>>,
         Query<TileMapQueryMut, With<TiledMapTileLayer>>,
     )>,
-    mut object_query: Query<(&Object, &mut TileColor)>,
+    mut object_query: Query<(&Object, &TilePos, &TileFootprint, &mut TileColor)>,
     mut tile_query: Query<&mut TileColor, Without<Object>>,
+    tile_data_query: Query<&Tile>,
     mut drag_image_query: Query<&mut Visibility, With<InventoryDragImage>>,
+    network: Res<TransportNetwork>,
+    placement_direction: Res<PlacementDirection>,
+    registry: Res<Registry>,
 ) {
     // TODO: if we drop on a UI window, this should fail
 
     let (camera, camera_transform) = camera_query.single();
 
+    // highlighted tiles are returned to their layer color
+    let object_layer_color = tilemap_layer_set.p0().single().color.0;
+    let tile_layer_color = tilemap_layer_set.p1().single().color.0;
+
     // TODO: should we just deal with the first (or last?) event?
     // what does it even mean to have more than one of these ...
     for event in events.read() {
         // TODO: pretty sure this is missing some edge cases
 
+        let item = registry.get_item(event.item_type);
         let world_position = get_world_position_from_cursor_position(
             event.cursor_position,
             camera,
@@ -244,38 +418,49 @@ pub(super) fn item_drop_event_handler(
         );
         if let Some(world_position) = world_position {
             // first check for objects
-            if let Some(drag_object) = &drag_object {
-                let (object, mut color) = object_query.get_mut(drag_object.0).unwrap();
-                color.0 = Color::default();
+            if let Some(drag_object) = &drag_targets.object {
+                let (object, object_position, object_footprint, mut color) =
+                    object_query.get_mut(drag_object.0).unwrap();
+                color.0 = object_layer_color;
                 commands.remove_resource::<ItemDragObject>();
 
                 let mut object_layer_query = tilemap_layer_set.p0();
                 let mut object_tilemap = object_layer_query.single_mut();
-                let object_position = get_tile_position(
-                    world_position,
-                    object_tilemap.size,
-                    object_tilemap.grid_size,
-                    object_tilemap.r#type,
-                    object_tilemap.transform,
-                )
-                .unwrap();
 
-                let object_id = object_tilemap.storage.get(&object_position).unwrap();
-                if event.item_type.can_drop_on_object(object.get_type()) {
+                // multi-tile objects are replaced as a single unit
+                if item.can_drop_on_object(object.get_type()) {
+                    let mut action = Action::new(format!("Place {}", event.item_type));
+                    action.push(Change::Inventory(event.item_type, -1));
+
+                    let saved_object = recorder.save_object(drag_object.0);
                     if event.item_type.on_drop_object(
                         &mut inventory.0,
                         &mut inventory_updated_events,
                         object,
+                        *object_position,
+                        *object_footprint,
                         &mut spawn_item_events,
                     ) {
+                        action
+                            .changes
+                            .extend(saved_object.map(Change::DespawnObject));
                         despawn_object(
                             &mut commands,
                             &mut object_tilemap.storage,
-                            object_id,
-                            object_position,
+                            drag_object.0,
+                            *object_position,
+                            *object_footprint,
                         );
                     }
 
+                    // only Harvesters are dropped on Objects
+                    action.push(Change::SpawnItem(SavedItem::Harvester {
+                        position: *object_position,
+                        footprint: *object_footprint,
+                        data: HarvesterData::from(&object.0),
+                    }));
+                    recorder.record(action);
+
                     let mut visibility = drag_image_query.single_mut();
                     *visibility = Visibility::Hidden;
                 } else {
@@ -295,13 +480,13 @@ pub(super) fn item_drop_event_handler(
             }
 
             // then check for tiles
-            if let Some(drag_tile) = &drag_tile {
+            if let Some(drag_tile) = &drag_targets.tile {
                 let mut color = tile_query.get_mut(drag_tile.0).unwrap();
-                color.0 = Color::default();
+                color.0 = tile_layer_color;
                 commands.remove_resource::<ItemDragTile>();
 
-                let mut tilemap_layer_query = tilemap_layer_set.p1();
-                let mut tilemap = tilemap_layer_query.single_mut();
+                let tilemap_layer_query = tilemap_layer_set.p1();
+                let tilemap = tilemap_layer_query.single();
                 let tile_position = get_tile_position(
                     world_position,
                     tilemap.size,
@@ -310,18 +495,67 @@ pub(super) fn item_drop_event_handler(
                     tilemap.transform,
                 )
                 .unwrap();
+                let map_size = *tilemap.size;
+
+                // the footprint has to fit on the map before any of its tiles are looked at
+                let object_layer_query = tilemap_layer_set.p0();
+                let can_place = can_place_item(
+                    item,
+                    tile_position,
+                    &map_size,
+                    object_layer_query.single().storage,
+                    &network,
+                ) && can_build_on_tiles(
+                    item,
+                    tile_position,
+                    tilemap_layer_set.p1().single().storage,
+                    &tile_data_query,
+                );
+
+                if can_place {
+                    let mut tilemap_layer_query = tilemap_layer_set.p1();
+                    let mut tilemap = tilemap_layer_query.single_mut();
+                    let mut action = Action::new(format!("Place {}", event.item_type));
+                    action.push(Change::Inventory(event.item_type, -1));
 
-                let tile_id = tilemap.storage.get(&tile_position).unwrap();
-                if event.item_type.can_drop_on_tile() {
                     if event.item_type.on_drop_tile(
                         &mut commands,
                         &mut inventory.0,
                         &mut inventory_updated_events,
+                        tile_position,
+                        item.footprint,
+                        placement_direction.0,
                         &mut spawn_item_events,
                     ) {
-                        despawn_tile(&mut commands, &mut tilemap.storage, tile_id, tile_position);
+                        for position in item.footprint.positions(tile_position) {
+                            if let Some(tile_id) = tilemap.storage.get(&position) {
+                                action
+                                    .changes
+                                    .extend(recorder.save_tile(tile_id).map(Change::DespawnTile));
+                                despawn_tile(
+                                    &mut commands,
+                                    &mut tilemap.storage,
+                                    tile_id,
+                                    position,
+                                );
+                            }
+                        }
                     }
 
+                    action.push(Change::SpawnItem(match event.item_type {
+                        ItemType::Conveyor => SavedItem::Conveyor {
+                            position: tile_position,
+                            data: ConveyorData::new(placement_direction.0),
+                        },
+                        ItemType::Crafter => SavedItem::Crafter {
+                            position: tile_position,
+                            footprint: item.footprint,
+                            data: CrafterData::default(),
+                        },
+                        ItemType::Harvester => unreachable!(),
+                    }));
+                    recorder.record(action);
+
                     let mut visibility = drag_image_query.single_mut();
                     *visibility = Visibility::Hidden;
                 } else {
@@ -343,16 +577,182 @@ pub(super) fn item_drop_event_handler(
     }
 }
 
-pub(super) fn spawn_item_event_handler(mut events: EventReader<SpawnItemEvent>) {
+pub(super) fn spawn_item_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<SpawnItemEvent>,
+    mut item_layer_query: Query<(Entity, &TiledMapItemLayer, &mut TileStorage)>,
+    mut network: ResMut<TransportNetwork>,
+    game_assets: Res<GameAssets>,
+    recipes: Res<Assets<Recipes>>,
+    registry: Res<Registry>,
+) {
     for event in events.read() {
-        match event {
-            SpawnItemEvent::Harvester(harvester_data) => harvester::spawn(harvester_data),
-            SpawnItemEvent::Conveyor => conveyor::spawn(),
-            SpawnItemEvent::Crafter => crafter::spawn(),
+        let item_type = event.get_type();
+        let sprite = &registry.get_item(item_type).sprite;
+        let texture_index = sprite.index;
+        let Some((item_layer_id, _, mut item_storage)) = item_layer_query
+            .iter_mut()
+            .find(|(_, layer, _)| layer.0 == item_type)
+        else {
+            warn!("Missing item layer for {}", item_type);
+            continue;
+        };
+
+        let mut animation = sprite.get_animation();
+
+        let item_id = match event {
+            SpawnItemEvent::Harvester(position, footprint, harvester_data) => {
+                let item_id = harvester::spawn(
+                    &mut commands,
+                    &mut item_storage,
+                    item_layer_id,
+                    *position,
+                    *footprint,
+                    texture_index,
+                    harvester_data,
+                );
+                network.insert(*position, *footprint, item_id);
+
+                // depleted harvesters aren't running
+                if let Some(animation) = &mut animation {
+                    animation.paused = harvester_data.is_depleted();
+                }
+
+                item_id
+            }
+            SpawnItemEvent::Conveyor(position, conveyor_data) => {
+                let item_id = conveyor::spawn(
+                    &mut commands,
+                    &mut item_storage,
+                    item_layer_id,
+                    *position,
+                    texture_index,
+                    conveyor_data,
+                );
+                network.insert(*position, TileFootprint::default(), item_id);
+                item_id
+            }
+            SpawnItemEvent::Crafter(position, footprint, crafter_data) => {
+                // new crafters default to the first recipe
+                let mut crafter_data = crafter_data.clone();
+                if crafter_data.get_recipe().is_none() {
+                    crafter_data.set_recipe(
+                        recipes
+                            .get(&game_assets.recipes)
+                            .and_then(|recipes| recipes.recipes.first())
+                            .cloned(),
+                    );
+                }
+
+                let item_id = crafter::spawn(
+                    &mut commands,
+                    &mut item_storage,
+                    item_layer_id,
+                    *position,
+                    *footprint,
+                    texture_index,
+                    &crafter_data,
+                );
+                network.insert(*position, *footprint, item_id);
+                item_id
+            }
+        };
+
+        if let Some(animation) = animation {
+            commands.entity(item_id).insert(animation);
+        }
+    }
+}
+
+/// Re-links Items around any positions that changed in the transport network
+#[allow(clippy::type_complexity)]
+pub(super) fn update_transport_links(
+    mut network: ResMut<TransportNetwork>,
+    item_layer_query: Query<&TilemapSize, With<TiledMapItemLayer>>,
+    conveyor_query: Query<&conveyor::Conveyor>,
+    producer_query: Query<(), Or<(With<harvester::Harvester>, With<crafter::Crafter>)>>,
+    crafter_query: Query<(), With<crafter::Crafter>>,
+    mut outputs_query: Query<(&TilePos, &TileFootprint, &mut ItemOutputs)>,
+) {
+    if network.dirty.is_empty() {
+        return;
+    }
+
+    let Some(map_size) = item_layer_query.iter().next() else {
+        return;
+    };
+
+    // changing a position can change the links of all of its neighbors
+    let mut items = HashSet::new();
+    for position in std::mem::take(&mut network.dirty) {
+        items.extend(
+            Direction::iter()
+                .filter_map(|direction| direction.offset(&position, map_size))
+                .chain(std::iter::once(position))
+                .filter_map(|position| network.get(&position)),
+        );
+    }
+
+    // crafters and conveyors that aren't facing back at us can be fed
+    let accepts_from = |target: Entity, direction: Direction| {
+        conveyor_query
+            .get(target)
+            .map(|conveyor| conveyor.direction != direction.opposite())
+            .unwrap_or_else(|_| crafter_query.contains(target))
+    };
+
+    // producers only feed conveyors
+    let conveyor_accepts_from = |target: Entity, direction: Direction| {
+        conveyor_query.contains(target) && accepts_from(target, direction)
+    };
+
+    for item in items {
+        let Ok((position, footprint, mut outputs)) = outputs_query.get_mut(item) else {
+            continue;
+        };
+        outputs.0.clear();
+
+        if let Ok(conveyor) = conveyor_query.get(item) {
+            // conveyors feed whatever they are facing
+            let target = conveyor
+                .direction
+                .offset(position, map_size)
+                .and_then(|target_position| network.get(&target_position));
+            if let Some(target) = target {
+                if accepts_from(target, conveyor.direction) {
+                    outputs.0.push(target);
+                }
+            }
+        } else if producer_query.contains(item) {
+            // producers feed any neighbor around their footprint
+            for covered in footprint.positions(*position) {
+                for direction in Direction::iter() {
+                    let target = direction
+                        .offset(&covered, map_size)
+                        .filter(|target_position| !footprint.contains(*position, target_position))
+                        .and_then(|target_position| network.get(&target_position));
+                    if let Some(target) = target {
+                        if conveyor_accepts_from(target, direction) && !outputs.contains(&target) {
+                            outputs.0.push(target);
+                        }
+                    }
+                }
+            }
         }
     }
 }
 
+pub(super) fn rotate_placement_direction(
+    mut placement_direction: ResMut<PlacementDirection>,
+    mut log_events: EventWriter<LogEvent>,
+) {
+    placement_direction.0 = placement_direction.rotate_clockwise();
+    log_events.send(LogEvent::new(format!(
+        "Placing Items facing {}",
+        placement_direction.0
+    )));
+}
+
 pub(super) fn item_click_event_handler(
     mut commands: Commands,
     mut events: EventReader<TiledMapItemClickEvent>,
@@ -1,21 +1,40 @@
 pub mod camera;
+pub mod editor;
+pub mod export;
+pub mod history;
 pub mod input;
 pub mod inventory;
 pub mod items;
 pub mod objects;
+pub mod save;
+pub mod selection;
+pub mod tiles;
 
-use std::collections::HashSet;
+use std::collections::{hash_map::RandomState, HashSet};
+use std::hash::{BuildHasher, Hasher};
 
 use bevy::{
     input::common_conditions::*, prelude::*, render::camera::ScalingMode, window::PrimaryWindow,
 };
+use bevy_ecs_tilemap::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 
-use crate::assets::tiled::TiledMap;
+use crate::assets::{
+    levels::Levels,
+    recipes::{Recipes, RecipesLoader},
+    registry::{Registry, RegistryLoader, REGISTRY_PATH},
+    tiled::TiledMap,
+};
 use crate::audio::start_music;
 use crate::cleanup_state;
-use crate::data::items::ItemType;
-use crate::plugins::{audio::AudioAssets, tiled::TiledMapBundle};
+use crate::data::levels::LevelDefinition;
+use crate::plugins::{
+    audio::AudioAssets,
+    game_ui::log::LogEvent,
+    loading::AssetTracker,
+    tiled::{TiledMapBundle, TiledMapLoadFailedEvent},
+};
+use crate::tiled::{generator::get_generated_map_path, TiledMapValidationError};
 use crate::AppState;
 
 /// Pause game sub-state
@@ -27,6 +46,17 @@ pub enum IsPaused {
     Paused,
 }
 
+/// Map editor sub-state
+///
+/// The simulation is stopped while editing
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates, Reflect)]
+#[source(AppState = AppState::InGame)]
+pub enum IsEditing {
+    #[default]
+    Playing,
+    Editing,
+}
+
 /// InGame state tag
 #[derive(Debug, Component)]
 pub struct OnInGame;
@@ -35,67 +65,88 @@ pub struct OnInGame;
 #[derive(Debug, Default, Reflect, Resource)]
 pub struct GameAssets {
     pub map: Handle<TiledMap>,
+    pub recipes: Handle<Recipes>,
 
-    pub resources_image: Handle<Image>,
-    pub resources_atlas: Handle<TextureAtlasLayout>,
-
-    pub harvester_image: Handle<Image>,
-    pub harvester_atlas: Handle<TextureAtlasLayout>,
-
-    pub conveyor_image: Handle<Image>,
-    pub conveyor_atlas: Handle<TextureAtlasLayout>,
-
-    pub crafter_image: Handle<Image>,
-    pub crafter_atlas: Handle<TextureAtlasLayout>,
+    /// Resource and Item definitions (and their sprites)
+    pub registry: Handle<Registry>,
 }
 
-impl GameAssets {
-    // TODO: well this kinda sucks
-    #[inline]
-    pub fn is_loaded(
-        &self,
-        map_assets: &Res<Assets<TiledMap>>,
-        image_assets: &Res<Assets<Image>>,
-        atlas_assets: &Res<Assets<TextureAtlasLayout>>,
-    ) -> bool {
-        map_assets.contains(&self.map)
-            && image_assets.contains(&self.resources_image)
-            && image_assets.contains(&self.harvester_image)
-            && atlas_assets.contains(&self.resources_atlas)
-            && atlas_assets.contains(&self.harvester_atlas)
-        // TODO: conveyor, crafter
-    }
-
-    #[inline]
-    pub fn get_item_texture(&self, item_type: ItemType) -> Handle<Image> {
-        match item_type {
-            ItemType::Harvester => self.harvester_image.clone(),
-            ItemType::Conveyor => self.conveyor_image.clone(),
-            ItemType::Crafter => self.crafter_image.clone(),
+/// The level being played
+///
+/// Set before loading the game assets, the level map is loaded with them
+#[derive(Debug, Clone, Resource, Deref)]
+pub struct CurrentLevel(pub LevelDefinition);
+
+impl CurrentLevel {
+    /// Starts a level
+    ///
+    /// Generated levels get a new random seed
+    pub fn new(level: &LevelDefinition) -> Self {
+        let mut level = level.clone();
+        if level.generated {
+            level.map = get_generated_map_path(RandomState::new().build_hasher().finish());
         }
+        Self(level)
     }
 
-    #[inline]
-    pub fn get_item_atlas(&self, item_type: ItemType) -> Handle<TextureAtlasLayout> {
-        match item_type {
-            ItemType::Harvester => self.harvester_atlas.clone(),
-            ItemType::Conveyor => self.conveyor_atlas.clone(),
-            ItemType::Crafter => self.crafter_atlas.clone(),
-        }
+    /// Gets the level for a saved game
+    ///
+    /// Saves only know their map, maps that are no longer in
+    /// the level manifest are still playable
+    pub fn from_map(map: impl Into<String>, levels: Option<&Levels>) -> Self {
+        let map = map.into();
+        Self(
+            levels
+                .and_then(|levels| levels.get_level_by_map(&map))
+                .map(|level| LevelDefinition {
+                    map: map.clone(),
+                    ..level.clone()
+                })
+                .unwrap_or_else(|| LevelDefinition {
+                    name: map.clone(),
+                    map,
+                    generated: false,
+                    inventory: default(),
+                }),
+        )
     }
 }
 
+/// Marquee selection in progress
 #[derive(Debug, Default, Reflect, Resource)]
 pub struct TileDrag {
+    /// The tile the drag started on
+    pub start: TilePos,
+
+    /// The tile currently under the cursor
+    pub end: TilePos,
+
+    /// Every tile, Object, and Item inside of the drag rectangle
     pub tiles: HashSet<Entity>,
 }
 
 impl TileDrag {
-    pub fn new(start: Entity) -> Self {
+    pub fn new(start: TilePos) -> Self {
         Self {
-            tiles: HashSet::from([start]),
+            start,
+            end: start,
+            tiles: HashSet::default(),
         }
     }
+
+    /// Gets the (inclusive) bottom-left and top-right corners of the drag rectangle
+    pub fn get_area(&self) -> (TilePos, TilePos) {
+        (
+            TilePos {
+                x: self.start.x.min(self.end.x),
+                y: self.start.y.min(self.end.y),
+            },
+            TilePos {
+                x: self.start.x.max(self.end.x),
+                y: self.start.y.max(self.end.y),
+            },
+        )
+    }
 }
 
 /// Tracks what Object is being viewed in the ObjectInfo window
@@ -106,7 +157,17 @@ pub struct ObjectInfo(pub Entity);
 #[derive(Debug, Reflect, Resource, Deref)]
 pub struct ItemInfo(pub Entity);
 
-// these should be less than (systems::tiled::MIN_TILEMAP_WIDTH / HEIGHT * systems::tiled::TILE_WIDTH / HEIGHT)
+/// Map validation failure report resource
+#[derive(Debug, Resource)]
+pub struct MapValidationReport {
+    pub name: String,
+    pub errors: Vec<TiledMapValidationError>,
+}
+
+/// Simulation (FixedUpdate) ticks per-second
+const SIMULATION_RATE: f64 = 4.0;
+
+// these should be less than (tiled::MIN_TILEMAP_WIDTH / HEIGHT * tiled::TILE_WIDTH / HEIGHT)
 const VIEW_WIDTH: f32 = 800.0;
 const VIEW_HEIGHT: f32 = 600.0;
 
@@ -115,38 +176,116 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_sub_state::<IsPaused>()
+        app.init_asset::<Recipes>()
+            .register_asset_loader(RecipesLoader)
+            .init_asset::<Registry>()
+            .register_asset_loader(RegistryLoader)
+            .add_sub_state::<IsPaused>()
             .enable_state_scoped_entities::<IsPaused>()
+            .add_sub_state::<IsEditing>()
             .add_event::<items::ItemDragEvent>()
             .add_event::<items::ItemDropEvent>()
             .add_event::<items::SpawnItemEvent>()
+            .add_event::<items::harvester::HarvesterDepletedEvent>()
             .add_event::<inventory::InventoryUpdatedEvent>()
+            .add_event::<save::SaveGameEvent>()
+            .add_event::<save::LoadGameEvent>()
+            .add_event::<export::ExportMapEvent>()
+            .add_event::<editor::EditObjectEvent>()
+            .add_event::<selection::SelectionActionEvent>()
+            .add_event::<history::HistoryEvent>()
             .add_systems(OnEnter(AppState::LoadAssets), load_assets)
             .add_systems(
                 Update,
                 (wait_for_assets,).run_if(in_state(AppState::LoadAssets)),
             )
+            // a failed load never gets to InGame, so InGame's exit never tears it down
+            .add_systems(
+                OnExit(AppState::LoadAssets),
+                (abandon_load, cleanup_state::<OnInGame>).run_if(not(in_state(AppState::InGame))),
+            )
+            .add_systems(
+                Update,
+                (
+                    map_load_failed_event_handler,
+                    show_map_validation_report
+                        .run_if(resource_exists::<MapValidationReport>)
+                        .after(map_load_failed_event_handler),
+                    // loading is available from the main menu
+                    save::load_game_event_handler,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    save::save_game_event_handler,
+                    export::export_map_event_handler,
+                    save::apply_pending_load
+                        .run_if(resource_exists::<save::PendingLoad>)
+                        .before(items::spawn_item_event_handler),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
             .add_systems(OnEnter(AppState::InGame), enter)
+            .add_systems(OnEnter(IsEditing::Editing), editor::enter)
+            .add_systems(OnExit(IsEditing::Editing), editor::exit)
             .add_systems(
                 Update,
                 (
                     pause_game.run_if(input_just_released(KeyCode::Escape)),
                     camera::pan,
-                    input::start_drag.run_if(input_just_pressed(MouseButton::Left)),
-                    input::stop_drag.run_if(input_just_released(MouseButton::Left)),
-                    // TODO: instead of "just_pressed" we should check for a Drag resource existing
-                    // (eg. resource_exists::<DragOperation>)
-                    input::drag
-                        .run_if(input_pressed(MouseButton::Left))
-                        .after(input::start_drag),
+                    (
+                        input::start_drag.run_if(input_just_pressed(MouseButton::Left)),
+                        input::stop_drag.run_if(input_just_released(MouseButton::Left)),
+                        // TODO: instead of "just_pressed" we should check for a Drag resource existing
+                        // (eg. resource_exists::<DragOperation>)
+                        input::drag
+                            .run_if(input_pressed(MouseButton::Left))
+                            .after(input::start_drag),
+                    )
+                        .run_if(in_state(IsEditing::Playing)),
+                    (
+                        selection::selection_shortcuts,
+                        selection::clear_selection_event_handler,
+                        selection::deconstruct_selection_event_handler,
+                        selection::copy_selection_event_handler,
+                        selection::paste_selection_event_handler
+                            .before(items::spawn_item_event_handler),
+                        history::history_shortcuts,
+                        history::history_event_handler.before(items::spawn_item_event_handler),
+                    )
+                        .run_if(in_state(IsEditing::Playing)),
+                    (
+                        selection::highlight_selected,
+                        selection::unhighlight_deselected,
+                    ),
+                    (
+                        editor::use_tool.run_if(input_pressed(MouseButton::Left)),
+                        editor::edit_object_event_handler,
+                    )
+                        .run_if(in_state(IsEditing::Editing)),
                     items::item_drag_event_handler,
                     items::item_drop_event_handler,
                     items::spawn_item_event_handler,
                     items::item_click_event_handler,
                     objects::object_click_event_handler,
+                    items::update_transport_links.after(items::spawn_item_event_handler),
+                    items::rotate_placement_direction.run_if(input_just_pressed(KeyCode::KeyR)),
+                    items::harvester::harvester_depleted_event_handler,
                 )
                     .run_if(in_state(IsPaused::Running)),
             )
+            .insert_resource(Time::<Fixed>::from_hz(SIMULATION_RATE))
+            .add_systems(
+                FixedUpdate,
+                (
+                    items::harvester::harvest,
+                    items::crafter::craft,
+                    items::conveyor::transport,
+                )
+                    .chain()
+                    .run_if(in_state(IsPaused::Running).and_then(in_state(IsEditing::Playing))),
+            )
             .add_systems(
                 OnExit(AppState::InGame),
                 (exit, cleanup_state::<OnInGame>, cleanup_state::<Node>),
@@ -157,14 +296,17 @@ impl Plugin for GamePlugin {
 fn load_assets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut asset_tracker: ResMut<AssetTracker>,
+    current_level: Res<CurrentLevel>,
 ) {
-    commands.insert_resource(AudioAssets {
-        // TODO: is there a way to make this streaming?
-        music: asset_server.load("music/Windless Slopes.ogg"),
-    });
+    // TODO: is there a way to make this streaming?
+    let music = asset_server.load("music/Windless Slopes.ogg");
+    asset_tracker.track(music.clone());
+    commands.insert_resource(AudioAssets { music });
 
-    let map = asset_server.load("map.tmx");
+    info!("Loading level {}", current_level.name);
+    let map = asset_server.load(current_level.map.clone());
+    asset_tracker.track(map.clone());
 
     // processing loaded maps requires this, it owns the tile storage
     // there's still a potential race condition until this doesn't
@@ -178,70 +320,55 @@ fn load_assets(
         OnInGame,
     ));
 
-    let resources_image = asset_server.load("resources.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 4, 4, None, None);
-    let resources_atlas = texture_atlas_layouts.add(layout);
-
-    let harvester_image = asset_server.load("harvester.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 4, 4, None, None);
-    let harvester_atlas = texture_atlas_layouts.add(layout);
+    let recipes = asset_server.load("crafting.recipes.ron");
+    asset_tracker.track(recipes.clone());
 
-    let conveyor_image = asset_server.load("missing.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 1, 1, None, None);
-    let conveyor_atlas = texture_atlas_layouts.add(layout);
-
-    let crafter_image = asset_server.load("missing.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 1, 1, None, None);
-    let crafter_atlas = texture_atlas_layouts.add(layout);
+    // sprite sheets are dependencies of the registry
+    let registry = asset_server.load(REGISTRY_PATH);
+    asset_tracker.track(registry.clone());
 
     commands.insert_resource(GameAssets {
         map,
-        resources_image,
-        resources_atlas,
-        harvester_image,
-        harvester_atlas,
-        conveyor_image,
-        conveyor_atlas,
-        crafter_image,
-        crafter_atlas,
+        recipes,
+        registry,
     });
 
     info!("Waiting for assets ...");
 }
 
-#[allow(clippy::too_many_arguments)]
 fn wait_for_assets(
-    mut contexts: EguiContexts,
+    mut commands: Commands,
     mut game_state: ResMut<NextState<AppState>>,
+    asset_server: Res<AssetServer>,
+    asset_tracker: Res<AssetTracker>,
     game_assets: Res<GameAssets>,
-    map_assets: Res<Assets<TiledMap>>,
-    image_assets: Res<Assets<Image>>,
-    atlas_assets: Res<Assets<TextureAtlasLayout>>,
-    game_audio_assets: Res<AudioAssets>,
-    audio_assets: Res<Assets<AudioSource>>,
+    registry_assets: Res<Assets<Registry>>,
 ) {
-    // TODO: other "systems" can load assets that we need to wait for
-    // so this whole setup needs to be reworked
-
-    egui::Window::new("Loading").show(contexts.ctx_mut(), |ui| {
-        ui.vertical(|ui| {
-            ui.label("Loading assets ...");
-        });
-    });
-
-    if !game_assets.is_loaded(&map_assets, &image_assets, &atlas_assets)
-        || !game_audio_assets.is_loaded(&audio_assets)
-    {
+    // the loading screen reports progress and failures
+    if !asset_tracker.is_loaded(&asset_server) {
         return;
     }
 
+    // the map is spawned once this is available
+    let registry = registry_assets.get(&game_assets.registry).unwrap();
+    commands.insert_resource(registry.clone());
+
     info!("Assets loaded, starting game ...");
     game_state.set(AppState::InGame);
 }
 
+fn abandon_load(mut commands: Commands) {
+    info!("Abandoning level load");
+
+    commands.remove_resource::<GameAssets>();
+    commands.remove_resource::<Registry>();
+    commands.remove_resource::<AudioAssets>();
+}
+
 fn enter(
     mut commands: Commands,
     audio_assets: Res<AudioAssets>,
+    current_level: Res<CurrentLevel>,
     mut inventory_update_events: EventWriter<inventory::InventoryUpdatedEvent>,
     mut window_query: Query<&mut Window, With<PrimaryWindow>>,
 ) {
@@ -270,10 +397,11 @@ fn enter(
 
     start_music(&mut commands, audio_assets.music.clone());
 
-    commands.insert_resource(
-        //inventory::Inventory::default()
-        inventory::Inventory(crate::data::inventory::InventoryData::new_test()),
-    );
+    commands.init_resource::<items::TransportNetwork>();
+    commands.init_resource::<items::PlacementDirection>();
+    commands.init_resource::<history::History>();
+
+    commands.insert_resource(inventory::Inventory(current_level.inventory.clone()));
     inventory_update_events.send_default();
 }
 
@@ -281,9 +409,18 @@ fn exit(mut commands: Commands) {
     info!("exiting InGame state");
 
     commands.remove_resource::<GameAssets>();
+    commands.remove_resource::<Registry>();
     commands.remove_resource::<AudioAssets>();
     commands.remove_resource::<ObjectInfo>();
+    commands.remove_resource::<ItemInfo>();
+    commands.remove_resource::<MapValidationReport>();
     commands.remove_resource::<TileDrag>();
+    commands.remove_resource::<selection::Selection>();
+    commands.remove_resource::<selection::SelectionClipboard>();
+    commands.remove_resource::<history::History>();
+    commands.remove_resource::<editor::EditorTool>();
+    commands.remove_resource::<items::TransportNetwork>();
+    commands.remove_resource::<items::PlacementDirection>();
     commands.remove_resource::<inventory::Inventory>();
     commands.remove_resource::<ClearColor>();
 }
@@ -291,3 +428,46 @@ fn exit(mut commands: Commands) {
 fn pause_game(mut pause_state: ResMut<NextState<IsPaused>>) {
     pause_state.set(IsPaused::Paused);
 }
+
+fn map_load_failed_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<TiledMapLoadFailedEvent>,
+    mut log_events: EventWriter<LogEvent>,
+) {
+    for event in events.read() {
+        log_events.send(LogEvent::new(format!(
+            "Map {} failed to load ({} errors)",
+            event.name,
+            event.errors.len()
+        )));
+
+        commands.insert_resource(MapValidationReport {
+            name: event.name.clone(),
+            errors: event.errors.clone(),
+        });
+    }
+}
+
+fn show_map_validation_report(
+    mut contexts: EguiContexts,
+    report: Res<MapValidationReport>,
+    mut game_state: ResMut<NextState<AppState>>,
+) {
+    egui::Window::new("Map Errors").show(contexts.ctx_mut(), |ui| {
+        ui.vertical(|ui| {
+            ui.label(format!("Map {} failed validation:", report.name));
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for error in &report.errors {
+                        ui.label(error.to_string());
+                    }
+                });
+
+            if ui.button("Main Menu").clicked() {
+                game_state.set(AppState::MainMenu);
+            }
+        });
+    });
+}
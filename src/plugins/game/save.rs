@@ -0,0 +1,317 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use super::camera::MainCamera;
+use super::history::History;
+use super::inventory::{Inventory, InventoryUpdatedEvent};
+use super::items::{
+    conveyor::Conveyor, crafter::Crafter, harvester::Harvester, PlacementDirection, SpawnItemEvent,
+    TransportNetwork,
+};
+use super::objects::Object;
+use super::{CurrentLevel, GameAssets, IsPaused, ItemInfo, ObjectInfo};
+use crate::assets::{levels::Levels, tiled::TiledMap};
+use crate::data::save::{SaveData, SavedItem, SavedObject, SAVE_VERSION};
+use crate::plugins::{
+    game_ui::{item_info::ItemInfoWindow, log::LogEvent, object_info::ObjectInfoWindow},
+    level_select::LevelAssets,
+    tiled::{TiledLayerColor, TiledLayerTilesets, TiledMapItemLayer, TiledMapObjectLayer},
+};
+use crate::tilemap::{spawn_object, TileAnimation, TileFootprint};
+use crate::AppState;
+
+// TODO: multiple save slots
+const SAVE_PATH: &str = "saves/savegame.ron";
+
+/// Emit to save the current game
+#[derive(Debug, Default, Event)]
+pub struct SaveGameEvent;
+
+/// Emit to load the saved game
+///
+/// Starts a new game if sent from outside of the game
+#[derive(Debug, Default, Event)]
+pub struct LoadGameEvent;
+
+/// A loaded save waiting to be applied
+///
+/// This is applied once the map has been spawned
+#[derive(Debug, Resource)]
+pub struct PendingLoad(pub SaveData);
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub(super) fn save_game_event_handler(
+    mut events: EventReader<SaveGameEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    current_level: Res<CurrentLevel>,
+    inventory: Res<Inventory>,
+    placement_direction: Res<PlacementDirection>,
+    object_query: Query<(
+        &Object,
+        &TilePos,
+        &TileFootprint,
+        &TilemapId,
+        &TileTextureIndex,
+        &TileFlip,
+        &TileVisible,
+        Option<&TileAnimation>,
+    )>,
+    object_layer_query: Query<&TiledLayerTilesets, With<TiledMapObjectLayer>>,
+    harvester_query: Query<(&Harvester, &TilePos, &TileFootprint)>,
+    conveyor_query: Query<(&Conveyor, &TilePos)>,
+    crafter_query: Query<(&Crafter, &TilePos, &TileFootprint)>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let tilesets = object_layer_query.get_single().ok();
+
+    let objects = object_query
+        .iter()
+        .map(
+            |(object, position, footprint, tilemap_id, texture_index, flip, visible, animation)| {
+                SavedObject {
+                    position: *position,
+                    footprint: *footprint,
+                    tileset: tilesets
+                        .and_then(|tilesets| tilesets.get_tileset(tilemap_id.0))
+                        .map(String::from),
+                    // animated Objects are saved as their base tile, not the current frame
+                    texture_index: animation
+                        .map(|animation| animation.texture_index)
+                        .unwrap_or(texture_index.0),
+                    flip: *flip,
+                    visible: visible.0,
+                    data: object.0.clone(),
+                }
+            },
+        )
+        .collect();
+
+    let mut items = Vec::default();
+    items.extend(
+        harvester_query
+            .iter()
+            .map(|(harvester, position, footprint)| SavedItem::Harvester {
+                position: *position,
+                footprint: *footprint,
+                data: harvester.0.clone(),
+            }),
+    );
+    items.extend(
+        conveyor_query
+            .iter()
+            .map(|(conveyor, position)| SavedItem::Conveyor {
+                position: *position,
+                data: conveyor.0.clone(),
+            }),
+    );
+    items.extend(
+        crafter_query
+            .iter()
+            .map(|(crafter, position, footprint)| SavedItem::Crafter {
+                position: *position,
+                footprint: *footprint,
+                data: crafter.0.clone(),
+            }),
+    );
+
+    let camera = camera_query
+        .get_single()
+        .map(|transform| (transform.translation.x, transform.translation.y))
+        .unwrap_or_default();
+
+    let save = SaveData {
+        version: SAVE_VERSION,
+        map: current_level.map.clone(),
+        inventory: inventory.0.clone(),
+        objects,
+        items,
+        camera,
+        placement_direction: placement_direction.0,
+    };
+
+    match save.write(SAVE_PATH) {
+        Ok(()) => {
+            info!("Saved game to {}", SAVE_PATH);
+            log_events.send(LogEvent::new("Game saved"));
+        }
+        Err(err) => {
+            error!("Failed to save game to {}: {}", SAVE_PATH, err);
+            log_events.send(LogEvent::new(format!("Save failed: {}", err)));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn load_game_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<LoadGameEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    current_level: Option<Res<CurrentLevel>>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Levels>>,
+    app_state: Res<State<AppState>>,
+    mut game_state: ResMut<NextState<AppState>>,
+    mut pause_state: ResMut<NextState<IsPaused>>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let save = match SaveData::read(SAVE_PATH) {
+        Ok(save) => save,
+        Err(err) => {
+            error!("Failed to load game from {}: {}", SAVE_PATH, err);
+            log_events.send(LogEvent::new(format!("Load failed: {}", err)));
+            return;
+        }
+    };
+
+    info!("Loaded game from {}", SAVE_PATH);
+
+    // saves for the map being played are applied in place
+    let same_map = current_level.is_some_and(|current_level| current_level.map == save.map);
+    if *app_state.get() == AppState::InGame && same_map {
+        pause_state.set(IsPaused::Running);
+    } else {
+        commands.insert_resource(CurrentLevel::from_map(
+            save.map.clone(),
+            levels.get(&level_assets.levels),
+        ));
+        game_state.set(AppState::LoadAssets);
+    }
+
+    commands.insert_resource(PendingLoad(save));
+}
+
+/// Replaces the current Objects, Items, and Inventory with a loaded save
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub(super) fn apply_pending_load(
+    mut commands: Commands,
+    pending: Res<PendingLoad>,
+    game_assets: Res<GameAssets>,
+    tiled_maps: Res<Assets<TiledMap>>,
+    mut inventory: ResMut<Inventory>,
+    mut inventory_updated_events: EventWriter<InventoryUpdatedEvent>,
+    mut network: ResMut<TransportNetwork>,
+    mut history: ResMut<History>,
+    mut placement_direction: ResMut<PlacementDirection>,
+    mut spawn_item_events: EventWriter<SpawnItemEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    mut object_layer_query: Query<
+        (
+            Entity,
+            &mut TileStorage,
+            &TiledLayerTilesets,
+            &TiledLayerColor,
+        ),
+        With<TiledMapObjectLayer>,
+    >,
+    mut item_layer_query: Query<
+        &mut TileStorage,
+        (With<TiledMapItemLayer>, Without<TiledMapObjectLayer>),
+    >,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+    mut window_query: Query<&mut Visibility, Or<(With<ObjectInfoWindow>, With<ItemInfoWindow>)>>,
+) {
+    // wait for the map to be spawned
+    let Ok((object_layer_id, mut object_storage, tilesets, layer_color)) =
+        object_layer_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Ok(mut camera_transform) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let save = &pending.0;
+    let tiled_map = tiled_maps.get(&game_assets.map);
+
+    // the info windows would be looking at despawned entities
+    commands.remove_resource::<ObjectInfo>();
+    commands.remove_resource::<ItemInfo>();
+    for mut visibility in &mut window_query {
+        *visibility = Visibility::Hidden;
+    }
+
+    clear_storage(&mut commands, &mut object_storage);
+    for mut item_storage in &mut item_layer_query {
+        clear_storage(&mut commands, &mut item_storage);
+    }
+    *network = TransportNetwork::default();
+
+    // recorded actions are for the world being replaced
+    history.clear();
+
+    commands.entity(object_layer_id).with_children(|parent| {
+        for object in &save.objects {
+            let tilemap = match &object.tileset {
+                Some(tileset) => tilesets.get(tileset).or_else(|| {
+                    warn!("Object layer no longer uses tileset {}", tileset);
+                    tilesets.first()
+                }),
+                None => tilesets.first(),
+            };
+
+            let Some(tilemap) = tilemap else {
+                warn!("Object layer has no tilesets, not spawning saved Object");
+                continue;
+            };
+
+            let animation = tiled_map.and_then(|tiled_map| {
+                let tileset = tilesets.get_tileset(tilemap)?;
+                tiled_map.get_tile_animation(tileset, object.texture_index)
+            });
+
+            spawn_object(
+                parent,
+                &mut object_storage,
+                tilemap,
+                object.position,
+                object.footprint,
+                object.texture_index,
+                object.flip,
+                layer_color.0,
+                object.visible,
+                object.data.clone(),
+                animation,
+            );
+        }
+    });
+
+    for item in &save.items {
+        spawn_item_events.send(item.clone().into());
+    }
+
+    inventory.0 = save.inventory.clone();
+    inventory_updated_events.send_default();
+
+    placement_direction.0 = save.placement_direction;
+
+    camera_transform.translation.x = save.camera.0;
+    camera_transform.translation.y = save.camera.1;
+
+    commands.remove_resource::<PendingLoad>();
+    log_events.send(LogEvent::new("Game loaded"));
+}
+
+/// Despawns everything in a tile storage
+fn clear_storage(commands: &mut Commands, storage: &mut TileStorage) {
+    // multi-tile entities are stored in every cell they cover
+    let entities = storage
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<std::collections::HashSet<_>>();
+    for entity in entities {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    *storage = TileStorage::empty(storage.size);
+}
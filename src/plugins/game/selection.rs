@@ -0,0 +1,523 @@
+//! Marquee selection of tiles, Objects, and Items
+//!
+//! Selections are made by dragging on the map (see input.rs),
+//! selected Items can then be deconstructed or copied and pasted elsewhere
+
+use std::collections::HashSet;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_ecs_tilemap::prelude::*;
+
+use super::camera::MainCamera;
+use super::history::ActionRecorder;
+use super::inventory::{Inventory, InventoryUpdatedEvent};
+use super::items::{
+    can_build_on_tiles, can_place_item, conveyor::Conveyor, crafter::Crafter, harvester::Harvester,
+    SpawnItemEvent, TransportNetwork,
+};
+use super::objects::Object;
+use super::tiles::Tile;
+use super::ItemInfo;
+use crate::assets::registry::Registry;
+use crate::data::{
+    direction::Direction,
+    history::{Action, Change},
+    items::{conveyor::ConveyorData, crafter::CrafterData, harvester::HarvesterData, ItemType},
+    recipes::Recipe,
+    save::SavedItem,
+};
+use crate::get_world_position_from_cursor_position;
+use crate::plugins::{
+    game_ui::{item_info::ItemInfoWindow, log::LogEvent},
+    tiled::{TiledLayerColor, TiledMapItemLayer, TiledMapObjectLayer, TiledMapTileLayer},
+};
+use crate::tilemap::{
+    despawn_item, despawn_object, get_tile_position, TileFootprint, TileMapQueryMut,
+};
+
+const SELECTED_COLOR: Color = Color::srgba(0.25, 0.5, 1.0, 0.75);
+
+/// Selected tile, Object, or Item tag
+///
+/// Selected tiles are highlighted until this is removed
+#[derive(Debug, Component)]
+pub struct Selected;
+
+/// The tiles, Objects, and Items selected by the last TileDrag
+#[derive(Debug, Default, Resource)]
+pub struct Selection {
+    /// Bottom-left corner of the selection
+    pub min: TilePos,
+
+    /// Top-right corner of the selection (inclusive)
+    pub max: TilePos,
+
+    pub tiles: HashSet<Entity>,
+    pub objects: HashSet<Entity>,
+    pub items: HashSet<Entity>,
+}
+
+impl Selection {
+    /// Gets the size of the selection in tiles
+    #[inline]
+    pub fn size(&self) -> (u32, u32) {
+        (self.max.x - self.min.x + 1, self.max.y - self.min.y + 1)
+    }
+
+    /// Removes the highlight from everything in the selection
+    pub fn clear(&self, commands: &mut Commands) {
+        for entity in self.tiles.iter().chain(&self.objects).chain(&self.items) {
+            if let Some(mut entity) = commands.get_entity(*entity) {
+                entity.remove::<Selected>();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SelectionAction {
+    /// Return the selected Items to the Inventory
+    Deconstruct,
+
+    /// Copy the selected Items
+    Copy,
+
+    /// Place the copied Items at the cursor
+    Paste,
+
+    /// Drop the selection
+    Clear,
+}
+
+/// Emit to act on the current Selection
+#[derive(Debug, Event)]
+pub struct SelectionActionEvent(pub SelectionAction);
+
+/// The state of a copied Item that is kept when it's pasted
+#[derive(Debug, Clone)]
+pub enum CopiedItemData {
+    Harvester,
+    Conveyor(Direction),
+    Crafter(Option<Recipe>),
+}
+
+impl CopiedItemData {
+    #[inline]
+    pub fn get_type(&self) -> ItemType {
+        match self {
+            Self::Harvester => ItemType::Harvester,
+            Self::Conveyor(_) => ItemType::Conveyor,
+            Self::Crafter(_) => ItemType::Crafter,
+        }
+    }
+}
+
+/// A copied Item, positioned relative to the bottom-left of the copy
+#[derive(Debug, Clone)]
+pub struct CopiedItem {
+    pub offset: TilePos,
+    pub data: CopiedItemData,
+}
+
+/// The Items copied from a Selection
+#[derive(Debug, Default, Resource, Deref)]
+pub struct SelectionClipboard(pub Vec<CopiedItem>);
+
+/// Gets the color a tile is drawn with when it isn't highlighted
+///
+/// Items are drawn on their layer, tiles and Objects
+/// on a tileset tilemap that is a child of their layer
+fn get_layer_color(
+    tilemap_id: &TilemapId,
+    tilemap_query: &Query<(Option<&TiledLayerColor>, Option<&Parent>)>,
+) -> Color {
+    let Ok((color, parent)) = tilemap_query.get(tilemap_id.0) else {
+        return Color::WHITE;
+    };
+
+    color
+        .or_else(|| {
+            parent
+                .and_then(|parent| tilemap_query.get(parent.get()).ok())
+                .and_then(|(color, _)| color)
+        })
+        .map(|color| color.0)
+        .unwrap_or(Color::WHITE)
+}
+
+pub(super) fn highlight_selected(mut tile_query: Query<&mut TileColor, Added<Selected>>) {
+    for mut color in &mut tile_query {
+        color.0 = SELECTED_COLOR;
+    }
+}
+
+pub(super) fn unhighlight_deselected(
+    mut removed: RemovedComponents<Selected>,
+    mut tile_query: Query<(&TilemapId, &mut TileColor), Without<Selected>>,
+    tilemap_query: Query<(Option<&TiledLayerColor>, Option<&Parent>)>,
+) {
+    for entity in removed.read() {
+        // despawned or selected again
+        let Ok((tilemap_id, mut color)) = tile_query.get_mut(entity) else {
+            continue;
+        };
+
+        color.0 = get_layer_color(tilemap_id, &tilemap_query);
+    }
+}
+
+pub(super) fn selection_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut action_events: EventWriter<SelectionActionEvent>,
+) {
+    let control = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    if keys.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+        action_events.send(SelectionActionEvent(SelectionAction::Deconstruct));
+    } else if control && keys.just_pressed(KeyCode::KeyC) {
+        action_events.send(SelectionActionEvent(SelectionAction::Copy));
+    } else if control && keys.just_pressed(KeyCode::KeyV) {
+        action_events.send(SelectionActionEvent(SelectionAction::Paste));
+    }
+}
+
+pub(super) fn clear_selection_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<SelectionActionEvent>,
+    selection: Option<Res<Selection>>,
+) {
+    if !events.read().any(|event| event.0 == SelectionAction::Clear) {
+        return;
+    }
+
+    if let Some(selection) = selection {
+        selection.clear(&mut commands);
+        commands.remove_resource::<Selection>();
+    }
+}
+
+/// Returns the selected Items to the Inventory
+///
+/// Items that don't fit in the Inventory are left in place.
+/// Anything an Item was holding is lost, as is the Object a Harvester replaced
+#[allow(clippy::too_many_arguments)]
+pub(super) fn deconstruct_selection_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<SelectionActionEvent>,
+    selection: Option<ResMut<Selection>>,
+    mut inventory: ResMut<Inventory>,
+    mut network: ResMut<TransportNetwork>,
+    registry: Res<Registry>,
+    item_info: Option<Res<ItemInfo>>,
+    mut recorder: ActionRecorder,
+    mut inventory_updated_events: EventWriter<InventoryUpdatedEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    item_query: Query<(&TilePos, &TileFootprint, &TilemapId)>,
+    mut item_layer_query: Query<(&TiledMapItemLayer, &mut TileStorage)>,
+    mut item_info_window_query: Query<&mut Visibility, With<ItemInfoWindow>>,
+) {
+    if !events
+        .read()
+        .any(|event| event.0 == SelectionAction::Deconstruct)
+    {
+        return;
+    }
+
+    let Some(mut selection) = selection else {
+        return;
+    };
+
+    let mut action = Action::new("Deconstruct");
+    let mut deconstructed = 0;
+    let mut kept = 0;
+    for item in selection.items.clone() {
+        let Ok((position, footprint, tilemap_id)) = item_query.get(item) else {
+            selection.items.remove(&item);
+            continue;
+        };
+        let Ok((item_layer, mut item_storage)) = item_layer_query.get_mut(tilemap_id.0) else {
+            continue;
+        };
+
+        if !inventory
+            .0
+            .add_item(item_layer.0, &registry, &mut inventory_updated_events)
+        {
+            kept += 1;
+            continue;
+        }
+
+        action
+            .changes
+            .extend(recorder.save_item(item).map(Change::DespawnItem));
+        action.push(Change::Inventory(item_layer.0, 1));
+
+        // the Item Info window can't show a removed Item
+        if item_info
+            .as_ref()
+            .is_some_and(|item_info| item_info.0 == item)
+        {
+            commands.remove_resource::<ItemInfo>();
+            *item_info_window_query.single_mut() = Visibility::Hidden;
+        }
+
+        despawn_item(
+            &mut commands,
+            &mut item_storage,
+            item,
+            *position,
+            *footprint,
+        );
+        network.remove(*position, *footprint);
+        selection.items.remove(&item);
+        deconstructed += 1;
+    }
+    recorder.record(action);
+
+    let mut message = format!("Deconstructed {} Items", deconstructed);
+    if kept > 0 {
+        message.push_str(&format!(", {} didn't fit in the Inventory", kept));
+    }
+    log_events.send(LogEvent::new(message));
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn copy_selection_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<SelectionActionEvent>,
+    selection: Option<Res<Selection>>,
+    mut log_events: EventWriter<LogEvent>,
+    item_query: Query<(
+        &TilePos,
+        Has<Harvester>,
+        Option<&Conveyor>,
+        Option<&Crafter>,
+    )>,
+) {
+    if !events.read().any(|event| event.0 == SelectionAction::Copy) {
+        return;
+    }
+
+    let Some(selection) = selection else {
+        return;
+    };
+
+    let items = selection
+        .items
+        .iter()
+        .filter_map(|item| item_query.get(*item).ok())
+        .filter_map(|(position, harvester, conveyor, crafter)| {
+            let data = if harvester {
+                CopiedItemData::Harvester
+            } else if let Some(conveyor) = conveyor {
+                CopiedItemData::Conveyor(conveyor.direction)
+            } else {
+                CopiedItemData::Crafter(crafter?.get_recipe().cloned())
+            };
+            Some((*position, data))
+        })
+        .collect::<Vec<_>>();
+
+    // Items can start outside of the selection if they're only partly in it
+    let min_x = items.iter().map(|(position, _)| position.x).min();
+    let min_y = items.iter().map(|(position, _)| position.y).min();
+    let (Some(min_x), Some(min_y)) = (min_x, min_y) else {
+        log_events.send(LogEvent::new("Nothing to copy"));
+        return;
+    };
+
+    let clipboard = items
+        .into_iter()
+        .map(|(position, data)| CopiedItem {
+            offset: TilePos {
+                x: position.x - min_x,
+                y: position.y - min_y,
+            },
+            data,
+        })
+        .collect::<Vec<_>>();
+
+    log_events.send(LogEvent::new(format!("Copied {} Items", clipboard.len())));
+    commands.insert_resource(SelectionClipboard(clipboard));
+}
+
+/// Places the copied Items from the bottom-left at the cursor
+///
+/// Items are taken from the Inventory, Items that can't be placed
+/// or that the Inventory doesn't have are skipped
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub(super) fn paste_selection_event_handler(
+    mut commands: Commands,
+    mut events: EventReader<SelectionActionEvent>,
+    clipboard: Option<Res<SelectionClipboard>>,
+    mut inventory: ResMut<Inventory>,
+    network: Res<TransportNetwork>,
+    registry: Res<Registry>,
+    mut recorder: ActionRecorder,
+    mut inventory_updated_events: EventWriter<InventoryUpdatedEvent>,
+    mut spawn_item_events: EventWriter<SpawnItemEvent>,
+    mut log_events: EventWriter<LogEvent>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut tilemap_layer_set: ParamSet<(
+        Query<TileMapQueryMut, With<TiledMapObjectLayer>>,
+        Query<TileMapQueryMut, With<TiledMapTileLayer>>,
+    )>,
+    object_query: Query<(&Object, &TilePos, &TileFootprint)>,
+    tile_data_query: Query<&Tile>,
+) {
+    if !events.read().any(|event| event.0 == SelectionAction::Paste) {
+        return;
+    }
+
+    let Some(clipboard) = clipboard else {
+        log_events.send(LogEvent::new("Nothing to paste"));
+        return;
+    };
+
+    let (camera, camera_transform) = camera_query.single();
+    let window = window_query.single();
+    let Some(origin) =
+        get_world_position_from_cursor_position(window.cursor_position(), camera, camera_transform)
+            .and_then(|world_position| {
+                let tilemap_layer_query = tilemap_layer_set.p1();
+                let tilemap = tilemap_layer_query.single();
+                get_tile_position(
+                    world_position,
+                    tilemap.size,
+                    tilemap.grid_size,
+                    tilemap.r#type,
+                    tilemap.transform,
+                )
+            })
+    else {
+        return;
+    };
+    let map_size = *tilemap_layer_set.p1().single().size;
+
+    let mut action = Action::new("Paste");
+    let mut pasted = 0;
+    for copied in clipboard.iter() {
+        let item_type = copied.data.get_type();
+        let item = registry.get_item(item_type);
+
+        // Items that would land off the map are skipped
+        let Some(position) = origin
+            .x
+            .checked_add(copied.offset.x)
+            .zip(origin.y.checked_add(copied.offset.y))
+            .map(|(x, y)| TilePos { x, y })
+            .filter(|position| position.within_map_bounds(&map_size))
+        else {
+            continue;
+        };
+
+        if inventory
+            .get_items()
+            .get(&item_type)
+            .copied()
+            .unwrap_or_default()
+            == 0
+        {
+            continue;
+        }
+
+        match &copied.data {
+            CopiedItemData::Harvester => {
+                let mut object_layer_query = tilemap_layer_set.p0();
+                let mut object_tilemap = object_layer_query.single_mut();
+
+                // Harvesters have to be pasted onto an Object
+                let Some((object_entity, (object, object_position, object_footprint))) =
+                    object_tilemap.storage.get(&position).and_then(|entity| {
+                        object_query.get(entity).ok().map(|object| (entity, object))
+                    })
+                else {
+                    continue;
+                };
+                if *object_position != position || !item.can_drop_on_object(object.get_type()) {
+                    continue;
+                }
+
+                action.push(Change::Inventory(item_type, -1));
+                let saved_object = recorder.save_object(object_entity);
+                if item_type.on_drop_object(
+                    &mut inventory.0,
+                    &mut inventory_updated_events,
+                    object,
+                    *object_position,
+                    *object_footprint,
+                    &mut spawn_item_events,
+                ) {
+                    action
+                        .changes
+                        .extend(saved_object.map(Change::DespawnObject));
+                    despawn_object(
+                        &mut commands,
+                        &mut object_tilemap.storage,
+                        object_entity,
+                        *object_position,
+                        *object_footprint,
+                    );
+                }
+                action.push(Change::SpawnItem(SavedItem::Harvester {
+                    position: *object_position,
+                    footprint: *object_footprint,
+                    data: HarvesterData::from(&object.0),
+                }));
+            }
+            CopiedItemData::Conveyor(_) | CopiedItemData::Crafter(_) => {
+                // the footprint has to fit on the map before any of its tiles are looked at
+                let object_layer_query = tilemap_layer_set.p0();
+                if !can_place_item(
+                    item,
+                    position,
+                    &map_size,
+                    object_layer_query.single().storage,
+                    &network,
+                ) || !can_build_on_tiles(
+                    item,
+                    position,
+                    tilemap_layer_set.p1().single().storage,
+                    &tile_data_query,
+                ) {
+                    continue;
+                }
+
+                let saved_item = match &copied.data {
+                    CopiedItemData::Conveyor(direction) => SavedItem::Conveyor {
+                        position,
+                        data: ConveyorData::new(*direction),
+                    },
+                    CopiedItemData::Crafter(recipe) => {
+                        let mut data = CrafterData::default();
+                        data.set_recipe(recipe.clone());
+                        SavedItem::Crafter {
+                            position,
+                            footprint: item.footprint,
+                            data,
+                        }
+                    }
+                    CopiedItemData::Harvester => unreachable!(),
+                };
+                spawn_item_events.send(saved_item.clone().into());
+                inventory
+                    .0
+                    .remove_item(item_type, &mut inventory_updated_events);
+
+                action.push(Change::Inventory(item_type, -1));
+                action.push(Change::SpawnItem(saved_item));
+            }
+        }
+
+        pasted += 1;
+    }
+    recorder.record(action);
+
+    log_events.send(LogEvent::new(format!(
+        "Pasted {} of {} Items",
+        pasted,
+        clipboard.len()
+    )));
+}
@@ -0,0 +1,7 @@
+use bevy::prelude::*;
+
+use crate::data::tiles::TileData;
+
+/// Game Tile data component
+#[derive(Debug, Component, Deref)]
+pub struct Tile(pub TileData);
@@ -0,0 +1,285 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_mod_picking::prelude::*;
+use bevy_simple_scroll_view::{ScrollView, ScrollableContent};
+
+use crate::assets::tiled::{get_tileset_texture_grid, TiledMap};
+use crate::plugins::{
+    game::{editor::EditorTool, export::ExportMapEvent, GameAssets, IsEditing},
+    tiled::{TiledLayerChunks, TiledLayerTilesets, TiledMapObjectLayer, TiledMapTileLayer},
+    ui::UiAssets,
+};
+use crate::tiled::{TILE_HEIGHT, TILE_WIDTH};
+use crate::ui::*;
+
+/// Map editor window tag
+#[derive(Debug, Component)]
+pub struct EditorWindow;
+
+/// Map editor window palette tag
+///
+/// Holds a tile button per-tile of every tileset the map layers use
+#[derive(Debug, Component)]
+pub struct EditorWindowPalette;
+
+/// Map editor window current tool label tag
+#[derive(Debug, Component)]
+pub struct EditorWindowToolUI;
+
+/// Map editor window tool button
+///
+/// Selects the tool when clicked
+#[derive(Debug, Component)]
+pub struct EditorWindowToolButton(pub EditorTool);
+
+fn select_tool(
+    mut commands: Commands,
+    event: Listener<Pointer<Click>>,
+    tool_query: Query<&EditorWindowToolButton>,
+) {
+    if !check_click_event(
+        event.listener(),
+        event.target,
+        event.button,
+        PointerButton::Primary,
+    ) {
+        return;
+    }
+
+    let tool = tool_query.get(event.target).unwrap();
+    commands.insert_resource(tool.0.clone());
+}
+
+pub(super) fn setup_window(
+    mut commands: Commands,
+    ui_assets: Res<UiAssets>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = window_query.single();
+
+    let content_id = create_window(
+        &mut commands,
+        &ui_assets,
+        window,
+        (400, 300),
+        "Map Editor",
+        false,
+        EditorWindow,
+    );
+    commands.entity(content_id).with_children(|parent| {
+        create_row_container(parent).with_children(|parent| {
+            for (content, tool) in [("Select", EditorTool::Select), ("Erase", EditorTool::Erase)] {
+                create_small_button(
+                    parent,
+                    &ui_assets,
+                    content,
+                    On::<Pointer<Click>>::run(select_tool),
+                )
+                .insert(EditorWindowToolButton(tool));
+            }
+
+            create_small_button(
+                parent,
+                &ui_assets,
+                "Save Map",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut export_events: EventWriter<ExportMapEvent>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        export_events.send_default();
+                    },
+                ),
+            );
+
+            create_small_button(
+                parent,
+                &ui_assets,
+                "Exit Editor",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut editing_state: ResMut<NextState<IsEditing>>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        editing_state.set(IsEditing::Playing);
+                    },
+                ),
+            );
+        });
+
+        create_row_container(parent).with_children(|parent| {
+            create_label(parent, &ui_assets, "Tool:", 14.0, FONT_COLOR);
+            create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR).insert(EditorWindowToolUI);
+        });
+
+        parent
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(90.0),
+                        height: Val::Px(200.0),
+                        margin: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Name::new("Scroll View"),
+                ScrollView::default(),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: bevy::ui::FlexDirection::Column,
+                            width: Val::Percent(100.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Name::new("Scroll Content"),
+                    ScrollableContent::default(),
+                    EditorWindowPalette,
+                ));
+            });
+    });
+}
+
+/// Shows the editor window with the tiles of the tilesets used by the Tile and Object layers
+///
+/// The palette is rebuilt every time since the map can be reloaded or changed
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub(super) fn show_window(
+    mut commands: Commands,
+    ui_assets: Res<UiAssets>,
+    game_assets: Res<GameAssets>,
+    tiled_maps: Res<Assets<TiledMap>>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tile_layer_query: Query<
+        &TiledLayerTilesets,
+        (With<TiledMapTileLayer>, Without<TiledLayerChunks>),
+    >,
+    object_layer_query: Query<&TiledLayerTilesets, With<TiledMapObjectLayer>>,
+    palette_query: Query<Entity, With<EditorWindowPalette>>,
+    mut window_query: Query<&mut Visibility, With<EditorWindow>>,
+) {
+    *window_query.single_mut() = Visibility::Visible;
+
+    let palette = palette_query.single();
+    commands.entity(palette).despawn_descendants();
+
+    let Some(tiled_map) = tiled_maps.get(&game_assets.map) else {
+        return;
+    };
+
+    // infinite tile layers are streamed and can't be painted
+    let sections: [(_, _, fn(String, u32) -> EditorTool); 2] = [
+        (
+            "Tiles",
+            tile_layer_query.get_single().ok(),
+            |tileset, tile_id| EditorTool::PaintTile { tileset, tile_id },
+        ),
+        (
+            "Objects",
+            object_layer_query.get_single().ok(),
+            |tileset, tile_id| EditorTool::PlaceObject { tileset, tile_id },
+        ),
+    ];
+
+    commands.entity(palette).with_children(|parent| {
+        for (section, tilesets, tool) in sections {
+            let Some(tilesets) = tilesets else {
+                continue;
+            };
+
+            create_label(parent, &ui_assets, section, 24.0, FONT_COLOR);
+
+            for (tileset_name, _) in &tilesets.0 {
+                let (Some(tileset), Some(TilemapTexture::Single(image))) = (
+                    tiled_map.get_tileset(tileset_name),
+                    tiled_map.tilemap_textures.get(tileset_name),
+                ) else {
+                    continue;
+                };
+
+                let grid = get_tileset_texture_grid(tileset);
+                let atlas = atlas_layouts.add(TextureAtlasLayout::from_grid(
+                    UVec2::new(TILE_WIDTH, TILE_HEIGHT),
+                    grid.x,
+                    grid.y,
+                    None,
+                    Some(UVec2::splat(tileset.margin)),
+                ));
+
+                let tile_ids = match tileset.image {
+                    Some(_) => (0..tileset.tilecount).collect::<Vec<_>>(),
+                    None => {
+                        let mut tile_ids = tileset.tiles().map(|(id, _)| id).collect::<Vec<_>>();
+                        tile_ids.sort();
+                        tile_ids
+                    }
+                };
+
+                create_label(parent, &ui_assets, tileset_name, 14.0, FONT_COLOR);
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                flex_wrap: FlexWrap::Wrap,
+                                width: Val::Percent(100.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        Name::new(tileset_name.clone()),
+                        Pickable::IGNORE,
+                    ))
+                    .with_children(|parent| {
+                        for tile_id in tile_ids {
+                            create_image_button_from_slice(
+                                parent,
+                                image.clone(),
+                                atlas.clone(),
+                                tile_id as usize,
+                                On::<Pointer<Click>>::run(select_tool),
+                            )
+                            .insert(EditorWindowToolButton(tool(tileset_name.clone(), tile_id)));
+                        }
+                    });
+            }
+        }
+    });
+}
+
+pub(super) fn hide_window(mut window_query: Query<&mut Visibility, With<EditorWindow>>) {
+    *window_query.single_mut() = Visibility::Hidden;
+}
+
+pub(super) fn update_tool_ui(
+    tool: Res<EditorTool>,
+    mut text_query: Query<&mut Text, With<EditorWindowToolUI>>,
+) {
+    let content = match &*tool {
+        EditorTool::Select => "Select (right click Objects to edit them)".to_owned(),
+        EditorTool::PaintTile { tileset, tile_id } => format!("Paint {} {}", tileset, tile_id),
+        EditorTool::PlaceObject { tileset, tile_id } => {
+            format!("Place Resources {} {}", tileset, tile_id)
+        }
+        EditorTool::Erase => "Erase Objects".to_owned(),
+    };
+
+    text_query.single_mut().sections.get_mut(0).unwrap().value = content;
+}
@@ -2,12 +2,12 @@ use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_mod_picking::prelude::*;
 use bevy_simple_scroll_view::{ScrollView, ScrollableContent};
 
+use crate::assets::registry::Registry;
 use crate::data::{items::ItemType, resources::ResourceType};
 use crate::plugins::{
     game::{
         inventory::{Inventory, InventoryUpdatedEvent},
         items::{ItemDragEvent, ItemDropEvent},
-        GameAssets,
     },
     ui::UiAssets,
 };
@@ -63,7 +63,7 @@ pub struct InventoryDragImage {
 fn start_drag_inventory_item(
     mut commands: Commands,
     event: Listener<Pointer<DragStart>>,
-    game_assets: Res<GameAssets>,
+    registry: Res<Registry>,
     item_image_query: Query<(&GlobalTransform, &InventoryItemImage)>,
     mut drag_image_query: Query<(
         Entity,
@@ -94,11 +94,12 @@ fn start_drag_inventory_item(
     *drag_image_visibility = Visibility::Visible;
 
     // update the drag image item
+    let sprite = &registry.get_item(item_image.0).sprite;
     drag_image.item_type = Some(item_image.0);
-    drag_image_image.texture = game_assets.get_item_texture(item_image.0);
+    drag_image_image.texture = sprite.image_handle.clone();
     commands.entity(drag_image_id).insert(TextureAtlas {
-        layout: game_assets.get_item_atlas(item_image.0),
-        index: 0,
+        layout: sprite.atlas_handle.clone(),
+        index: sprite.index as usize,
     });
 
     let half_width = if let Val::Px(width) = drag_image_style.width {
@@ -203,7 +204,7 @@ pub(super) fn hide_item_drag_image_event_handler(
 pub(super) fn setup_window(
     mut commands: Commands,
     ui_assets: Res<UiAssets>,
-    game_assets: Res<GameAssets>,
+    registry: Res<Registry>,
     window_query: Query<&Window, With<PrimaryWindow>>,
 ) {
     let window = window_query.single();
@@ -253,26 +254,33 @@ pub(super) fn setup_window(
                             .with_children(|parent| {
                                 create_label(parent, &ui_assets, "Resources", 24.0, FONT_COLOR);
 
-                                create_row_container(parent)
-                                    .insert((
-                                        Visibility::Hidden,
-                                        Name::new("Iron"),
-                                        InventoryResourcesUI(ResourceType::Iron),
-                                    ))
-                                    .with_children(|parent| {
-                                        create_image_from_slice(
-                                            parent,
-                                            game_assets.resources_image.clone(),
-                                            game_assets.resources_atlas.clone(),
-                                            0,
-                                        );
-                                        create_label(parent, &ui_assets, "Iron:", 14.0, FONT_COLOR);
-                                        create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
-                                            .insert(InventoryResourcesAmountUI(
-                                                ResourceType::Iron,
-                                                0,
-                                            ));
-                                    });
+                                for resource in &registry.resources {
+                                    create_row_container(parent)
+                                        .insert((
+                                            Visibility::Hidden,
+                                            Name::new(resource.name.clone()),
+                                            InventoryResourcesUI(resource.id),
+                                        ))
+                                        .with_children(|parent| {
+                                            create_image_from_slice(
+                                                parent,
+                                                resource.sprite.image_handle.clone(),
+                                                resource.sprite.atlas_handle.clone(),
+                                                resource.sprite.index as usize,
+                                            );
+                                            create_label(
+                                                parent,
+                                                &ui_assets,
+                                                format!("{}:", resource.name),
+                                                14.0,
+                                                FONT_COLOR,
+                                            );
+                                            create_label(
+                                                parent, &ui_assets, "N/A", 14.0, FONT_COLOR,
+                                            )
+                                            .insert(InventoryResourcesAmountUI(resource.id, 0));
+                                        });
+                                }
                             });
 
                         // Items
@@ -281,122 +289,45 @@ pub(super) fn setup_window(
                             .with_children(|parent| {
                                 create_label(parent, &ui_assets, "Items", 24.0, FONT_COLOR);
 
-                                create_row_container(parent)
-                                    .insert((
-                                        Visibility::Hidden,
-                                        Name::new("Harvesters"),
-                                        InventoryItemUI(ItemType::Harvester),
-                                    ))
-                                    .with_children(|parent| {
-                                        let item_image_id = create_draggable_image_from_slice(
-                                            parent,
-                                            game_assets.harvester_image.clone(),
-                                            game_assets.harvester_atlas.clone(),
-                                            0,
-                                            On::<Pointer<DragStart>>::run(
-                                                start_drag_inventory_item,
-                                            ),
-                                            On::<Pointer<Drag>>::run(drag_inventory_item),
-                                            On::<Pointer<DragEnd>>::run(end_drag_inventory_item),
-                                        )
-                                        .insert((
-                                            InventoryItemImage(ItemType::Harvester),
-                                            Pickable::IGNORE,
-                                        ))
-                                        .id();
-
-                                        create_label(
-                                            parent,
-                                            &ui_assets,
-                                            "Harvesters:",
-                                            14.0,
-                                            FONT_COLOR,
-                                        );
-                                        create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
-                                            .insert(InventoryItemAmountUI(
-                                                ItemType::Harvester,
-                                                0,
-                                                item_image_id,
-                                            ));
-                                    });
-
-                                create_row_container(parent)
-                                    .insert((
-                                        Visibility::Hidden,
-                                        Name::new("Conveyors"),
-                                        InventoryItemUI(ItemType::Conveyor),
-                                    ))
-                                    .with_children(|parent| {
-                                        let item_image_id = create_draggable_image_from_slice(
-                                            parent,
-                                            game_assets.conveyor_image.clone(),
-                                            game_assets.conveyor_atlas.clone(),
-                                            0,
-                                            On::<Pointer<DragStart>>::run(
-                                                start_drag_inventory_item,
-                                            ),
-                                            On::<Pointer<Drag>>::run(drag_inventory_item),
-                                            On::<Pointer<DragEnd>>::run(end_drag_inventory_item),
-                                        )
-                                        .insert((
-                                            InventoryItemImage(ItemType::Conveyor),
-                                            Pickable::IGNORE,
-                                        ))
-                                        .id();
-
-                                        create_label(
-                                            parent,
-                                            &ui_assets,
-                                            "Conveyors:",
-                                            14.0,
-                                            FONT_COLOR,
-                                        );
-                                        create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
-                                            .insert(InventoryItemAmountUI(
-                                                ItemType::Conveyor,
-                                                0,
-                                                item_image_id,
-                                            ));
-                                    });
-
-                                create_row_container(parent)
-                                    .insert((
-                                        Visibility::Hidden,
-                                        Name::new("Crafters"),
-                                        InventoryItemUI(ItemType::Crafter),
-                                    ))
-                                    .with_children(|parent| {
-                                        let item_image_id = create_draggable_image_from_slice(
-                                            parent,
-                                            game_assets.crafter_image.clone(),
-                                            game_assets.crafter_atlas.clone(),
-                                            0,
-                                            On::<Pointer<DragStart>>::run(
-                                                start_drag_inventory_item,
-                                            ),
-                                            On::<Pointer<Drag>>::run(drag_inventory_item),
-                                            On::<Pointer<DragEnd>>::run(end_drag_inventory_item),
-                                        )
+                                for item in &registry.items {
+                                    create_row_container(parent)
                                         .insert((
-                                            InventoryItemImage(ItemType::Crafter),
-                                            Pickable::IGNORE,
+                                            Visibility::Hidden,
+                                            Name::new(item.name.clone()),
+                                            InventoryItemUI(item.id),
                                         ))
-                                        .id();
-
-                                        create_label(
-                                            parent,
-                                            &ui_assets,
-                                            "Crafters:",
-                                            14.0,
-                                            FONT_COLOR,
-                                        );
-                                        create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
-                                            .insert(InventoryItemAmountUI(
-                                                ItemType::Crafter,
-                                                0,
-                                                item_image_id,
-                                            ));
-                                    });
+                                        .with_children(|parent| {
+                                            let item_image_id = create_draggable_image_from_slice(
+                                                parent,
+                                                item.sprite.image_handle.clone(),
+                                                item.sprite.atlas_handle.clone(),
+                                                item.sprite.index as usize,
+                                                On::<Pointer<DragStart>>::run(
+                                                    start_drag_inventory_item,
+                                                ),
+                                                On::<Pointer<Drag>>::run(drag_inventory_item),
+                                                On::<Pointer<DragEnd>>::run(
+                                                    end_drag_inventory_item,
+                                                ),
+                                            )
+                                            .insert((InventoryItemImage(item.id), Pickable::IGNORE))
+                                            .id();
+
+                                            create_label(
+                                                parent,
+                                                &ui_assets,
+                                                format!("{}:", item.name),
+                                                14.0,
+                                                FONT_COLOR,
+                                            );
+                                            create_label(
+                                                parent, &ui_assets, "N/A", 14.0, FONT_COLOR,
+                                            )
+                                            .insert(
+                                                InventoryItemAmountUI(item.id, 0, item_image_id),
+                                            );
+                                        });
+                                }
                             });
                     });
             });
@@ -1,12 +1,78 @@
 use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_mod_picking::prelude::*;
 
-use crate::plugins::{game::ItemInfo, ui::UiAssets};
+use crate::assets::recipes::Recipes;
+use crate::data::items::ItemType;
+use crate::plugins::{
+    game::{
+        items::{conveyor::Conveyor, crafter::Crafter, harvester::Harvester},
+        GameAssets, ItemInfo,
+    },
+    ui::UiAssets,
+};
 use crate::ui::*;
 
 /// Game Item info window tag
 #[derive(Debug, Component)]
 pub struct ItemInfoWindow;
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ItemInfoData {
+    ItemType,
+}
+
+/// Game Item info window data tag
+#[derive(Debug, Component)]
+pub struct ItemInfoWindowDataUI(pub ItemInfoData);
+
+/// Game Item info window crafter tag
+#[derive(Debug, Component)]
+pub struct ItemInfoWindowCrafter;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ItemInfoCrafterData {
+    Recipe,
+    Status,
+}
+
+/// Game Item info window crafter data tag
+#[derive(Debug, Component)]
+pub struct ItemInfoWindowCrafterDataUI(pub ItemInfoCrafterData);
+
+fn next_recipe(
+    event: Listener<Pointer<Click>>,
+    item: Option<Res<ItemInfo>>,
+    game_assets: Res<GameAssets>,
+    recipes: Res<Assets<Recipes>>,
+    mut crafter_query: Query<&mut Crafter>,
+) {
+    if !check_click_event(
+        event.listener(),
+        event.target,
+        event.button,
+        PointerButton::Primary,
+    ) {
+        return;
+    }
+
+    let Some(item) = item else {
+        return;
+    };
+
+    let Ok(mut crafter) = crafter_query.get_mut(item.0) else {
+        return;
+    };
+
+    let Some(recipes) = recipes.get(&game_assets.recipes) else {
+        return;
+    };
+
+    let recipe = recipes
+        .next(crafter.get_recipe().map(|recipe| &recipe.name))
+        .cloned();
+    crafter.set_recipe(recipe);
+}
+
 pub(super) fn setup_window(
     mut commands: Commands,
     ui_assets: Res<UiAssets>,
@@ -23,8 +89,40 @@ pub(super) fn setup_window(
         false,
         ItemInfoWindow,
     );
-    commands.entity(content_id).with_children(|_parent| {
-        // TODO:
+    commands.entity(content_id).with_children(|parent| {
+        create_row_container(parent).with_children(|parent| {
+            create_label(parent, &ui_assets, "Item Type:", 14.0, FONT_COLOR);
+            create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
+                .insert(ItemInfoWindowDataUI(ItemInfoData::ItemType));
+        });
+
+        // Crafter
+        create_column_container(parent)
+            .insert((
+                Visibility::Hidden,
+                Name::new("Crafter"),
+                ItemInfoWindowCrafter,
+            ))
+            .with_children(|parent| {
+                create_row_container(parent).with_children(|parent| {
+                    create_label(parent, &ui_assets, "Recipe:", 14.0, FONT_COLOR);
+                    create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
+                        .insert(ItemInfoWindowCrafterDataUI(ItemInfoCrafterData::Recipe));
+                });
+
+                create_row_container(parent).with_children(|parent| {
+                    create_label(parent, &ui_assets, "Status:", 14.0, FONT_COLOR);
+                    create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
+                        .insert(ItemInfoWindowCrafterDataUI(ItemInfoCrafterData::Status));
+                });
+
+                create_button(
+                    parent,
+                    &ui_assets,
+                    "Next Recipe",
+                    On::<Pointer<Click>>::run(next_recipe),
+                );
+            });
     });
 }
 
@@ -45,8 +143,61 @@ pub(super) fn should_update_item_info_ui(
 // (or update it once entirely on show, and then only update dynamic stuff while open)
 #[allow(clippy::type_complexity)]
 pub(super) fn update_item_info_ui(
-    _item: Res<ItemInfo>,
-    //item_query: Query<&Item>,
+    item: Res<ItemInfo>,
+    harvester_query: Query<(), With<Harvester>>,
+    conveyor_query: Query<(), With<Conveyor>>,
+    crafter_query: Query<&Crafter>,
+    mut text_set: ParamSet<(
+        Query<(&mut Text, &ItemInfoWindowDataUI)>,
+        Query<(&mut Text, &ItemInfoWindowCrafterDataUI)>,
+    )>,
+    mut crafter_section_query: Query<&mut Visibility, With<ItemInfoWindowCrafter>>,
 ) {
-    // TODO:
+    let item_type = if harvester_query.contains(item.0) {
+        ItemType::Harvester
+    } else if conveyor_query.contains(item.0) {
+        ItemType::Conveyor
+    } else if crafter_query.contains(item.0) {
+        ItemType::Crafter
+    } else {
+        panic!("Item tile missing Item!");
+    };
+
+    for (mut text, data) in text_set.p0().iter_mut() {
+        match data.0 {
+            ItemInfoData::ItemType => {
+                // TODO: only update if changed
+                text.sections.get_mut(0).unwrap().value = item_type.to_string();
+            }
+        }
+    }
+
+    let mut crafter_visibility = crafter_section_query.single_mut();
+    let Ok(crafter) = crafter_query.get(item.0) else {
+        *crafter_visibility = Visibility::Hidden;
+        return;
+    };
+
+    for (mut text, data) in text_set.p1().iter_mut() {
+        match data.0 {
+            ItemInfoCrafterData::Recipe => {
+                // TODO: only update if changed
+                text.sections.get_mut(0).unwrap().value = crafter
+                    .get_recipe()
+                    .map(|recipe| recipe.name.clone())
+                    .unwrap_or_else(|| "None".to_string());
+            }
+            ItemInfoCrafterData::Status => {
+                // TODO: only update if changed
+                text.sections.get_mut(0).unwrap().value = if crafter.is_crafting() {
+                    "Crafting"
+                } else {
+                    "Idle"
+                }
+                .to_string();
+            }
+        }
+    }
+
+    *crafter_visibility = Visibility::Inherited;
 }
@@ -1,11 +1,16 @@
+pub mod editor;
 pub mod inventory;
 pub mod item_info;
 pub mod log;
 pub mod object_info;
+pub mod selection_info;
 
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
-use crate::plugins::{game::IsPaused, ui::IsPointerCaptured};
+use crate::plugins::{
+    game::{editor::EditorTool, selection::Selection, IsEditing, IsPaused},
+    ui::IsPointerCaptured,
+};
 use crate::AppState;
 
 #[derive(Debug, Default)]
@@ -23,8 +28,18 @@ impl Plugin for GameUiPlugin {
                     item_info::setup_window,
                     object_info::setup_window,
                     inventory::setup_window,
+                    editor::setup_window,
+                    selection_info::setup_window,
                 ),
             )
+            .add_systems(
+                OnEnter(IsEditing::Editing),
+                (editor::show_window, object_info::show_edit_buttons),
+            )
+            .add_systems(
+                OnExit(IsEditing::Editing),
+                (editor::hide_window, object_info::hide_edit_buttons),
+            )
             .add_systems(
                 Update,
                 (
@@ -35,6 +50,10 @@ impl Plugin for GameUiPlugin {
                     inventory::show_inventory.run_if(input_just_pressed(KeyCode::KeyI)),
                     inventory::inventory_updated_event_handler,
                     inventory::hide_item_drag_image_event_handler,
+                    editor::update_tool_ui.run_if(resource_exists_and_changed::<EditorTool>),
+                    selection_info::update_selection_info_ui
+                        .run_if(resource_exists_and_changed::<Selection>),
+                    selection_info::hide_window.run_if(resource_removed::<Selection>()),
                 )
                     .run_if(in_state(IsPaused::Running)),
             )
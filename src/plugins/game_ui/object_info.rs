@@ -1,8 +1,13 @@
 use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_mod_picking::prelude::*;
 
 use crate::data::objects::ObjectData;
 use crate::plugins::{
-    game::{objects::Object, ObjectInfo},
+    game::{
+        editor::{EditObjectEvent, ObjectEdit},
+        objects::Object,
+        ObjectInfo,
+    },
     ui::UiAssets,
 };
 use crate::ui::*;
@@ -15,6 +20,7 @@ pub struct ObjectInfoWindow;
 pub enum ObjectInfoData {
     ObjectId,
     ObjectType,
+    Direction,
 }
 
 /// Game Object info window data tag
@@ -35,6 +41,46 @@ pub enum ObjectInfoResourcesData {
 #[derive(Debug, Component)]
 pub struct ObjectInfoWindowResourcesDataUI(pub ObjectInfoResourcesData);
 
+/// Game Object info window edit button tag
+///
+/// Only shown in the map editor
+#[derive(Debug, Component)]
+pub struct ObjectInfoWindowEditUI(pub ObjectEdit);
+
+fn edit_object(
+    event: Listener<Pointer<Click>>,
+    mut edit_events: EventWriter<EditObjectEvent>,
+    edit_query: Query<&ObjectInfoWindowEditUI>,
+) {
+    if !check_click_event(
+        event.listener(),
+        event.target,
+        event.button,
+        PointerButton::Primary,
+    ) {
+        return;
+    }
+
+    let edit = edit_query.get(event.target).unwrap();
+    edit_events.send(EditObjectEvent(edit.0));
+}
+
+fn create_edit_button(parent: &mut ChildBuilder, ui_assets: &Res<UiAssets>, edit: ObjectEdit) {
+    let content = match edit {
+        ObjectEdit::PreviousResourceType => "<".to_owned(),
+        ObjectEdit::NextResourceType => ">".to_owned(),
+        ObjectEdit::Amount(amount) => format!("{:+}", amount),
+    };
+
+    create_small_button(
+        parent,
+        ui_assets,
+        content,
+        On::<Pointer<Click>>::run(edit_object),
+    )
+    .insert((Visibility::Hidden, ObjectInfoWindowEditUI(edit)));
+}
+
 pub(super) fn setup_window(
     mut commands: Commands,
     ui_assets: Res<UiAssets>,
@@ -64,6 +110,12 @@ pub(super) fn setup_window(
                 .insert(ObjectInfoWindowDataUI(ObjectInfoData::ObjectType));
         });
 
+        create_row_container(parent).with_children(|parent| {
+            create_label(parent, &ui_assets, "Facing:", 14.0, FONT_COLOR);
+            create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
+                .insert(ObjectInfoWindowDataUI(ObjectInfoData::Direction));
+        });
+
         // Resources
         create_column_container(parent)
             .insert((
@@ -74,21 +126,45 @@ pub(super) fn setup_window(
             .with_children(|parent| {
                 create_row_container(parent).with_children(|parent| {
                     create_label(parent, &ui_assets, "Resource Type:", 14.0, FONT_COLOR);
+                    create_edit_button(parent, &ui_assets, ObjectEdit::PreviousResourceType);
                     create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR).insert(
                         ObjectInfoWindowResourcesDataUI(ObjectInfoResourcesData::ResourceType),
                     );
+                    create_edit_button(parent, &ui_assets, ObjectEdit::NextResourceType);
                 });
 
                 create_row_container(parent).with_children(|parent| {
                     create_label(parent, &ui_assets, "Amount:", 14.0, FONT_COLOR);
+                    for amount in [-100, -10] {
+                        create_edit_button(parent, &ui_assets, ObjectEdit::Amount(amount));
+                    }
                     create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR).insert(
                         ObjectInfoWindowResourcesDataUI(ObjectInfoResourcesData::Amount(0)),
                     );
+                    for amount in [10, 100] {
+                        create_edit_button(parent, &ui_assets, ObjectEdit::Amount(amount));
+                    }
                 });
             });
     });
 }
 
+pub(super) fn show_edit_buttons(
+    mut edit_query: Query<&mut Visibility, With<ObjectInfoWindowEditUI>>,
+) {
+    for mut visibility in edit_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+pub(super) fn hide_edit_buttons(
+    mut edit_query: Query<&mut Visibility, With<ObjectInfoWindowEditUI>>,
+) {
+    for mut visibility in edit_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
 pub(super) fn should_update_object_info_ui(
     object: Option<Res<ObjectInfo>>,
     window_query: Query<&ViewVisibility, With<ObjectInfoWindow>>,
@@ -106,17 +182,25 @@ pub(super) fn should_update_object_info_ui(
 // (or update it once entirely on show, and then only update dynamic stuff while open)
 #[allow(clippy::type_complexity)]
 pub(super) fn update_object_info_ui(
+    mut commands: Commands,
     object: Res<ObjectInfo>,
     object_query: Query<&Object>,
+    mut window_query: Query<
+        &mut Visibility,
+        (With<ObjectInfoWindow>, Without<ObjectInfoWindowResources>),
+    >,
     mut text_set: ParamSet<(
         Query<(&mut Text, &ObjectInfoWindowDataUI)>,
         Query<(&mut Text, &mut ObjectInfoWindowResourcesDataUI)>,
     )>,
     mut resources_section_query: Query<&mut Visibility, With<ObjectInfoWindowResources>>,
 ) {
-    let object = object_query
-        .get(object.0)
-        .expect("Object tile missing Object!");
+    // the Object can be removed out from under us (map reload)
+    let Ok(object) = object_query.get(object.0) else {
+        commands.remove_resource::<ObjectInfo>();
+        *window_query.single_mut() = Visibility::Hidden;
+        return;
+    };
 
     for (mut text, data) in text_set.p0().iter_mut() {
         match data.0 {
@@ -128,6 +212,10 @@ pub(super) fn update_object_info_ui(
                 // TODO: only update if changed
                 text.sections.get_mut(0).unwrap().value = object.get_type().to_string();
             }
+            ObjectInfoData::Direction => {
+                // TODO: only update if changed
+                text.sections.get_mut(0).unwrap().value = object.get_direction().to_string();
+            }
         }
     }
 
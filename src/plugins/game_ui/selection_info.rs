@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::data::objects::ObjectData;
+use crate::plugins::{
+    game::{
+        objects::Object,
+        selection::{Selection, SelectionAction, SelectionActionEvent},
+        tiles::Tile,
+    },
+    tiled::TiledMapItemLayer,
+    ui::UiAssets,
+};
+use crate::ui::*;
+
+/// Selection info window tag
+#[derive(Debug, Component)]
+pub struct SelectionInfoWindow;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SelectionInfoData {
+    Area,
+    Tiles,
+    Objects,
+    Resources,
+    Items,
+}
+
+/// Selection info window data tag
+#[derive(Debug, Component)]
+pub struct SelectionInfoWindowDataUI(pub SelectionInfoData);
+
+/// Selection info window action button
+#[derive(Debug, Component)]
+pub struct SelectionInfoWindowActionButton(pub SelectionAction);
+
+fn select_action(
+    event: Listener<Pointer<Click>>,
+    mut action_events: EventWriter<SelectionActionEvent>,
+    action_query: Query<&SelectionInfoWindowActionButton>,
+) {
+    if !check_click_event(
+        event.listener(),
+        event.target,
+        event.button,
+        PointerButton::Primary,
+    ) {
+        return;
+    }
+
+    let action = action_query.get(event.target).unwrap();
+    action_events.send(SelectionActionEvent(action.0));
+}
+
+/// Formats per-type totals as "Type xN, ..." sorted by type
+fn format_totals(totals: BTreeMap<String, u32>) -> String {
+    if totals.is_empty() {
+        return "None".to_owned();
+    }
+
+    totals
+        .iter()
+        .map(|(name, total)| format!("{} x{}", name, total))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(super) fn setup_window(
+    mut commands: Commands,
+    ui_assets: Res<UiAssets>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = window_query.single();
+
+    let content_id = create_window(
+        &mut commands,
+        &ui_assets,
+        window,
+        (400, 200),
+        "Selection",
+        false,
+        SelectionInfoWindow,
+    );
+    commands.entity(content_id).with_children(|parent| {
+        for (label, data) in [
+            ("Area:", SelectionInfoData::Area),
+            ("Tiles:", SelectionInfoData::Tiles),
+            ("Objects:", SelectionInfoData::Objects),
+            ("Resources:", SelectionInfoData::Resources),
+            ("Items:", SelectionInfoData::Items),
+        ] {
+            create_row_container(parent).with_children(|parent| {
+                create_label(parent, &ui_assets, label, 14.0, FONT_COLOR);
+                create_label(parent, &ui_assets, "N/A", 14.0, FONT_COLOR)
+                    .insert(SelectionInfoWindowDataUI(data));
+            });
+        }
+
+        create_row_container(parent).with_children(|parent| {
+            for (content, action) in [
+                ("Deconstruct", SelectionAction::Deconstruct),
+                ("Copy", SelectionAction::Copy),
+                ("Clear", SelectionAction::Clear),
+            ] {
+                create_small_button(
+                    parent,
+                    &ui_assets,
+                    content,
+                    On::<Pointer<Click>>::run(select_action),
+                )
+                .insert(SelectionInfoWindowActionButton(action));
+            }
+        });
+    });
+}
+
+/// Shows the selection info window with totals for everything in the Selection
+pub(super) fn update_selection_info_ui(
+    selection: Res<Selection>,
+    tile_query: Query<&Tile>,
+    object_query: Query<&Object>,
+    item_query: Query<&TilemapId>,
+    item_layer_query: Query<&TiledMapItemLayer>,
+    mut text_query: Query<(&mut Text, &SelectionInfoWindowDataUI)>,
+    mut window_query: Query<&mut Visibility, With<SelectionInfoWindow>>,
+) {
+    let mut terrain = BTreeMap::new();
+    for tile in tile_query.iter_many(&selection.tiles) {
+        *terrain.entry(tile.0.terrain.to_string()).or_default() += 1;
+    }
+
+    let mut objects = BTreeMap::new();
+    let mut resources = BTreeMap::new();
+    for object in object_query.iter_many(&selection.objects) {
+        *objects.entry(object.get_type().to_string()).or_default() += 1;
+
+        match &object.0 {
+            ObjectData::Resources { r#type, amount, .. } => {
+                *resources.entry(r#type.to_string()).or_default() += *amount;
+            }
+        }
+    }
+
+    let mut items = BTreeMap::new();
+    for tilemap_id in item_query.iter_many(&selection.items) {
+        if let Ok(item_layer) = item_layer_query.get(tilemap_id.0) {
+            *items.entry(item_layer.0.to_string()).or_default() += 1;
+        }
+    }
+
+    let (width, height) = selection.size();
+    let area = format!("{}x{}", width, height);
+    let terrain = format_totals(terrain);
+    let objects = format_totals(objects);
+    let resources = format_totals(resources);
+    let items = format_totals(items);
+
+    for (mut text, data) in text_query.iter_mut() {
+        let content = match data.0 {
+            SelectionInfoData::Area => &area,
+            SelectionInfoData::Tiles => &terrain,
+            SelectionInfoData::Objects => &objects,
+            SelectionInfoData::Resources => &resources,
+            SelectionInfoData::Items => &items,
+        };
+        text.sections.get_mut(0).unwrap().value.clone_from(content);
+    }
+
+    *window_query.single_mut() = Visibility::Visible;
+}
+
+pub(super) fn hide_window(mut window_query: Query<&mut Visibility, With<SelectionInfoWindow>>) {
+    *window_query.single_mut() = Visibility::Hidden;
+}
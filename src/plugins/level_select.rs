@@ -0,0 +1,170 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_mod_picking::prelude::*;
+
+use crate::assets::levels::{Levels, LevelsLoader};
+use crate::cleanup_state;
+use crate::plugins::{game::CurrentLevel, ui::UiAssets};
+use crate::ui::{check_click_event, create_button, create_canvas, create_label, FONT_COLOR};
+use crate::AppState;
+
+const LEVELS_PATH: &str = "game.levels.ron";
+const ERROR_COLOR: Color = Color::srgb(0.9, 0.3, 0.3);
+
+/// Level select state tag
+#[derive(Debug, Component)]
+pub struct OnLevelSelect;
+
+/// Level select list tag, until the levels have been listed
+#[derive(Debug, Component)]
+pub struct PendingLevelList;
+
+/// Level manifest container resource
+#[derive(Debug, Default, Reflect, Resource)]
+pub struct LevelAssets {
+    pub levels: Handle<Levels>,
+}
+
+#[derive(Debug, Default)]
+pub struct LevelSelectPlugin;
+
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Levels>()
+            .register_asset_loader(LevelsLoader)
+            .add_systems(PreStartup, load_assets)
+            .add_systems(OnEnter(AppState::LevelSelect), enter)
+            .add_systems(Update, list_levels.run_if(in_state(AppState::LevelSelect)))
+            .add_systems(
+                OnExit(AppState::LevelSelect),
+                (exit, cleanup_state::<OnLevelSelect>, cleanup_state::<Node>),
+            );
+    }
+}
+
+fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // saves need to find their level from the main menu, so this is always loaded
+    commands.insert_resource(LevelAssets {
+        levels: asset_server.load(LEVELS_PATH),
+    });
+}
+
+fn enter(mut commands: Commands, ui_assets: Res<UiAssets>) {
+    info!("entering LevelSelect state");
+
+    commands.insert_resource(ClearColor(Color::srgb(0.0, 0.0, 0.0)));
+    commands.spawn((Camera2dBundle::default(), OnLevelSelect));
+
+    create_canvas(&mut commands, "Level Select")
+        .insert(OnLevelSelect)
+        .with_children(|parent| {
+            create_label(parent, &ui_assets, "Select Level", 32.0, FONT_COLOR);
+
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        flex_direction: FlexDirection::Column,
+                        margin: UiRect::all(Val::Px(15.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Name::new("Levels"),
+                Pickable::IGNORE,
+                PendingLevelList,
+            ));
+
+            create_button(
+                parent,
+                &ui_assets,
+                "Back",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut game_state: ResMut<NextState<AppState>>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        game_state.set(AppState::MainMenu);
+                    },
+                ),
+            );
+        });
+}
+
+fn exit(mut commands: Commands) {
+    info!("exiting LevelSelect state");
+
+    commands.remove_resource::<ClearColor>();
+}
+
+/// Fills in the level list once the manifest has loaded
+fn list_levels(
+    mut commands: Commands,
+    ui_assets: Res<UiAssets>,
+    asset_server: Res<AssetServer>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Levels>>,
+    list_query: Query<Entity, With<PendingLevelList>>,
+) {
+    let Ok(list) = list_query.get_single() else {
+        return;
+    };
+
+    let Some(levels) = levels.get(&level_assets.levels) else {
+        if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&level_assets.levels) {
+            error!("Failed to load levels: {}", err);
+
+            commands
+                .entity(list)
+                .remove::<PendingLevelList>()
+                .with_children(|parent| {
+                    create_label(
+                        parent,
+                        &ui_assets,
+                        format!("Failed to load levels: {}", err),
+                        14.0,
+                        ERROR_COLOR,
+                    );
+                });
+        }
+        return;
+    };
+
+    commands
+        .entity(list)
+        .remove::<PendingLevelList>()
+        .with_children(|parent| {
+            for level in &levels.levels {
+                let level = level.clone();
+                create_button(
+                    parent,
+                    &ui_assets,
+                    level.name.clone(),
+                    On::<Pointer<Click>>::run(
+                        move |event: Listener<Pointer<Click>>,
+                              mut commands: Commands,
+                              mut game_state: ResMut<NextState<AppState>>| {
+                            if !check_click_event(
+                                event.listener(),
+                                event.target,
+                                event.button,
+                                PointerButton::Primary,
+                            ) {
+                                return;
+                            }
+
+                            let level = CurrentLevel::new(&level);
+                            info!("Starting level {} ({})", level.name, level.map);
+                            commands.insert_resource(level);
+                            game_state.set(AppState::LoadAssets);
+                        },
+                    ),
+                );
+            }
+        });
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState, UntypedAssetId},
+    prelude::*,
+};
+use bevy_mod_picking::prelude::*;
+
+use crate::cleanup_state;
+use crate::plugins::ui::UiAssets;
+use crate::ui::{
+    check_click_event, create_button, create_canvas, create_column_container, create_label,
+    FONT_COLOR,
+};
+use crate::AppState;
+
+const PROGRESS_BAR_WIDTH: f32 = 400.0;
+const PROGRESS_BAR_HEIGHT: f32 = 24.0;
+const PROGRESS_BAR_BACKGROUND: Color = Color::srgb(0.15, 0.15, 0.15);
+const PROGRESS_BAR_FILL: Color = Color::srgb(0.35, 0.75, 0.35);
+const ERROR_COLOR: Color = Color::srgb(0.9, 0.3, 0.3);
+
+/// Loading screen state tag
+#[derive(Debug, Component)]
+pub struct OnLoadingScreen;
+
+/// Loading screen progress bar fill tag
+#[derive(Debug, Component)]
+pub struct LoadingProgressBar;
+
+/// Loading screen progress label tag
+#[derive(Debug, Component)]
+pub struct LoadingProgressLabel;
+
+/// Tracks every asset the game needs before it can start
+///
+/// Plugins register their handles when they request them,
+/// the LoadAssets state waits until every tracked asset
+/// (and all of its dependencies) has loaded
+#[derive(Debug, Default, Resource)]
+pub struct AssetTracker {
+    handles: HashMap<UntypedAssetId, UntypedHandle>,
+}
+
+impl AssetTracker {
+    /// Starts tracking an asset
+    ///
+    /// Tracked assets are kept alive by the tracker
+    #[inline]
+    pub fn track(&mut self, handle: impl Into<UntypedHandle>) {
+        let handle = handle.into();
+        self.handles.insert(handle.id(), handle);
+    }
+
+    /// Checks the load state of every tracked asset
+    pub fn get_progress(&self, asset_server: &AssetServer) -> AssetTrackerProgress {
+        let mut progress = AssetTrackerProgress {
+            total: self.handles.len(),
+            ..default()
+        };
+
+        for handle in self.handles.values() {
+            match asset_server.get_recursive_dependency_load_state(handle) {
+                Some(RecursiveDependencyLoadState::Loaded) => progress.loaded += 1,
+                Some(RecursiveDependencyLoadState::Failed) => {
+                    progress.failed.push(describe_failure(asset_server, handle))
+                }
+                _ => (),
+            }
+        }
+
+        // keep the error report stable between frames
+        progress.failed.sort();
+
+        progress
+    }
+
+    /// Stops tracking every asset
+    ///
+    /// Assets that nothing else holds a handle to are unloaded
+    #[inline]
+    pub fn clear(&mut self) {
+        self.handles.clear();
+    }
+
+    #[inline]
+    pub fn is_loaded(&self, asset_server: &AssetServer) -> bool {
+        self.handles.values().all(|handle| {
+            asset_server.get_recursive_dependency_load_state(handle)
+                == Some(RecursiveDependencyLoadState::Loaded)
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AssetTrackerProgress {
+    pub loaded: usize,
+    pub total: usize,
+
+    /// One message per failed asset
+    pub failed: Vec<String>,
+}
+
+impl AssetTrackerProgress {
+    /// Loaded fraction, 0.0 - 1.0
+    #[inline]
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+}
+
+/// Asset load failure report resource
+#[derive(Debug, Resource)]
+pub struct AssetLoadFailed(pub Vec<String>);
+
+/// Describes why a tracked asset failed to load
+///
+/// Failures in dependencies (sprite sheets, tilesets, etc)
+/// are reported against the tracked asset that needs them
+fn describe_failure(asset_server: &AssetServer, handle: &UntypedHandle) -> String {
+    let path = asset_server
+        .get_path(handle.id())
+        .map(|path| path.to_string())
+        .unwrap_or_else(|| handle.id().to_string());
+
+    match asset_server.get_load_state(handle) {
+        Some(LoadState::Failed(err)) => format!("{}: {}", path, err),
+        _ => format!("{}: a dependency failed to load", path),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetTracker>()
+            .add_systems(OnEnter(AppState::LoadAssets), enter)
+            .add_systems(
+                Update,
+                (
+                    update_progress.run_if(not(resource_exists::<AssetLoadFailed>)),
+                    show_error.run_if(resource_added::<AssetLoadFailed>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::LoadAssets)),
+            )
+            .add_systems(
+                OnExit(AppState::LoadAssets),
+                (
+                    exit,
+                    cleanup_state::<OnLoadingScreen>,
+                    cleanup_state::<Node>,
+                ),
+            );
+    }
+}
+
+fn enter(mut commands: Commands, ui_assets: Res<UiAssets>) {
+    info!("entering LoadAssets state");
+
+    commands.insert_resource(ClearColor(Color::srgb(0.0, 0.0, 0.0)));
+    commands.spawn((Camera2dBundle::default(), OnLoadingScreen));
+
+    create_canvas(&mut commands, "Loading")
+        .insert(OnLoadingScreen)
+        .with_children(|parent| {
+            create_label(parent, &ui_assets, "Loading ...", 32.0, FONT_COLOR);
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(PROGRESS_BAR_WIDTH),
+                            height: Val::Px(PROGRESS_BAR_HEIGHT),
+                            margin: UiRect::all(Val::Px(15.0)),
+                            ..default()
+                        },
+                        background_color: PROGRESS_BAR_BACKGROUND.into(),
+                        ..default()
+                    },
+                    Name::new("Progress Bar"),
+                    Pickable::IGNORE,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: PROGRESS_BAR_FILL.into(),
+                            ..default()
+                        },
+                        Name::new("Progress Bar Fill"),
+                        Pickable::IGNORE,
+                        LoadingProgressBar,
+                    ));
+                });
+
+            create_label(parent, &ui_assets, "", 14.0, FONT_COLOR).insert(LoadingProgressLabel);
+        });
+}
+
+fn exit(mut commands: Commands, mut asset_tracker: ResMut<AssetTracker>) {
+    info!("exiting LoadAssets state");
+
+    // the next load (another level, etc) only waits on its own assets
+    asset_tracker.clear();
+
+    commands.remove_resource::<AssetLoadFailed>();
+    commands.remove_resource::<ClearColor>();
+}
+
+fn update_progress(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    asset_tracker: Res<AssetTracker>,
+    mut progress_bar_query: Query<&mut Style, With<LoadingProgressBar>>,
+    mut progress_label_query: Query<&mut Text, With<LoadingProgressLabel>>,
+) {
+    let progress = asset_tracker.get_progress(&asset_server);
+
+    if !progress.failed.is_empty() {
+        for failure in &progress.failed {
+            error!("Asset failed to load: {}", failure);
+        }
+
+        commands.insert_resource(AssetLoadFailed(progress.failed));
+        return;
+    }
+
+    for mut style in progress_bar_query.iter_mut() {
+        style.width = Val::Percent(progress.fraction() * 100.0);
+    }
+
+    for mut text in progress_label_query.iter_mut() {
+        text.sections[0].value = format!("{} / {} assets", progress.loaded, progress.total);
+    }
+}
+
+fn show_error(
+    mut commands: Commands,
+    ui_assets: Res<UiAssets>,
+    failed: Res<AssetLoadFailed>,
+    canvas_query: Query<Entity, (With<OnLoadingScreen>, With<Node>)>,
+) {
+    for canvas in canvas_query.iter() {
+        commands.entity(canvas).despawn_recursive();
+    }
+
+    create_canvas(&mut commands, "Loading Failed")
+        .insert(OnLoadingScreen)
+        .with_children(|parent| {
+            create_label(
+                parent,
+                &ui_assets,
+                "Failed to load assets",
+                32.0,
+                ERROR_COLOR,
+            );
+
+            create_column_container(parent)
+                .insert(Name::new("Errors"))
+                .with_children(|parent| {
+                    for failure in &failed.0 {
+                        create_label(parent, &ui_assets, failure, 14.0, FONT_COLOR);
+                    }
+                });
+
+            create_button(
+                parent,
+                &ui_assets,
+                "Main Menu",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut game_state: ResMut<NextState<AppState>>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        game_state.set(AppState::MainMenu);
+                    },
+                ),
+            );
+        });
+}
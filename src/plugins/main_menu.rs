@@ -2,7 +2,10 @@ use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 
 use crate::cleanup_state;
-use crate::plugins::ui::UiAssets;
+use crate::plugins::{
+    game::save::{LoadGameEvent, PendingLoad},
+    ui::UiAssets,
+};
 use crate::ui::{check_click_event, create_button, create_canvas};
 use crate::AppState;
 
@@ -30,6 +33,9 @@ impl Plugin for MainMenuPlugin {
 fn enter(mut commands: Commands, ui_assets: Res<UiAssets>) {
     info!("entering MainMenu state");
 
+    // a load may have been abandoned (failed assets, quit during load, etc)
+    commands.remove_resource::<PendingLoad>();
+
     commands.insert_resource(ClearColor(Color::srgb(0.0, 0.0, 0.0)));
     commands.spawn((Camera2dBundle::default(), OnMainMenu));
 
@@ -51,7 +57,27 @@ fn enter(mut commands: Commands, ui_assets: Res<UiAssets>) {
                         ) {
                             return;
                         }
-                        game_state.set(AppState::LoadAssets);
+                        game_state.set(AppState::LevelSelect);
+                    },
+                ),
+            );
+
+            create_button(
+                parent,
+                &ui_assets,
+                "Load Game",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut load_events: EventWriter<LoadGameEvent>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        load_events.send_default();
                     },
                 ),
             );
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bevy::{
+    asset::{
+        io::{
+            AssetReader, AssetReaderError, AssetSource, AssetSourceId, ErasedAssetReader,
+            PathStream, Reader, VecReader,
+        },
+        AsyncReadExt,
+    },
+    prelude::*,
+};
+
+use crate::assets::registry::{Registry, REGISTRY_PATH};
+use crate::tiled::generator::{
+    generate_map, get_generated_map_file_seed, GeneratorTilesets, MapGeneratorSettings,
+    GENERATED_MAP_SOURCE, OBJECTS_TILESET, TILES_TILESET,
+};
+
+/// Default asset source directory, matching the AssetPlugin default
+const ASSETS_PATH: &str = "assets";
+
+/// Serves generated maps as TMX
+///
+/// The seed is the file name, so generated://1234.tmx is always the same map.
+/// Tilesets referenced by the map and the Registry
+/// are read from the default asset source
+struct GeneratedMapReader {
+    assets: Box<dyn ErasedAssetReader>,
+}
+
+impl GeneratedMapReader {
+    async fn read_asset(&self, path: &Path) -> Result<Vec<u8>, AssetReaderError> {
+        let mut reader = self.assets.read(path).await?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(bytes)
+    }
+
+    async fn load_registry(&self) -> Result<Registry, AssetReaderError> {
+        let bytes = self.read_asset(Path::new(REGISTRY_PATH)).await?;
+        let registry = ron::de::from_bytes::<Registry>(&bytes).map_err(invalid_data)?;
+        registry.validate().map_err(invalid_data)?;
+        Ok(registry)
+    }
+
+    async fn load_tilesets(&self) -> Result<GeneratorTilesets, AssetReaderError> {
+        let mut files = HashMap::new();
+        for tileset in [TILES_TILESET, OBJECTS_TILESET] {
+            let path = PathBuf::from(tileset);
+            let bytes = self.read_asset(&path).await?;
+            files.insert(path, bytes);
+        }
+
+        let mut loader = tiled::Loader::with_reader(|path: &Path| -> std::io::Result<_> {
+            files
+                .get(path)
+                .map(|bytes| Cursor::new(bytes.clone()))
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+        });
+        GeneratorTilesets::load(&mut loader, "").map_err(invalid_data)
+    }
+}
+
+fn invalid_data(err: impl std::error::Error + Send + Sync + 'static) -> AssetReaderError {
+    AssetReaderError::Io(Arc::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        err,
+    )))
+}
+
+impl AssetReader for GeneratedMapReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        let seed = path
+            .to_str()
+            .and_then(get_generated_map_file_seed)
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?;
+
+        let registry = self.load_registry().await?;
+        let tilesets = self.load_tilesets().await?;
+
+        info!("Generating map from seed {}", seed);
+        let map = generate_map(&MapGeneratorSettings::new(seed, &registry), &tilesets);
+
+        let reader: Box<Reader<'a>> = Box::new(VecReader::new(map.to_tmx(&tilesets).into_bytes()));
+        Ok(reader)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        // generated maps use the default loader settings
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn is_directory<'a>(&'a self, _path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(false)
+    }
+}
+
+/// Registers the generated map asset source
+///
+/// This must be added before DefaultPlugins (the AssetPlugin)
+#[derive(Debug, Default)]
+pub struct MapGeneratorPlugin;
+
+impl Plugin for MapGeneratorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_source(
+            AssetSourceId::from(GENERATED_MAP_SOURCE),
+            AssetSource::build().with_reader(|| {
+                Box::new(GeneratedMapReader {
+                    assets: AssetSource::get_default_reader(ASSETS_PATH.to_string())(),
+                })
+            }),
+        );
+    }
+}
@@ -2,7 +2,10 @@ pub mod audio;
 pub mod debug;
 pub mod game;
 pub mod game_ui;
+pub mod level_select;
+pub mod loading;
 pub mod main_menu;
+pub mod map_generator;
 pub mod pause_menu;
 pub mod splash;
 pub mod tiled;
@@ -1,7 +1,15 @@
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 
-use crate::plugins::{audio::Music, game::IsPaused, ui::UiAssets};
+use crate::plugins::{
+    audio::Music,
+    game::{
+        export::ExportMapEvent,
+        save::{LoadGameEvent, SaveGameEvent},
+        IsEditing, IsPaused,
+    },
+    ui::UiAssets,
+};
 use crate::ui::{check_click_event, create_button, create_canvas};
 use crate::AppState;
 
@@ -45,6 +53,92 @@ fn setup(mut commands: Commands, ui_assets: Res<UiAssets>) {
                 ),
             );
 
+            create_button(
+                parent,
+                &ui_assets,
+                "Save Game",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut save_events: EventWriter<SaveGameEvent>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        save_events.send_default();
+                    },
+                ),
+            );
+
+            create_button(
+                parent,
+                &ui_assets,
+                "Load Game",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut load_events: EventWriter<LoadGameEvent>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        load_events.send_default();
+                    },
+                ),
+            );
+
+            create_button(
+                parent,
+                &ui_assets,
+                "Export Map",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     mut export_events: EventWriter<ExportMapEvent>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        export_events.send_default();
+                    },
+                ),
+            );
+
+            create_button(
+                parent,
+                &ui_assets,
+                "Map Editor",
+                On::<Pointer<Click>>::run(
+                    |event: Listener<Pointer<Click>>,
+                     editing_state: Res<State<IsEditing>>,
+                     mut next_editing_state: ResMut<NextState<IsEditing>>,
+                     mut pause_state: ResMut<NextState<IsPaused>>| {
+                        if !check_click_event(
+                            event.listener(),
+                            event.target,
+                            event.button,
+                            PointerButton::Primary,
+                        ) {
+                            return;
+                        }
+                        next_editing_state.set(match editing_state.get() {
+                            IsEditing::Playing => IsEditing::Editing,
+                            IsEditing::Editing => IsEditing::Playing,
+                        });
+                        pause_state.set(IsPaused::Running);
+                    },
+                ),
+            );
+
             create_button(
                 parent,
                 &ui_assets,
@@ -1,21 +1,45 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_ecs_tilemap::prelude::*;
 use bevy_mod_picking::prelude::*;
-
-use crate::assets::tiled::*;
-use crate::data::objects::ObjectData;
-use crate::plugins::game::OnInGame;
-use crate::tilemap::{spawn_object, spawn_tile};
-
-/// Maps layer index to layer id
+use strum::IntoEnumIterator;
+
+use crate::assets::{registry::Registry, tiled::*};
+use crate::data::{direction::Direction, items::ItemType, objects::ObjectData, tiles::TileData};
+use crate::plugins::game::{
+    camera::MainCamera, items::TransportNetwork, objects::Object, tiles::Tile, GameAssets, OnInGame,
+};
+use crate::tiled::{
+    get_layers, get_object_footprint, get_object_tile_flip, get_tile_animation, get_tile_flip,
+    validate_map, FlattenedLayer, TiledMapValidationError, TILE_HEIGHT, TILE_WIDTH,
+};
+use crate::tilemap::{
+    animate_tiles, spawn_object, spawn_tile, sync_footprint_parts, update_object, TileAnimation,
+};
+
+/// Chunks past the edge of the view that are kept loaded
+const CHUNK_LOAD_MARGIN: i32 = 1;
+
+/// Tracks the entities spawned for a map
 ///
-/// Tiles are created as children
-/// of their respective layer entities
+/// Layers are created as children of the root entity
+/// and tiles are created as children of their respective layer entities
 #[derive(Debug, Default, Component)]
 pub struct TiledLayersStorage {
+    pub root: Option<Entity>,
+
+    /// Maps Tiled layer id to layer entity
     pub storage: HashMap<u32, Entity>,
+
+    /// Item layers aren't part of the Tiled map
+    pub item_layers: HashMap<ItemType, Entity>,
+
+    /// Size of the spawned map
+    pub size: TilemapSize,
+
+    /// Every Object id spawned from the map
+    pub spawned_objects: HashSet<u32>,
 }
 
 // TODO: this shouldn't need to be a bundle,
@@ -41,9 +65,65 @@ pub struct TiledMapTileLayer;
 #[derive(Debug, Component)]
 pub struct TiledMapObjectLayer;
 
+/// The tilemaps a Tiled layer is rendered with, one per-tileset
+///
+/// A tilemap can only use a single (atlas) texture, so every tileset used by a layer
+/// gets its own tilemap as a child of the layer. The layer entity itself isn't rendered,
+/// it holds the TileStorage for all of its tiles, and each tile's TilemapId
+/// points at the tilemap for its tileset
+///
+/// Tilesets are kept in the order they are first used by the layer
+#[derive(Debug, Default, Component)]
+pub struct TiledLayerTilesets(pub Vec<(String, Entity)>);
+
+impl TiledLayerTilesets {
+    /// Gets the tilemap for a tileset
+    #[inline]
+    pub fn get(&self, tileset: impl AsRef<str>) -> Option<Entity> {
+        let tileset = tileset.as_ref();
+        self.0
+            .iter()
+            .find(|(name, _)| name == tileset)
+            .map(|(_, tilemap)| *tilemap)
+    }
+
+    /// Gets the tileset a tilemap renders
+    #[inline]
+    pub fn get_tileset(&self, tilemap: Entity) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(_, entity)| *entity == tilemap)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Gets the tilemap for the first tileset used by the layer
+    #[inline]
+    pub fn first(&self) -> Option<Entity> {
+        self.0.first().map(|(_, tilemap)| *tilemap)
+    }
+}
+
+/// Tile color of a Tiled layer, its opacity combined with its parent groups
+///
+/// Tiles and Objects are returned to this color after being highlighted
+#[derive(Debug, Copy, Clone, Component, Deref)]
+pub struct TiledLayerColor(pub Color);
+
+/// The loaded chunks of an infinite tile layer
+///
+/// Every loaded chunk has a tilemap per-tileset it uses, spawned as children of the layer,
+/// the layer's TileStorage only holds the tiles of loaded chunks
+#[derive(Debug, Default, Component)]
+pub struct TiledLayerChunks {
+    /// Maps Tiled chunk position to chunk tilemaps
+    pub loaded: HashMap<(i32, i32), Vec<Entity>>,
+}
+
 /// Item layer tag
+///
+/// One per-Item type
 #[derive(Debug, Component)]
-pub struct TiledMapItemLayer;
+pub struct TiledMapItemLayer(pub ItemType);
 
 /// Emitted when an Object is clicked
 #[derive(Debug, Event)]
@@ -54,7 +134,6 @@ pub struct TiledMapObjectClickEvent {
 }
 
 /// Emitted when an Item is clicked
-#[allow(dead_code)]
 #[derive(Debug, Event)]
 pub struct TiledMapItemClickEvent {
     pub listener: Entity,
@@ -62,10 +141,14 @@ pub struct TiledMapItemClickEvent {
     pub button: PointerButton,
 }
 
-const MIN_TILEMAP_WIDTH: u32 = 25;
-const MIN_TILEMAP_HEIGHT: u32 = 25;
-const TILE_WIDTH: u32 = 32;
-const TILE_HEIGHT: u32 = 32;
+/// Emitted when a map fails validation
+///
+/// The map is not spawned
+#[derive(Debug, Event)]
+pub struct TiledMapLoadFailedEvent {
+    pub name: String,
+    pub errors: Vec<TiledMapValidationError>,
+}
 
 #[derive(Debug, Default)]
 pub struct TiledMapPlugin;
@@ -76,18 +159,31 @@ impl Plugin for TiledMapPlugin {
             .register_asset_loader(TiledLoader)
             .add_event::<TiledMapObjectClickEvent>()
             .add_event::<TiledMapItemClickEvent>()
-            .add_systems(Update, process_loaded_maps);
+            .add_event::<TiledMapLoadFailedEvent>()
+            // item layers need the registry, which is part of the game assets
+            .add_systems(
+                Update,
+                (process_loaded_maps, stream_tiled_chunks)
+                    .chain()
+                    .run_if(resource_exists::<GameAssets>),
+            )
+            .add_systems(Update, animate_tiles)
+            .add_systems(PostUpdate, sync_footprint_parts);
     }
 }
 
 // TODO: how does this even work ... the asset is loaded
 // before the bundle is available, right?
 // so it's just getting lucky on the timing?
+#[allow(clippy::too_many_arguments)]
 fn process_loaded_maps(
     mut commands: Commands,
     mut map_events: EventReader<AssetEvent<TiledMap>>,
     tiled_maps: Res<Assets<TiledMap>>,
-    tile_storage_query: Query<(Entity, &TileStorage)>,
+    registry: Option<Res<Registry>>,
+    mut changed_maps: Local<Vec<AssetId<TiledMap>>>,
+    mut load_failed_events: EventWriter<TiledMapLoadFailedEvent>,
+    queries: MapLayerQueries,
     mut tiled_map_query: Query<(
         &Handle<TiledMap>,
         &mut TiledLayersStorage,
@@ -95,7 +191,7 @@ fn process_loaded_maps(
     )>,
     //new_maps: Query<&Handle<TiledMap>, Added<Handle<TiledMap>>>,
 ) {
-    let mut changed_maps = Vec::<AssetId<TiledMap>>::default();
+    // changes are held onto until the registry is available
     for event in map_events.read() {
         match event {
             AssetEvent::Added { id } => {
@@ -110,7 +206,7 @@ fn process_loaded_maps(
                 debug!("Map {} removed", id);
                 // if mesh was modified and removed in the same update, ignore the modification
                 // events are ordered so future modification events are ok
-                changed_maps.retain(|changed_handle| changed_handle == id);
+                changed_maps.retain(|changed_handle| changed_handle != id);
             }
             _ => continue,
         }
@@ -124,478 +220,1038 @@ fn process_loaded_maps(
         changed_maps.push(new_map_handle.id());
     }*/
 
-    for changed_map in changed_maps.iter() {
+    let Some(registry) = registry else {
+        return;
+    };
+
+    for changed_map in changed_maps.drain(..) {
         for (map_handle, mut layer_storage, render_settings) in tiled_map_query.iter_mut() {
             // only deal with currently changed map
-            if map_handle.id() != *changed_map {
+            if map_handle.id() != changed_map {
                 continue;
             }
 
             debug!("Processing map {}", map_handle.id());
 
             if let Some(tiled_map) = tiled_maps.get(map_handle) {
-                // TODO: Create a RemoveMap component..
-                for layer_entity in layer_storage.storage.values() {
-                    if let Ok((_, layer_tile_storage)) = tile_storage_query.get(*layer_entity) {
-                        for tile in layer_tile_storage.iter().flatten() {
-                            commands.entity(*tile).despawn_recursive()
-                        }
+                // validate before touching the existing map
+                let errors = validate_map(&tiled_map.map, &registry);
+                if !errors.is_empty() {
+                    for error in &errors {
+                        error!("Map {} is invalid: {}", tiled_map.name, error);
                     }
-                    // commands.entity(*layer_entity).despawn_recursive();
+
+                    load_failed_events.send(TiledMapLoadFailedEvent {
+                        name: tiled_map.name.clone(),
+                        errors,
+                    });
+                    continue;
                 }
 
                 process_loaded_map(
                     &mut commands,
                     tiled_map,
+                    &registry,
                     &mut layer_storage,
                     *render_settings,
+                    &queries,
                 );
             }
         }
     }
 }
 
+/// Existing map entities that are updated in place on reload
+#[derive(SystemParam)]
+struct MapLayerQueries<'w, 's> {
+    tile_layer_query: Query<'w, 's, &'static TileStorage, With<TiledMapTileLayer>>,
+    object_layer_query: Query<'w, 's, &'static TileStorage, With<TiledMapObjectLayer>>,
+    tilesets_query: Query<'w, 's, &'static TiledLayerTilesets>,
+    chunks_query: Query<'w, 's, &'static TiledLayerChunks>,
+    tile_query: Query<'w, 's, &'static TilemapId>,
+    object_query: Query<'w, 's, (&'static Object, &'static TileVisible, &'static TilemapId)>,
+    network: Option<Res<'w, TransportNetwork>>,
+}
+
+/// Spawns the layers of a loaded map, or updates them in place if the map was reloaded
+///
+/// Items are not part of the Tiled map and are left alone on reload,
+/// so the map size can't change while Items are placed on it
+///
+/// The map must have passed validate_map()
 fn process_loaded_map(
     commands: &mut Commands,
     tiled_map: &TiledMap,
+    registry: &Registry,
     layer_storage: &mut TiledLayersStorage,
     render_settings: TilemapRenderSettings,
+    queries: &MapLayerQueries,
 ) {
     debug!("Processing loaded map {}", tiled_map.name);
 
-    if tiled_map.map.width < MIN_TILEMAP_WIDTH || tiled_map.map.height < MIN_TILEMAP_HEIGHT {
-        panic!("Map {} is below the minimum size", tiled_map.name);
+    let map_size = get_map_size(tiled_map);
+
+    let root = match layer_storage.root {
+        Some(root) => {
+            if layer_storage.size.x != map_size.x || layer_storage.size.y != map_size.y {
+                warn!(
+                    "Map {} changed size from {}x{} to {}x{}, restart to apply",
+                    tiled_map.name,
+                    layer_storage.size.x,
+                    layer_storage.size.y,
+                    map_size.x,
+                    map_size.y
+                );
+                return;
+            }
+            root
+        }
+        None => {
+            let root = commands
+                .spawn((
+                    SpatialBundle::default(),
+                    Name::new(tiled_map.name.clone()),
+                    OnInGame,
+                ))
+                .id();
+            layer_storage.root = Some(root);
+            layer_storage.size = map_size;
+            root
+        }
+    };
+
+    // The TilemapBundle requires that all tile images come exclusively from a single
+    // tiled texture or from a Vec of independent per-tile images. Since Tiled allows
+    // tiles of mixed tilesets on each layer, each layer is split into one tilemap
+    // per-tileset (see TiledLayerTilesets). The tilemaps are children of their layer
+    // so they keep the layer's z-order.
+
+    // layers are tracked by their Tiled id, which doesn't change when layers are reordered
+    let layers = get_layers(&tiled_map.map);
+    let layer_ids = layers
+        .iter()
+        .map(|layer| layer.layer.id())
+        .collect::<HashSet<_>>();
+    layer_storage.storage.retain(|layer_id, layer_entity| {
+        let keep = layer_ids.contains(layer_id);
+        if !keep {
+            debug!("Removing layer {}", layer_id);
+            commands.entity(*layer_entity).despawn_recursive();
+        }
+        keep
+    });
+
+    // Once materials have been created/added we need to then create the layers.
+    // Group layers are flattened, so their children are spawned as top level layers
+    for (layer_index, flattened_layer) in layers.iter().enumerate() {
+        let layer = &flattened_layer.layer;
+        debug!("Processing layer {} ({}) ", layer_index, layer.id());
+
+        match layer.layer_type() {
+            tiled::LayerType::Tiles(tiled::TileLayer::Finite(tile_layer)) => {
+                process_tile_layer(
+                    commands,
+                    root,
+                    layer_storage,
+                    tiled_map,
+                    layer_index,
+                    flattened_layer,
+                    &tile_layer,
+                    render_settings,
+                    queries,
+                );
+            }
+            tiled::LayerType::Tiles(tiled::TileLayer::Infinite(_)) => {
+                process_infinite_tile_layer(
+                    commands,
+                    root,
+                    layer_storage,
+                    tiled_map,
+                    layer_index,
+                    flattened_layer,
+                    queries,
+                );
+            }
+            tiled::LayerType::Objects(object_layer) => {
+                process_object_layer(
+                    commands,
+                    root,
+                    layer_storage,
+                    tiled_map,
+                    layer_index,
+                    flattened_layer,
+                    &object_layer,
+                    render_settings,
+                    queries,
+                );
+            }
+            _ => unreachable!(),
+        }
     }
 
-    if tiled_map.map.tile_width != TILE_WIDTH || tiled_map.map.tile_height != TILE_HEIGHT {
-        panic!("Map {} tiles have invalid tile size", tiled_map.name);
+    // item layers go above the map layers
+    for (idx, item_type) in ItemType::iter().enumerate() {
+        let layer_index = layers.len() + 1 + idx;
+        match layer_storage.item_layers.get(&item_type) {
+            Some(layer_entity) => {
+                commands
+                    .entity(*layer_entity)
+                    .insert(get_layer_transform(tiled_map, layer_index));
+            }
+            None => create_item_layer(
+                commands,
+                root,
+                layer_storage,
+                tiled_map,
+                layer_index,
+                item_type,
+                TilemapTexture::Single(registry.get_item(item_type).sprite.image_handle.clone()),
+                render_settings,
+            ),
+        }
     }
+}
 
-    commands
-        .spawn((
-            SpatialBundle::default(),
-            Name::new(tiled_map.name.clone()),
-            OnInGame,
-        ))
-        .with_children(|parent| {
-            // TODO: better explain the way this is restricted and what we're doing about it
-            //
-            // The TilemapBundle requires that all tile images come exclusively from a single
-            // tiled texture or from a Vec of independent per-tile images. Furthermore, all of
-            // the per-tile images must be the same size. Since Tiled allows tiles of mixed
-            // tilesets on each layer and allows differently-sized tile images in each tileset,
-            // this means we need to load each combination of tileset and layer separately.
-
-            // Once materials have been created/added we need to then create the layers.
-            for (layer_index, layer) in tiled_map.map.layers().enumerate() {
-                debug!("Processing layer {} ({}) ", layer_index, layer.id());
-
-                if layer.offset_x != 0.0 || layer.offset_y != 0.0 {
-                    panic!("Layer {} has invalid offset", layer.id());
-                }
+#[inline]
+fn get_map_size(tiled_map: &TiledMap) -> TilemapSize {
+    tiled_map.bounds.size()
+}
 
-                match layer.layer_type() {
-                    tiled::LayerType::Tiles(tile_layer) => {
-                        process_tile_layer(
-                            parent,
-                            layer_storage,
-                            tiled_map,
-                            layer_index,
-                            layer.id(),
-                            &tile_layer,
-                            render_settings,
-                        );
-                    }
-                    tiled::LayerType::Objects(object_layer) => {
-                        process_object_layer(
-                            parent,
-                            layer_storage,
-                            tiled_map,
-                            layer_index,
-                            layer.id(),
-                            &object_layer,
-                            render_settings,
-                        );
-                    }
-                    _ => {
-                        panic!(
-                            "Invalid layer {} - only Tile and Object layers are supported.",
-                            layer.id()
-                        );
-                    }
-                }
-            }
+#[inline]
+fn get_map_grid_size(tiled_map: &TiledMap) -> TilemapGridSize {
+    TilemapGridSize {
+        x: tiled_map.map.tile_width as f32,
+        y: tiled_map.map.tile_height as f32,
+    }
+}
 
-            create_item_layer(parent, layer_storage, tiled_map, render_settings);
-        });
+#[inline]
+fn get_layer_transform(tiled_map: &TiledMap, layer_index: usize) -> Transform {
+    get_tilemap_center_transform(
+        &get_map_size(tiled_map),
+        &get_map_grid_size(tiled_map),
+        &TilemapType::Square,
+        layer_index as f32,
+    )
 }
 
-#[allow(clippy::too_many_arguments)]
-fn process_tile_layer(
-    parent: &mut ChildBuilder,
-    layer_storage: &mut TiledLayersStorage,
+fn create_layer_bundle(
     tiled_map: &TiledMap,
     layer_index: usize,
-    layer_id: u32,
-    layer: &tiled::TileLayer,
+    storage: TileStorage,
+    texture: TilemapTexture,
     render_settings: TilemapRenderSettings,
-) {
-    debug!("Processing tile layer {} ({})", layer_index, layer_id);
+) -> TilemapBundle {
+    TilemapBundle {
+        grid_size: get_map_grid_size(tiled_map),
+        size: get_map_size(tiled_map),
+        storage,
+        texture,
+        tile_size: TilemapTileSize {
+            x: TILE_WIDTH as f32,
+            y: TILE_HEIGHT as f32,
+        },
+        transform: get_layer_transform(tiled_map, layer_index),
+        map_type: TilemapType::Square,
+        render_settings,
+        ..Default::default()
+    }
+}
 
-    let tiled::TileLayer::Finite(layer) = layer else {
-        panic!("Tile layer {} may not be infinite", layer_id);
-    };
+/// Gets the entity for a map layer
+///
+/// The existing entity is reused if it's still the same kind of layer,
+/// otherwise it's replaced with a new one
+fn get_layer_entity(
+    commands: &mut Commands,
+    root: Entity,
+    layer_storage: &mut TiledLayersStorage,
+    layer_id: u32,
+    reuse: bool,
+    name: String,
+) -> Entity {
+    match layer_storage.storage.get(&layer_id) {
+        Some(layer_entity) if reuse => *layer_entity,
+        previous => {
+            if let Some(previous) = previous {
+                commands.entity(*previous).despawn_recursive();
+            }
 
-    let map_size = TilemapSize {
-        x: tiled_map.map.width,
-        y: tiled_map.map.height,
-    };
+            let layer_entity = commands
+                .spawn((SpatialBundle::default(), Name::new(name)))
+                .set_parent(root)
+                .id();
+            layer_storage.storage.insert(layer_id, layer_entity);
+            layer_entity
+        }
+    }
+}
 
-    let grid_size = TilemapGridSize {
-        x: tiled_map.map.tile_width as f32,
-        y: tiled_map.map.tile_height as f32,
-    };
+/// Tiled layers aren't rendered themselves, their tiles are rendered by their tileset tilemaps
+///
+/// Hiding the layer hides its tileset tilemaps
+fn create_tiled_layer_bundle(
+    tiled_map: &TiledMap,
+    layer_index: usize,
+    flattened_layer: &FlattenedLayer,
+    storage: TileStorage,
+    tilesets: TiledLayerTilesets,
+) -> impl Bundle {
+    (
+        get_map_size(tiled_map),
+        get_map_grid_size(tiled_map),
+        TilemapType::Square,
+        storage,
+        get_layer_transform(tiled_map, layer_index),
+        if flattened_layer.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
+        TiledLayerColor(get_layer_color(flattened_layer)),
+        tilesets,
+    )
+}
 
-    let map_type = match tiled_map.map.orientation {
-        tiled::Orientation::Orthogonal => TilemapType::Square,
-        _ => panic!("Tile layer {} must be Orthogonal", layer_id),
-    };
+/// Tilemaps have no opacity of their own, so it's applied to every tile
+#[inline]
+fn get_layer_color(flattened_layer: &FlattenedLayer) -> Color {
+    Color::WHITE.with_alpha(flattened_layer.opacity)
+}
 
-    let mut tile_storage = TileStorage::empty(map_size);
-    let mut layer_entity = parent.spawn((
-        SpatialBundle::default(),
-        Name::new(format!("Tile Layer {}", layer_id)),
-    ));
-    let layer_entity_id = layer_entity.id();
+/// Tileset tilemaps are positioned by their layer
+fn create_tileset_layer_bundle(
+    tiled_map: &TiledMap,
+    texture: TilemapTexture,
+    render_settings: TilemapRenderSettings,
+) -> TilemapBundle {
+    TilemapBundle {
+        grid_size: get_map_grid_size(tiled_map),
+        size: get_map_size(tiled_map),
+        // tiles are looked up through the layer's storage
+        storage: TileStorage::default(),
+        texture,
+        tile_size: TilemapTileSize {
+            x: TILE_WIDTH as f32,
+            y: TILE_HEIGHT as f32,
+        },
+        map_type: TilemapType::Square,
+        render_settings,
+        ..Default::default()
+    }
+}
+
+/// Gets the tileset tilemaps for a layer
+///
+/// Existing tilemaps are reused by tileset name,
+/// tilemaps for tilesets the layer no longer uses are despawned
+fn get_tileset_layers(
+    commands: &mut Commands,
+    layer_entity: Entity,
+    tiled_map: &TiledMap,
+    existing_tilesets: Option<&TiledLayerTilesets>,
+    tilesets: &[&str],
+    render_settings: TilemapRenderSettings,
+) -> TiledLayerTilesets {
+    let mut tileset_layers = TiledLayerTilesets::default();
 
-    let mut shared_tilemap_texture = None;
+    for tileset in tilesets {
+        // validated, every used tileset has a texture
+        let texture = tiled_map.tilemap_textures.get(*tileset).unwrap().clone();
 
-    layer_entity.with_children(|parent| {
-        for x in 0..map_size.x {
-            for y in 0..map_size.y {
-                // Transform TMX coords into bevy coords.
-                let mapped_y = tiled_map.map.height - 1 - y;
+        let bundle = create_tileset_layer_bundle(tiled_map, texture, render_settings);
 
-                let mapped_x = x as i32;
-                let mapped_y = mapped_y as i32;
+        let tilemap = match existing_tilesets.and_then(|existing| existing.get(tileset)) {
+            Some(tilemap) => {
+                commands.entity(tilemap).insert(bundle);
+                tilemap
+            }
+            None => commands
+                .spawn((bundle, Name::new(format!("Tileset {}", tileset))))
+                .set_parent(layer_entity)
+                .id(),
+        };
 
-                let layer_tile = layer.get_tile(mapped_x, mapped_y).unwrap_or_else(|| {
-                    panic!("Tile layer {} missing tile at ({}, {})", layer_id, x, y)
-                });
+        tileset_layers.0.push((tileset.to_string(), tilemap));
+    }
 
-                let tileset = layer_tile.get_tileset();
-                if tileset.tile_width != TILE_WIDTH || tileset.tile_height != TILE_HEIGHT {
-                    panic!("Tileset {} tiles have invalid tile size", tileset.name);
-                }
+    if let Some(existing_tilesets) = existing_tilesets {
+        for (tileset, tilemap) in &existing_tilesets.0 {
+            if tileset_layers.get(tileset).is_none() {
+                debug!("Removing tileset {} from layer", tileset);
+                commands.entity(*tilemap).despawn_recursive();
+            }
+        }
+    }
 
-                if tileset.spacing != 0 {
-                    panic!("Tileset {} tiles have invalid tile spacing", tileset.name);
-                }
+    tileset_layers
+}
 
-                let tilemap_texture = tiled_map
-                    .tilemap_textures
-                    .get(&tileset.name)
-                    .unwrap_or_else(|| panic!("Tile layer {} missing tilemap texture", layer_id));
+/// Tiles have no runtime state, existing tiles just have their texture updated
+///
+/// Tiles that moved to a different tileset are respawned on that tileset's tilemap
+#[allow(clippy::too_many_arguments)]
+fn process_tile_layer(
+    commands: &mut Commands,
+    root: Entity,
+    layer_storage: &mut TiledLayersStorage,
+    tiled_map: &TiledMap,
+    layer_index: usize,
+    flattened_layer: &FlattenedLayer,
+    layer: &tiled::FiniteTileLayer,
+    render_settings: TilemapRenderSettings,
+    queries: &MapLayerQueries,
+) {
+    let layer_id = flattened_layer.layer.id();
+    let color = get_layer_color(flattened_layer);
+    debug!("Processing tile layer {} ({})", layer_index, layer_id);
 
-                if shared_tilemap_texture.is_none() {
-                    shared_tilemap_texture = Some(tilemap_texture.clone());
-                }
-                // TODO: ensure the textures are the same texture
+    let existing_layer = layer_storage
+        .storage
+        .get(&layer_id)
+        .and_then(|layer_entity| {
+            queries
+                .tile_layer_query
+                .get(*layer_entity)
+                .ok()
+                .zip(queries.tilesets_query.get(*layer_entity).ok())
+        });
+    let layer_entity = get_layer_entity(
+        commands,
+        root,
+        layer_storage,
+        layer_id,
+        existing_layer.is_some(),
+        format!("Tile Layer {}", layer_id),
+    );
+
+    let map_size = get_map_size(tiled_map);
+    let mut tiles = Vec::new();
+    let mut tilesets = Vec::new();
+
+    for x in 0..map_size.x {
+        for y in 0..map_size.y {
+            // Transform TMX coords into bevy coords.
+            let (mapped_x, mapped_y) = tiled_map.bounds.get_tiled_pos(&TilePos { x, y });
+
+            // validated, every position has a tile
+            let layer_tile = layer.get_tile(mapped_x, mapped_y).unwrap();
+
+            let tileset = layer_tile.get_tileset().name.as_str();
+            if !tilesets.contains(&tileset) {
+                tilesets.push(tileset);
+            }
 
-                let layer_tile_data =
-                    layer.get_tile_data(mapped_x, mapped_y).unwrap_or_else(|| {
-                        panic!(
-                            "Tile layer {} missing tile data at ({}, {})",
-                            layer_id, x, y
-                        )
-                    });
+            let texture_index = match tiled_map.tilemap_textures.get(tileset).unwrap() {
+                TilemapTexture::Single(_) => layer_tile.id(),
+            };
 
-                if layer_tile_data.flip_h || layer_tile_data.flip_v || layer_tile_data.flip_d {
-                    panic!(
-                        "Tile layer {} has unsupported tile flip at ({}, {})",
-                        layer_id, x, y
-                    )
-                }
+            let flip = get_tile_flip(layer_tile.flip_h, layer_tile.flip_v, layer_tile.flip_d);
 
-                let texture_index = match tilemap_texture {
-                    TilemapTexture::Single(_) => layer_tile.id(),
-                };
+            // validated, tileset tile properties are valid
+            let data = TileData::from_tile(layer_tile.get_tile()).unwrap_or_default();
+            let animation = get_tile_animation(layer_tile.get_tileset(), layer_tile.id());
 
-                let tile_pos = TilePos { x, y };
-                spawn_tile(
-                    parent,
-                    &mut tile_storage,
-                    layer_entity_id,
-                    tile_pos,
-                    texture_index,
-                    true,
-                );
+            tiles.push((
+                TilePos { x, y },
+                tileset,
+                texture_index,
+                flip,
+                data,
+                animation,
+            ));
+        }
+    }
+
+    let tileset_layers = get_tileset_layers(
+        commands,
+        layer_entity,
+        tiled_map,
+        existing_layer.map(|(_, tilesets)| tilesets),
+        &tilesets,
+        render_settings,
+    );
+
+    let mut tile_storage = TileStorage::empty(map_size);
+    let mut new_tiles = Vec::new();
+
+    for (tile_pos, tileset, texture_index, flip, data, animation) in tiles {
+        let tilemap = tileset_layers.get(tileset).unwrap();
+
+        match existing_layer.and_then(|(storage, _)| storage.get(&tile_pos)) {
+            Some(tile_entity)
+                if queries.tile_query.get(tile_entity).ok() == Some(&TilemapId(tilemap)) =>
+            {
+                let mut tile = commands.entity(tile_entity);
+                tile.insert((
+                    TileTextureIndex(texture_index),
+                    flip,
+                    TileColor(color),
+                    Tile(data),
+                ));
+                match animation {
+                    Some(animation) => tile.insert(animation),
+                    None => tile.remove::<TileAnimation>(),
+                };
+                tile_storage.set(&tile_pos, tile_entity);
+            }
+            previous => {
+                // tiles can't be moved between tilemaps
+                if let Some(tile_entity) = previous {
+                    commands.entity(tile_entity).despawn_recursive();
+                }
+                new_tiles.push((tile_pos, tilemap, texture_index, flip, data, animation));
             }
         }
+    }
+
+    commands.entity(layer_entity).with_children(|parent| {
+        for (tile_pos, tilemap, texture_index, flip, data, animation) in new_tiles {
+            spawn_tile(
+                parent,
+                &mut tile_storage,
+                tilemap,
+                tile_pos,
+                texture_index,
+                flip,
+                color,
+                data,
+                true,
+                animation,
+            );
+        }
     });
 
-    layer_entity.insert((
-        TilemapBundle {
-            grid_size,
-            size: map_size,
-            storage: tile_storage,
-            texture: shared_tilemap_texture.unwrap(),
-            tile_size: TilemapTileSize {
-                x: TILE_WIDTH as f32,
-                y: TILE_HEIGHT as f32,
-            },
-            transform: get_tilemap_center_transform(
-                &map_size,
-                &grid_size,
-                &map_type,
-                layer_index as f32,
-            ),
-            map_type,
-            render_settings,
-            ..Default::default()
-        },
+    commands.entity(layer_entity).insert((
+        create_tiled_layer_bundle(
+            tiled_map,
+            layer_index,
+            flattened_layer,
+            tile_storage,
+            tileset_layers,
+        ),
         TiledMapTileLayer,
     ));
-
-    layer_storage
-        .storage
-        .insert(layer_index as u32, layer_entity_id);
 }
 
-// TODO: Objects should be allowed in multiples of the tile size
-// (so that we can have small and large objects as needed)
+/// Infinite tile layers are streamed in around the camera a chunk at a time (see stream_tiled_chunks)
+///
+/// Reloading drops every loaded chunk so they are streamed back in from the new map
 #[allow(clippy::too_many_arguments)]
-fn process_object_layer(
-    parent: &mut ChildBuilder,
+fn process_infinite_tile_layer(
+    commands: &mut Commands,
+    root: Entity,
     layer_storage: &mut TiledLayersStorage,
     tiled_map: &TiledMap,
     layer_index: usize,
-    layer_id: u32,
-    layer: &tiled::ObjectLayer,
-    render_settings: TilemapRenderSettings,
+    flattened_layer: &FlattenedLayer,
+    queries: &MapLayerQueries,
 ) {
-    debug!("Processing object layer {} ({})", layer_index, layer_id);
+    let layer_id = flattened_layer.layer.id();
+    debug!(
+        "Processing infinite tile layer {} ({})",
+        layer_index, layer_id
+    );
 
-    let map_size = TilemapSize {
-        x: tiled_map.map.width,
-        y: tiled_map.map.height,
-    };
+    let existing_chunks = layer_storage
+        .storage
+        .get(&layer_id)
+        .and_then(|layer_entity| queries.chunks_query.get(*layer_entity).ok());
+    let layer_entity = get_layer_entity(
+        commands,
+        root,
+        layer_storage,
+        layer_id,
+        existing_chunks.is_some(),
+        format!("Tile Layer {}", layer_id),
+    );
+
+    for tilemap in existing_chunks
+        .iter()
+        .flat_map(|chunks| chunks.loaded.values().flatten())
+    {
+        commands.entity(*tilemap).despawn_recursive();
+    }
 
-    let grid_size = TilemapGridSize {
-        x: tiled_map.map.tile_width as f32,
-        y: tiled_map.map.tile_height as f32,
-    };
+    commands.entity(layer_entity).insert((
+        create_tiled_layer_bundle(
+            tiled_map,
+            layer_index,
+            flattened_layer,
+            TileStorage::empty(get_map_size(tiled_map)),
+            TiledLayerTilesets::default(),
+        ),
+        TiledLayerChunks::default(),
+        TiledMapTileLayer,
+    ));
+}
 
-    let map_type = match tiled_map.map.orientation {
-        tiled::Orientation::Orthogonal => TilemapType::Square,
-        _ => panic!("Object layer {} must be Orthogonal", layer_id),
+/// Spawns the chunks of infinite tile layers around the MainCamera
+/// and despawns the chunks that are out of range
+fn stream_tiled_chunks(
+    mut commands: Commands,
+    tiled_maps: Res<Assets<TiledMap>>,
+    map_query: Query<(
+        &Handle<TiledMap>,
+        &TiledLayersStorage,
+        &TilemapRenderSettings,
+    )>,
+    mut layer_query: Query<
+        (
+            &mut TiledLayerChunks,
+            &mut TileStorage,
+            &Transform,
+            &TiledLayerColor,
+        ),
+        With<TiledMapTileLayer>,
+    >,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
     };
+    let view = Rect::from_corners(
+        camera_transform.translation.truncate() + projection.area.min,
+        camera_transform.translation.truncate() + projection.area.max,
+    );
+
+    for (map_handle, layer_storage, render_settings) in &map_query {
+        let Some(tiled_map) = tiled_maps.get(map_handle) else {
+            continue;
+        };
+
+        for FlattenedLayer { layer, .. } in get_layers(&tiled_map.map) {
+            let tiled::LayerType::Tiles(tiled::TileLayer::Infinite(tile_layer)) =
+                layer.layer_type()
+            else {
+                continue;
+            };
 
-    let mut tile_storage = TileStorage::empty(map_size);
-    let mut layer_entity = parent.spawn((
-        SpatialBundle::default(),
-        Name::new(format!("Object layer {}", layer_id)),
-    ));
-    let layer_entity_id = layer_entity.id();
+            let Some(layer_entity) = layer_storage.storage.get(&layer.id()) else {
+                continue;
+            };
 
-    let mut shared_tilemap_texture = None;
+            let Ok((mut chunks, mut tile_storage, layer_transform, layer_color)) =
+                layer_query.get_mut(*layer_entity)
+            else {
+                continue;
+            };
 
-    layer_entity.with_children(|parent| {
-        for object in layer.objects() {
-            if object.rotation != 0.0 {
-                panic!(
-                    "Object layer {} may not have rotated object {}",
-                    layer_id,
-                    object.id()
-                );
-            }
+            let (min_chunk, max_chunk) = get_chunk_range(tiled_map, layer_transform, view);
+            let in_range = |(x, y): (i32, i32)| {
+                x >= min_chunk.0 && x <= max_chunk.0 && y >= min_chunk.1 && y <= max_chunk.1
+            };
 
-            let object_tile = object.get_tile().unwrap_or_else(|| {
-                panic!(
-                    "Object layer {} missing tile for object {}",
-                    layer_id,
-                    object.id()
-                )
+            chunks.loaded.retain(|chunk_pos, tilemaps| {
+                let keep = in_range(*chunk_pos);
+                if !keep {
+                    debug!("Unloading chunk {:?} of layer {}", chunk_pos, layer.id());
+                    for tilemap in tilemaps.iter() {
+                        commands.entity(*tilemap).despawn_recursive();
+                    }
+                    for tile_pos in get_chunk_tile_positions(tiled_map, *chunk_pos) {
+                        tile_storage.remove(&tile_pos);
+                    }
+                }
+                keep
             });
 
-            let tileset = object_tile.get_tileset();
-            if tileset.tile_width != TILE_WIDTH || tileset.tile_height != TILE_HEIGHT {
-                panic!(
-                    "Object tileset {} tiles have invalid tile size",
-                    tileset.name
-                );
-            }
+            for x in min_chunk.0..=max_chunk.0 {
+                for y in min_chunk.1..=max_chunk.1 {
+                    if chunks.loaded.contains_key(&(x, y)) {
+                        continue;
+                    }
 
-            if tileset.spacing != 0 {
-                panic!(
-                    "Object tileset {} tiles have invalid tile spacing",
-                    tileset.name
-                );
+                    let Some(chunk) = tile_layer.get_chunk(x, y) else {
+                        continue;
+                    };
+
+                    debug!("Loading chunk {:?} of layer {}", (x, y), layer.id());
+                    let tilemaps = spawn_chunk(
+                        &mut commands,
+                        *layer_entity,
+                        tiled_map,
+                        (x, y),
+                        &chunk,
+                        &mut tile_storage,
+                        layer_color.0,
+                        *render_settings,
+                    );
+                    chunks.loaded.insert((x, y), tilemaps);
+                }
             }
+        }
+    }
+}
 
-            let tilemap_texture = tiled_map
-                .tilemap_textures
-                .get(&tileset.name)
-                .unwrap_or_else(|| {
-                    panic!(
-                        "Object layer {} missing tilemap texture for object {}",
-                        layer_id,
-                        object.id()
-                    )
-                });
-
-            if shared_tilemap_texture.is_none() {
-                shared_tilemap_texture = Some(tilemap_texture.clone());
-            }
-            // TODO: ensure the textures are the same texture
+/// Gets the (inclusive) range of chunks that should be loaded for the given view
+fn get_chunk_range(
+    tiled_map: &TiledMap,
+    layer_transform: &Transform,
+    view: Rect,
+) -> ((i32, i32), (i32, i32)) {
+    let grid_size = get_map_grid_size(tiled_map);
+    let bounds = &tiled_map.bounds;
+    let inverse = layer_transform.compute_matrix().inverse();
+
+    let [min, max] = [view.min, view.max].map(|corner| {
+        let local = inverse.transform_point3(corner.extend(0.0));
+
+        // tiles are centered on their position
+        let x = (local.x / grid_size.x + 0.5).floor() as i32;
+        let y = (local.y / grid_size.y + 0.5).floor() as i32;
+
+        // Transform bevy coords into TMX coords.
+        tiled::ChunkData::tile_to_chunk_pos(bounds.x + x, bounds.y + bounds.height as i32 - 1 - y)
+    });
 
-            let object_tile_data = object.tile_data().unwrap_or_else(|| {
-                panic!(
-                    "Object layer {} missing tile data for object {}",
-                    layer_id,
-                    object.id()
-                )
+    // TMX y is flipped, so the view corners swap
+    (
+        (min.0 - CHUNK_LOAD_MARGIN, max.1 - CHUNK_LOAD_MARGIN),
+        (max.0 + CHUNK_LOAD_MARGIN, min.1 + CHUNK_LOAD_MARGIN),
+    )
+}
+
+/// Gets the positions of every tile in a chunk
+fn get_chunk_tile_positions(
+    tiled_map: &TiledMap,
+    (chunk_x, chunk_y): (i32, i32),
+) -> impl Iterator<Item = TilePos> + '_ {
+    let width = tiled::ChunkData::WIDTH as i32;
+    let height = tiled::ChunkData::HEIGHT as i32;
+
+    (0..width).flat_map(move |x| {
+        (0..height).filter_map(move |y| {
+            tiled_map
+                .bounds
+                .get_tile_pos(chunk_x * width + x, chunk_y * height + y)
+        })
+    })
+}
+
+/// Spawns a tilemap for each tileset used by a chunk of an infinite tile layer
+///
+/// Returns the spawned tilemaps
+#[allow(clippy::too_many_arguments)]
+fn spawn_chunk(
+    commands: &mut Commands,
+    layer_entity: Entity,
+    tiled_map: &TiledMap,
+    chunk_pos: (i32, i32),
+    chunk: &tiled::Chunk,
+    tile_storage: &mut TileStorage,
+    color: Color,
+    render_settings: TilemapRenderSettings,
+) -> Vec<Entity> {
+    let mut tilesets: Vec<(&str, Vec<_>)> = Vec::new();
+
+    for tile_pos in get_chunk_tile_positions(tiled_map, chunk_pos) {
+        let (mapped_x, mapped_y) = tiled_map.bounds.get_tiled_pos(&tile_pos);
+
+        // infinite layers may have gaps
+        let Some(layer_tile) = chunk.get_tile(
+            mapped_x - chunk_pos.0 * tiled::ChunkData::WIDTH as i32,
+            mapped_y - chunk_pos.1 * tiled::ChunkData::HEIGHT as i32,
+        ) else {
+            continue;
+        };
+
+        let tileset = layer_tile.get_tileset().name.as_str();
+        let texture_index = match tiled_map.tilemap_textures.get(tileset).unwrap() {
+            TilemapTexture::Single(_) => layer_tile.id(),
+        };
+        let flip = get_tile_flip(layer_tile.flip_h, layer_tile.flip_v, layer_tile.flip_d);
+
+        // validated, tileset tile properties are valid
+        let data = TileData::from_tile(layer_tile.get_tile()).unwrap_or_default();
+        let animation = get_tile_animation(layer_tile.get_tileset(), layer_tile.id());
+
+        let tile = (tile_pos, texture_index, flip, data, animation);
+        match tilesets.iter_mut().find(|(name, _)| *name == tileset) {
+            Some((_, tiles)) => tiles.push(tile),
+            None => tilesets.push((tileset, vec![tile])),
+        }
+    }
+
+    tilesets
+        .into_iter()
+        .map(|(tileset, tiles)| {
+            // validated, every used tileset has a texture
+            let texture = tiled_map.tilemap_textures.get(tileset).unwrap().clone();
+
+            let mut tilemap = commands.spawn((
+                create_tileset_layer_bundle(tiled_map, texture, render_settings),
+                Name::new(format!(
+                    "Chunk ({}, {}) Tileset {}",
+                    chunk_pos.0, chunk_pos.1, tileset
+                )),
+            ));
+            tilemap.set_parent(layer_entity);
+
+            let tilemap_id = tilemap.id();
+            tilemap.with_children(|parent| {
+                for (tile_pos, texture_index, flip, data, animation) in tiles {
+                    spawn_tile(
+                        parent,
+                        tile_storage,
+                        tilemap_id,
+                        tile_pos,
+                        texture_index,
+                        flip,
+                        color,
+                        data,
+                        true,
+                        animation,
+                    );
+                }
             });
 
-            if object_tile_data.flip_h || object_tile_data.flip_v || object_tile_data.flip_d {
-                panic!(
-                    "Object layer {} has unsupported tile flip for object {}",
-                    layer_id,
-                    object.id()
+            tilemap_id
+        })
+        .collect()
+}
+
+/// Objects may cover multiple tiles, in multiples of the tile size
+///
+/// Objects that already exist keep their runtime state (amount, visibility),
+/// Objects that were removed during play (replaced by Harvesters) are not brought back
+#[allow(clippy::too_many_arguments)]
+fn process_object_layer(
+    commands: &mut Commands,
+    root: Entity,
+    layer_storage: &mut TiledLayersStorage,
+    tiled_map: &TiledMap,
+    layer_index: usize,
+    flattened_layer: &FlattenedLayer,
+    layer: &tiled::ObjectLayer,
+    render_settings: TilemapRenderSettings,
+    queries: &MapLayerQueries,
+) {
+    let layer_id = flattened_layer.layer.id();
+    let color = get_layer_color(flattened_layer);
+    debug!("Processing object layer {} ({})", layer_index, layer_id);
+
+    let existing_layer = layer_storage
+        .storage
+        .get(&layer_id)
+        .and_then(|layer_entity| {
+            queries
+                .object_layer_query
+                .get(*layer_entity)
+                .ok()
+                .zip(queries.tilesets_query.get(*layer_entity).ok())
+        });
+    let layer_entity = get_layer_entity(
+        commands,
+        root,
+        layer_storage,
+        layer_id,
+        existing_layer.is_some(),
+        format!("Object layer {}", layer_id),
+    );
+
+    // multi-tile objects are stored in every cell they cover
+    let mut existing_objects =
+        existing_layer
+            .iter()
+            .flat_map(|(storage, _)| storage.iter().flatten())
+            .filter_map(|object_entity| {
+                queries.object_query.get(*object_entity).ok().map(
+                    |(object, visible, tilemap_id)| {
+                        (
+                            object.get_id(),
+                            (*object_entity, visible.0, *tilemap_id, object.0.clone()),
+                        )
+                    },
                 )
+            })
+            .collect::<HashMap<_, _>>();
+
+    let tilesets = layer.objects().filter_map(|object| object.get_tile()).fold(
+        Vec::new(),
+        |mut tilesets, object_tile| {
+            let tileset = object_tile.get_tileset().name.as_str();
+            if !tilesets.contains(&tileset) {
+                tilesets.push(tileset);
             }
+            tilesets
+        },
+    );
+
+    let tileset_layers = get_tileset_layers(
+        commands,
+        layer_entity,
+        tiled_map,
+        existing_layer.map(|(_, tilesets)| tilesets),
+        &tilesets,
+        render_settings,
+    );
+
+    let mut tile_storage = TileStorage::empty(get_map_size(tiled_map));
+    let mut new_objects = Vec::new();
+
+    for object in layer.objects() {
+        // validated, objects are quarter turn rotated tiles
+        let object_tile = object.get_tile().unwrap();
+        let direction = Direction::from_rotation(object.rotation).unwrap();
+        let flip = get_object_tile_flip(&object_tile, direction);
+
+        let tileset = object_tile.get_tileset().name.as_str();
+        let tilemap = tileset_layers.get(tileset).unwrap();
+
+        let texture_index = match tiled_map.tilemap_textures.get(tileset).unwrap() {
+            TilemapTexture::Single(_) => object_tile.id(),
+        };
+        let animation = get_tile_animation(object_tile.get_tileset(), object_tile.id());
+
+        // validated, objects are tile-aligned and fit on the map
+        let (tile_pos, footprint) = get_object_footprint(&tiled_map.bounds, &object).unwrap();
+
+        if let Some(network) = &queries.network {
+            if footprint
+                .positions(tile_pos)
+                .any(|position| network.contains(&position))
+            {
+                warn!(
+                    "Object {} overlaps a placed Item, not spawning it",
+                    object.id()
+                );
+                continue;
+            }
+        }
 
-            let texture_index = match tilemap_texture {
-                TilemapTexture::Single(_) => object_tile.id(),
-            };
-
-            let (x, y) = match object.shape {
-                tiled::ObjectShape::Rect { width, height } => {
-                    if width != TILE_WIDTH as f32 || height != TILE_HEIGHT as f32 {
-                        panic!("Object {} shape has invalid size", object.id());
-                    }
-                    (object.x / width, object.y / height)
-                }
-                _ => {
-                    panic!(
-                        "Object layer {} has unsupported shape {:?} for object {}",
-                        layer_id,
-                        object.shape,
-                        object.id(),
-                    );
+        if let Some((object_entity, visible, tilemap_id, mut object_data)) =
+            existing_objects.remove(&object.id())
+        {
+            // orientation comes from the map, everything else is runtime state
+            object_data.set_direction(direction);
+
+            if tilemap_id.0 == tilemap {
+                let mut object_commands = commands.entity(object_entity);
+                object_commands.insert((Object(object_data), TileColor(color)));
+                match animation {
+                    Some(animation) => object_commands.insert(animation),
+                    None => object_commands.remove::<TileAnimation>(),
+                };
+                update_object(
+                    commands,
+                    object_entity,
+                    tilemap,
+                    tile_pos,
+                    footprint,
+                    texture_index,
+                    flip,
+                    visible,
+                );
+                for position in footprint.positions(tile_pos) {
+                    tile_storage.set(&position, object_entity);
                 }
-            };
+            } else {
+                // objects can't be moved between tilemaps,
+                // so respawn it with its runtime state
+                commands.entity(object_entity).despawn_recursive();
+                new_objects.push((
+                    tile_pos,
+                    footprint,
+                    tilemap,
+                    texture_index,
+                    flip,
+                    visible,
+                    object_data,
+                    animation,
+                ));
+            }
+            continue;
+        }
 
-            let object_data = ObjectData::new(layer_id, &object)
-                .unwrap_or_else(|err| panic!("Object {} failed to load: {}", object.id(), err,));
+        if !layer_storage.spawned_objects.insert(object.id()) {
+            // removed during play
+            continue;
+        }
 
-            let tile_pos = TilePos {
-                x: x as u32,
+        let object_data = ObjectData::new(layer_id, &object).unwrap();
+        new_objects.push((
+            tile_pos,
+            footprint,
+            tilemap,
+            texture_index,
+            flip,
+            object.visible,
+            object_data,
+            animation,
+        ));
+    }
 
-                // Transform TMX coords into bevy coords.
-                y: tiled_map.map.height - 1 - y as u32,
-            };
+    // removed from the map
+    for (object_entity, ..) in existing_objects.into_values() {
+        commands.entity(object_entity).despawn_recursive();
+    }
 
+    commands.entity(layer_entity).with_children(|parent| {
+        for (tile_pos, footprint, tilemap, texture_index, flip, visible, object_data, animation) in
+            new_objects
+        {
             spawn_object(
                 parent,
                 &mut tile_storage,
-                layer_entity_id,
+                tilemap,
                 tile_pos,
+                footprint,
                 texture_index,
-                object.visible,
+                flip,
+                color,
+                visible,
                 object_data,
+                animation,
             );
         }
     });
 
-    layer_entity.insert((
-        TilemapBundle {
-            grid_size,
-            size: map_size,
-            storage: tile_storage,
-            texture: shared_tilemap_texture.unwrap(),
-            tile_size: TilemapTileSize {
-                x: TILE_WIDTH as f32,
-                y: TILE_HEIGHT as f32,
-            },
-            transform: get_tilemap_center_transform(
-                &map_size,
-                &grid_size,
-                &map_type,
-                layer_index as f32,
-            ),
-            map_type,
-            render_settings,
-            ..Default::default()
-        },
+    commands.entity(layer_entity).insert((
+        create_tiled_layer_bundle(
+            tiled_map,
+            layer_index,
+            flattened_layer,
+            tile_storage,
+            tileset_layers,
+        ),
         TiledMapObjectLayer,
     ));
-
-    layer_storage
-        .storage
-        .insert(layer_index as u32, layer_entity_id);
 }
 
-// TODO: Items should be allowed in multiples of the tile size
-// (so that we can have small and large objects as needed)
-// TODO: this probably needs to move to a post-step
-// where we would have access to the Item images for the layer
+// Each Item type gets its own layer because
+// a tilemap can only use a single (atlas) texture
 #[allow(clippy::too_many_arguments)]
 fn create_item_layer(
-    parent: &mut ChildBuilder,
+    commands: &mut Commands,
+    root: Entity,
     layer_storage: &mut TiledLayersStorage,
     tiled_map: &TiledMap,
+    layer_index: usize,
+    item_type: ItemType,
+    texture: TilemapTexture,
     render_settings: TilemapRenderSettings,
 ) {
-    let layer_index = tiled_map.map.layers().len() + 1;
-    let layer_id = 0; // TODO: use an actual unique id
-    debug!("Creating item layer {} ({})", layer_index, layer_id);
-
-    let map_size = TilemapSize {
-        x: tiled_map.map.width,
-        y: tiled_map.map.height,
-    };
-
-    let grid_size = TilemapGridSize {
-        x: tiled_map.map.tile_width as f32,
-        y: tiled_map.map.tile_height as f32,
-    };
-
-    let map_type = TilemapType::Square;
+    debug!("Creating item layer {} ({})", layer_index, item_type);
 
-    let tile_storage = TileStorage::empty(map_size);
-    let mut layer_entity = parent.spawn((
-        SpatialBundle::default(),
-        Name::new(format!("Item layer {}", layer_id)),
-    ));
-    let layer_entity_id = layer_entity.id();
-
-    layer_entity.insert((
-        TilemapBundle {
-            grid_size,
-            size: map_size,
-            storage: tile_storage,
-            // TODO: with "atlas" off this would be a vec of images I think?
-            // one for each type of item ?
-            //texture: shared_tilemap_texture.unwrap(),
-            tile_size: TilemapTileSize {
-                x: TILE_WIDTH as f32,
-                y: TILE_HEIGHT as f32,
-            },
-            transform: get_tilemap_center_transform(
-                &map_size,
-                &grid_size,
-                &map_type,
-                layer_index as f32,
+    let tile_storage = TileStorage::empty(get_map_size(tiled_map));
+    let layer_entity = commands
+        .spawn((
+            SpatialBundle::default(),
+            Name::new(format!("Item layer {}", item_type)),
+            create_layer_bundle(
+                tiled_map,
+                layer_index,
+                tile_storage,
+                texture,
+                render_settings,
             ),
-            map_type,
-            render_settings,
-            ..Default::default()
-        },
-        TiledMapItemLayer,
-    ));
+            // Items aren't part of the Tiled map, so they are always opaque
+            TiledLayerColor(Color::WHITE),
+            TiledMapItemLayer(item_type),
+        ))
+        .set_parent(root)
+        .id();
 
-    layer_storage
-        .storage
-        .insert(layer_index as u32, layer_entity_id);
+    layer_storage.item_layers.insert(item_type, layer_entity);
 }
@@ -1,8 +1,11 @@
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::prelude::*;
 use bevy_mod_picking::backend::prelude::*;
 
 use crate::get_world_position_from_cursor_position;
-use crate::plugins::{game::camera::MainCamera, tiled::TiledMapObjectLayer};
+use crate::plugins::{
+    game::camera::MainCamera,
+    tiled::{TiledMapItemLayer, TiledMapObjectLayer},
+};
 use crate::tilemap::{get_tile_position, TileMapQuery};
 
 #[derive(Debug, Default)]
@@ -10,21 +13,19 @@ pub struct TiledPickingBackend;
 
 impl Plugin for TiledPickingBackend {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, object_picking.in_set(PickSet::Backend));
+        app.add_systems(
+            PreUpdate,
+            (object_picking, item_picking).in_set(PickSet::Backend),
+        );
     }
 }
 
 fn object_picking(
     pointers: Query<(&PointerId, &PointerLocation)>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<(Entity, &Camera, &GlobalTransform), With<MainCamera>>,
     object_layer_query: Query<TileMapQuery, With<TiledMapObjectLayer>>,
     mut output: EventWriter<PointerHits>,
 ) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-
     let Ok((camera_entity, camera, camera_transform)) = camera_query.get_single() else {
         return;
     };
@@ -43,11 +44,9 @@ fn object_picking(
             pointer_pos -= viewport.min;
         }
 
-        if let Some(world_position) = get_world_position_from_cursor_position(
-            window.cursor_position(),
-            camera,
-            camera_transform,
-        ) {
+        if let Some(world_position) =
+            get_world_position_from_cursor_position(Some(pointer_pos), camera, camera_transform)
+        {
             if let Some(object_position) = get_tile_position(
                 world_position,
                 object_tilemap.size,
@@ -77,3 +76,56 @@ fn object_picking(
         }
     }
 }
+
+fn item_picking(
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    camera_query: Query<(Entity, &Camera, &GlobalTransform), With<MainCamera>>,
+    item_layer_query: Query<TileMapQuery, With<TiledMapItemLayer>>,
+    mut output: EventWriter<PointerHits>,
+) {
+    let Ok((camera_entity, camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+
+    for (pointer_id, pointer_location) in
+        pointers.iter().filter_map(|(pointer, pointer_location)| {
+            Some(*pointer).zip(pointer_location.location().cloned())
+        })
+    {
+        let mut pointer_pos = pointer_location.position;
+        if let Some(viewport) = camera.logical_viewport_rect() {
+            pointer_pos -= viewport.min;
+        }
+
+        let Some(world_position) =
+            get_world_position_from_cursor_position(Some(pointer_pos), camera, camera_transform)
+        else {
+            continue;
+        };
+
+        // items are one per-tile across all of the item layers
+        for item_tilemap in &item_layer_query {
+            let Some(item_position) = get_tile_position(
+                world_position,
+                item_tilemap.size,
+                item_tilemap.grid_size,
+                item_tilemap.r#type,
+                item_tilemap.transform,
+            ) else {
+                continue;
+            };
+
+            if let Some(item_entity) = item_tilemap.storage.get(&item_position) {
+                output.send(PointerHits::new(
+                    pointer_id,
+                    vec![(
+                        item_entity,
+                        HitData::new(camera_entity, 0.0, Some(world_position.extend(0.0)), None),
+                    )],
+                    camera.order as f32,
+                ));
+                break;
+            }
+        }
+    }
+}
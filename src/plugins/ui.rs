@@ -2,6 +2,7 @@ use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_egui::EguiContexts;
 
 use crate::audio::play_oneshot_audio;
+use crate::plugins::loading::AssetTracker;
 use crate::ui::*;
 use crate::AppState;
 
@@ -48,17 +49,26 @@ impl Plugin for UiPlugin {
     }
 }
 
-fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.insert_resource(UiAssets {
+fn load_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut asset_tracker: ResMut<AssetTracker>,
+) {
+    let ui_assets = UiAssets {
         // TODO: we should probably generate this rather than load it
         // (what if the missing image is missing??)
         missing_image: asset_server.load("missing.png"),
         font: asset_server.load(FONT),
         button_hover_sound: asset_server.load("sounds/ui/button-hover.mp3"),
         button_pressed_sound: asset_server.load("sounds/ui/button-click.mp3"),
-    });
+    };
+
+    asset_tracker.track(ui_assets.missing_image.clone());
+    asset_tracker.track(ui_assets.font.clone());
+    asset_tracker.track(ui_assets.button_hover_sound.clone());
+    asset_tracker.track(ui_assets.button_pressed_sound.clone());
 
-    // TODO: need to wait for the assets to be loaded
+    commands.insert_resource(ui_assets);
 }
 
 #[allow(clippy::type_complexity)]
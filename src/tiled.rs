@@ -1,47 +1,845 @@
-pub fn require_object_string_property<'a>(
-    object: &'a tiled::Object,
-    property: impl AsRef<str>,
-) -> anyhow::Result<&'a String> {
-    let Some(property_value) = object.properties.get(property.as_ref()) else {
-        anyhow::bail!(
-            "Object {} missing property '{}'",
-            object.id(),
-            property.as_ref(),
-        );
-    };
+pub mod export;
+pub mod generator;
+pub mod project;
 
-    let tiled::PropertyValue::StringValue(value) = property_value else {
-        anyhow::bail!(
-            "Object {} has invalid property '{}' {:?}",
-            object.id(),
-            property.as_ref(),
-            property_value,
-        );
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use bevy::color::Color;
+use bevy_ecs_tilemap::prelude::*;
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::assets::registry::Registry;
+use crate::data::{direction::Direction, objects::ObjectData, tiles::TileData};
+use crate::tilemap::{TileAnimation, TileAnimationFrame, TileFootprint};
+
+pub const MIN_TILEMAP_WIDTH: u32 = 25;
+pub const MIN_TILEMAP_HEIGHT: u32 = 25;
+pub const TILE_WIDTH: u32 = 32;
+pub const TILE_HEIGHT: u32 = 32;
+
+/// The class of the Object layer exported Items are written to
+///
+/// The game doesn't load Items from maps, so these layers are skipped
+pub const ITEM_LAYER_CLASS: &str = "Items";
+
+/// A single problem with a Tiled map
+///
+/// Tile coordinates are in bevy (bottom-up) coordinates
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TiledMapValidationError {
+    #[error("Map is below the minimum size ({width}x{height})")]
+    InvalidMapSize { width: u32, height: u32 },
+
+    #[error("Map tiles have invalid tile size ({width}x{height})")]
+    InvalidTileSize { width: u32, height: u32 },
+
+    #[error("Map must be Orthogonal")]
+    InvalidOrientation,
+
+    #[error("Tileset {tileset} tiles have invalid tile size")]
+    InvalidTilesetTileSize { tileset: String },
+
+    #[error("Tileset {tileset} tiles have invalid tile spacing")]
+    InvalidTilesetSpacing { tileset: String },
+
+    #[error("Tileset {tileset} tile {tile_id} image has invalid size ({width}x{height})")]
+    InvalidCollectionImage {
+        tileset: String,
+        tile_id: u32,
+        width: i32,
+        height: i32,
+    },
+
+    #[error("Tileset {tileset} tile {tile_id} animation uses tiles outside of the tileset")]
+    InvalidTileAnimation { tileset: String, tile_id: u32 },
+
+    #[error("Tileset {tileset} tile {tile_id} has invalid properties: {reason}")]
+    InvalidTileData {
+        tileset: String,
+        tile_id: u32,
+        reason: String,
+    },
+
+    #[error("Layer {layer_id} has invalid offset")]
+    InvalidLayerOffset { layer_id: u32 },
+
+    #[error("Layer {layer_id} is not a Tile, Object, or Group layer")]
+    UnsupportedLayerType { layer_id: u32 },
+
+    #[error("Map must have exactly one Tile layer (found {count})")]
+    InvalidTileLayerCount { count: usize },
+
+    #[error("Map must have exactly one Object layer (found {count})")]
+    InvalidObjectLayerCount { count: usize },
+
+    #[error("Tile layer {layer_id} missing tile at ({x}, {y})")]
+    MissingTile { layer_id: u32, x: u32, y: u32 },
+
+    #[error("Object layer {layer_id} object {object_id} has unsupported rotation {rotation} (must be a multiple of 90)")]
+    UnsupportedObjectRotation {
+        layer_id: u32,
+        object_id: u32,
+        rotation: f32,
+    },
+
+    #[error("Object layer {layer_id} missing tile for object {object_id}")]
+    MissingObjectTile { layer_id: u32, object_id: u32 },
+
+    #[error("Object layer {layer_id} has unsupported shape for object {object_id}")]
+    InvalidObjectShape { layer_id: u32, object_id: u32 },
+
+    #[error("Object layer {layer_id} object {object_id} has invalid size ({width}x{height})")]
+    InvalidObjectSize {
+        layer_id: u32,
+        object_id: u32,
+        width: f32,
+        height: f32,
+    },
+
+    #[error("Object layer {layer_id} object {object_id} is not on the map grid")]
+    InvalidObjectPosition { layer_id: u32, object_id: u32 },
+
+    #[error("Object layer {layer_id} object {object_id} overlaps object {other_object_id}")]
+    OverlappingObjects {
+        layer_id: u32,
+        object_id: u32,
+        other_object_id: u32,
+    },
+
+    #[error("Object layer {layer_id} object {object_id} failed to load: {reason}")]
+    InvalidObjectData {
+        layer_id: u32,
+        object_id: u32,
+        reason: String,
+    },
+}
+
+/// A Tile or Object layer with its parent Group layers flattened away
+#[derive(Clone)]
+pub struct FlattenedLayer<'map> {
+    pub layer: tiled::Layer<'map>,
+
+    /// The layer opacity multiplied by every parent group's opacity
+    pub opacity: f32,
+
+    /// Hidden if the layer or any parent group is hidden
+    pub visible: bool,
+
+    /// The layer offset plus every parent group's offset
+    pub offset_x: f32,
+    pub offset_y: f32,
+}
+
+/// Gets every non-Group layer of a map in draw order,
+/// recursively flattening Group layers into their children
+///
+/// Exported Item layers are not included
+pub fn get_layers(map: &tiled::Map) -> Vec<FlattenedLayer<'_>> {
+    let mut layers = Vec::new();
+    flatten_layers(map.layers(), 1.0, true, (0.0, 0.0), &mut layers);
+    layers
+}
+
+fn flatten_layers<'map>(
+    layers: impl Iterator<Item = tiled::Layer<'map>>,
+    opacity: f32,
+    visible: bool,
+    offset: (f32, f32),
+    flattened: &mut Vec<FlattenedLayer<'map>>,
+) {
+    for layer in layers {
+        if layer.user_type.as_deref() == Some(ITEM_LAYER_CLASS) {
+            continue;
+        }
+
+        let opacity = opacity * layer.opacity;
+        let visible = visible && layer.visible;
+        let offset = (offset.0 + layer.offset_x, offset.1 + layer.offset_y);
+
+        match layer.layer_type() {
+            tiled::LayerType::Group(group) => {
+                flatten_layers(group.layers(), opacity, visible, offset, flattened)
+            }
+            _ => flattened.push(FlattenedLayer {
+                layer,
+                opacity,
+                visible,
+                offset_x: offset.0,
+                offset_y: offset.1,
+            }),
+        }
+    }
+}
+
+/// Gets the animation of a tileset tile, if it has one
+///
+/// Tilesets are a single image, so animation frame tile ids are texture indices
+pub fn get_tile_animation(tileset: &tiled::Tileset, tile_id: u32) -> Option<TileAnimation> {
+    let frames = tileset
+        .get_tile(tile_id)?
+        .animation
+        .as_ref()?
+        .iter()
+        .map(|frame| TileAnimationFrame {
+            texture_index: frame.tile_id,
+            duration: Duration::from_millis(frame.duration as u64),
+        })
+        .collect();
+
+    TileAnimation::new(tile_id, frames)
+}
+
+/// Gets every tileset used by a map, including the tilesets of Object templates
+///
+/// Tilesets are identified by name, template tilesets that are also used by the map are only included once
+pub fn get_tilesets(map: &tiled::Map) -> Vec<&tiled::Tileset> {
+    let mut tilesets = map
+        .tilesets()
+        .iter()
+        .map(|tileset| tileset.as_ref())
+        .collect::<Vec<_>>();
+
+    for layer in get_layers(map) {
+        let Some(object_layer) = layer.layer.as_object_layer() else {
+            continue;
+        };
+
+        for object in object_layer.objects() {
+            let Some(object_tile) = object.get_tile() else {
+                continue;
+            };
+
+            let tileset = object_tile.get_tileset();
+            if !tilesets.iter().any(|other| other.name == tileset.name) {
+                tilesets.push(tileset);
+            }
+        }
+    }
+
+    tilesets
+}
+
+/// The area of a Tiled map the game plays on, in Tiled (top-down) tile coordinates
+///
+/// Finite maps use their whole size,
+/// infinite maps use the bounds of the chunks in their tile layers
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TiledMapBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TiledMapBounds {
+    pub fn new(map: &tiled::Map) -> Self {
+        if !map.infinite() {
+            return Self {
+                x: 0,
+                y: 0,
+                width: map.width,
+                height: map.height,
+            };
+        }
+
+        let chunks = get_layers(map)
+            .into_iter()
+            .filter_map(|layer| match layer.layer.layer_type() {
+                tiled::LayerType::Tiles(tiled::TileLayer::Infinite(layer)) => Some(layer),
+                _ => None,
+            })
+            .flat_map(|layer| layer.chunks().map(|(position, _)| position))
+            .collect::<Vec<_>>();
+
+        let (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) = (
+            chunks.iter().map(|(x, _)| *x).min(),
+            chunks.iter().map(|(_, y)| *y).min(),
+            chunks.iter().map(|(x, _)| *x).max(),
+            chunks.iter().map(|(_, y)| *y).max(),
+        ) else {
+            return Self::default();
+        };
+
+        Self {
+            x: min_x * tiled::ChunkData::WIDTH as i32,
+            y: min_y * tiled::ChunkData::HEIGHT as i32,
+            width: (max_x - min_x + 1) as u32 * tiled::ChunkData::WIDTH,
+            height: (max_y - min_y + 1) as u32 * tiled::ChunkData::HEIGHT,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> TilemapSize {
+        TilemapSize {
+            x: self.width,
+            y: self.height,
+        }
+    }
+
+    /// Transforms Tiled (top-down) tile coordinates into a bevy TilePos
+    ///
+    /// Returns None if the coordinates are outside of the bounds
+    #[inline]
+    pub fn get_tile_pos(&self, x: i32, y: i32) -> Option<TilePos> {
+        let x = u32::try_from(x - self.x).ok()?;
+        let y = u32::try_from(y - self.y).ok()?;
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(TilePos {
+            x,
+            y: self.height - 1 - y,
+        })
+    }
+
+    /// Transforms a bevy TilePos into Tiled (top-down) tile coordinates
+    #[inline]
+    pub fn get_tiled_pos(&self, position: &TilePos) -> (i32, i32) {
+        (
+            self.x + position.x as i32,
+            self.y + (self.height - 1 - position.y) as i32,
+        )
+    }
+}
+
+/// Checks a Tiled map against everything the game supports
+///
+/// Returns every problem found rather than stopping at the first one
+pub fn validate_map(map: &tiled::Map, registry: &Registry) -> Vec<TiledMapValidationError> {
+    let mut errors = Vec::new();
+
+    let bounds = TiledMapBounds::new(map);
+    if bounds.width < MIN_TILEMAP_WIDTH || bounds.height < MIN_TILEMAP_HEIGHT {
+        errors.push(TiledMapValidationError::InvalidMapSize {
+            width: bounds.width,
+            height: bounds.height,
+        });
+    }
+
+    if map.tile_width != TILE_WIDTH || map.tile_height != TILE_HEIGHT {
+        errors.push(TiledMapValidationError::InvalidTileSize {
+            width: map.tile_width,
+            height: map.tile_height,
+        });
+    }
+
+    if map.orientation != tiled::Orientation::Orthogonal {
+        errors.push(TiledMapValidationError::InvalidOrientation);
+    }
+
+    for tileset in get_tilesets(map) {
+        validate_tileset(tileset, &mut errors);
+    }
+
+    // the game plays on a single Tile layer and a single Object layer,
+    // Group layers can't be used to add more
+    let mut tile_layers = 0;
+    let mut object_layers = 0;
+    for FlattenedLayer {
+        layer,
+        offset_x,
+        offset_y,
+        ..
+    } in get_layers(map)
+    {
+        if offset_x != 0.0 || offset_y != 0.0 {
+            errors.push(TiledMapValidationError::InvalidLayerOffset {
+                layer_id: layer.id(),
+            });
+        }
+
+        match layer.layer_type() {
+            tiled::LayerType::Tiles(tile_layer) => {
+                tile_layers += 1;
+                validate_tile_layer(&bounds, layer.id(), &tile_layer, &mut errors)
+            }
+            tiled::LayerType::Objects(object_layer) => {
+                object_layers += 1;
+                validate_object_layer(&bounds, registry, layer.id(), &object_layer, &mut errors)
+            }
+            _ => errors.push(TiledMapValidationError::UnsupportedLayerType {
+                layer_id: layer.id(),
+            }),
+        }
+    }
+
+    if tile_layers != 1 {
+        errors.push(TiledMapValidationError::InvalidTileLayerCount { count: tile_layers });
+    }
+
+    if object_layers != 1 {
+        errors.push(TiledMapValidationError::InvalidObjectLayerCount {
+            count: object_layers,
+        });
+    }
+
+    errors
+}
+
+fn validate_tileset(tileset: &tiled::Tileset, errors: &mut Vec<TiledMapValidationError>) {
+    if tileset.tile_width != TILE_WIDTH || tileset.tile_height != TILE_HEIGHT {
+        errors.push(TiledMapValidationError::InvalidTilesetTileSize {
+            tileset: tileset.name.clone(),
+        });
+    }
+
+    if tileset.spacing != 0 {
+        errors.push(TiledMapValidationError::InvalidTilesetSpacing {
+            tileset: tileset.name.clone(),
+        });
+    }
+
+    for (tile_id, tile) in tileset.tiles() {
+        // image collection tiles are packed into an atlas of tile sized cells
+        if let Some(image) = &tile.image {
+            if image.width != TILE_WIDTH as i32 || image.height != TILE_HEIGHT as i32 {
+                errors.push(TiledMapValidationError::InvalidCollectionImage {
+                    tileset: tileset.name.clone(),
+                    tile_id,
+                    width: image.width,
+                    height: image.height,
+                });
+            }
+        }
+
+        if let Some(frames) = &tile.animation {
+            if frames
+                .iter()
+                .any(|frame| !is_tileset_tile(tileset, frame.tile_id))
+            {
+                errors.push(TiledMapValidationError::InvalidTileAnimation {
+                    tileset: tileset.name.clone(),
+                    tile_id,
+                });
+            }
+        }
+
+        if let Err(err) = TileData::new(&tile.properties) {
+            errors.push(TiledMapValidationError::InvalidTileData {
+                tileset: tileset.name.clone(),
+                tile_id,
+                reason: err.to_string(),
+            });
+        }
+    }
+}
+
+/// Image collection tile ids may have gaps, atlas tile ids never do
+fn is_tileset_tile(tileset: &tiled::Tileset, tile_id: u32) -> bool {
+    match tileset.image {
+        Some(_) => tile_id < tileset.tilecount,
+        None => tileset
+            .get_tile(tile_id)
+            .is_some_and(|tile| tile.image.is_some()),
+    }
+}
+
+/// Infinite layers may leave gaps, nothing can be placed there
+fn validate_tile_layer(
+    bounds: &TiledMapBounds,
+    layer_id: u32,
+    layer: &tiled::TileLayer,
+    errors: &mut Vec<TiledMapValidationError>,
+) {
+    let tiled::TileLayer::Finite(layer) = layer else {
+        return;
     };
 
-    Ok(value)
+    for x in 0..bounds.width {
+        for y in 0..bounds.height {
+            // Transform TMX coords into bevy coords.
+            let (mapped_x, mapped_y) = bounds.get_tiled_pos(&TilePos { x, y });
+
+            if layer.get_tile_data(mapped_x, mapped_y).is_none() {
+                errors.push(TiledMapValidationError::MissingTile { layer_id, x, y });
+            }
+        }
+    }
+}
+
+fn validate_object_layer(
+    bounds: &TiledMapBounds,
+    registry: &Registry,
+    layer_id: u32,
+    layer: &tiled::ObjectLayer,
+    errors: &mut Vec<TiledMapValidationError>,
+) {
+    let mut covered = HashMap::new();
+
+    for object in layer.objects() {
+        let object_id = object.id();
+
+        let rotation_supported = Direction::from_rotation(object.rotation).is_some();
+        if !rotation_supported {
+            errors.push(TiledMapValidationError::UnsupportedObjectRotation {
+                layer_id,
+                object_id,
+                rotation: object.rotation,
+            });
+        }
+
+        if object.tile_data().is_none() {
+            errors.push(TiledMapValidationError::MissingObjectTile {
+                layer_id,
+                object_id,
+            });
+        }
+
+        match object.shape {
+            tiled::ObjectShape::Rect { width, height } => {
+                if !is_tile_multiple(width, TILE_WIDTH) || !is_tile_multiple(height, TILE_HEIGHT) {
+                    errors.push(TiledMapValidationError::InvalidObjectSize {
+                        layer_id,
+                        object_id,
+                        width,
+                        height,
+                    });
+                } else if let Some((position, footprint)) = get_object_footprint(bounds, &object) {
+                    for position in footprint.positions(position) {
+                        if let Some(other_object_id) = covered.insert(position, object_id) {
+                            errors.push(TiledMapValidationError::OverlappingObjects {
+                                layer_id,
+                                object_id,
+                                other_object_id,
+                            });
+                            break;
+                        }
+                    }
+                } else if rotation_supported {
+                    errors.push(TiledMapValidationError::InvalidObjectPosition {
+                        layer_id,
+                        object_id,
+                    });
+                }
+            }
+            _ => errors.push(TiledMapValidationError::InvalidObjectShape {
+                layer_id,
+                object_id,
+            }),
+        }
+
+        match ObjectData::new(layer_id, &object) {
+            Ok(ObjectData::Resources { r#type, .. }) => {
+                if registry.get_resource(r#type).is_none() {
+                    errors.push(TiledMapValidationError::InvalidObjectData {
+                        layer_id,
+                        object_id,
+                        reason: format!(
+                            "Resource {} has unknown ResourceType {}",
+                            object_id, r#type
+                        ),
+                    });
+                }
+            }
+            Err(err) => errors.push(TiledMapValidationError::InvalidObjectData {
+                layer_id,
+                object_id,
+                reason: err.to_string(),
+            }),
+        }
+    }
 }
 
-pub fn require_object_int_property(
+#[inline]
+fn is_tile_multiple(size: f32, tile_size: u32) -> bool {
+    size >= tile_size as f32 && size % tile_size as f32 == 0.0
+}
+
+/// Gets the bottom-left tile position (in bevy coordinates)
+/// and the footprint of a tile Object
+///
+/// Tile Objects are anchored at their bottom-left corner in Tiled
+/// and rotate clockwise around it
+///
+/// Returns None if the Object isn't a tile-aligned, quarter turn rotated Rect that fits on the map
+pub fn get_object_footprint(
+    bounds: &TiledMapBounds,
     object: &tiled::Object,
+) -> Option<(TilePos, TileFootprint)> {
+    let tiled::ObjectShape::Rect { width, height } = object.shape else {
+        return None;
+    };
+
+    if !is_tile_multiple(width, TILE_WIDTH) || !is_tile_multiple(height, TILE_HEIGHT) {
+        return None;
+    }
+
+    // the covered area in Tiled (top-down) coordinates, relative to the bounds
+    let x = object.x - (bounds.x * TILE_WIDTH as i32) as f32;
+    let y = object.y - (bounds.y * TILE_HEIGHT as i32) as f32;
+    let (left, bottom, width, height) = match Direction::from_rotation(object.rotation)? {
+        Direction::East => (x, y, width, height),
+        Direction::South => (x, y + width, height, width),
+        Direction::West => (x - width, y + height, width, height),
+        Direction::North => (x - height, y, height, width),
+    };
+
+    if left < 0.0 || left % TILE_WIDTH as f32 != 0.0 || bottom % TILE_HEIGHT as f32 != 0.0 {
+        return None;
+    }
+
+    let footprint = TileFootprint::new(width as u32 / TILE_WIDTH, height as u32 / TILE_HEIGHT);
+    let x = left as u32 / TILE_WIDTH;
+    let bottom = bottom as i64 / TILE_HEIGHT as i64;
+    if bottom < footprint.height as i64 || bottom > bounds.height as i64 {
+        return None;
+    }
+
+    // Transform TMX coords into bevy coords.
+    let position = TilePos {
+        x,
+        y: bounds.height - bottom as u32,
+    };
+
+    footprint
+        .fits(position, &bounds.size())
+        .then_some((position, footprint))
+}
+
+/// Maps Tiled tile flip flags to a TileFlip
+///
+/// Tiled flips diagonally first, then horizontally, then vertically,
+/// which matches how bevy_ecs_tilemap applies TileFlip
+#[inline]
+pub fn get_tile_flip(flip_h: bool, flip_v: bool, flip_d: bool) -> TileFlip {
+    TileFlip {
+        x: flip_h,
+        y: flip_v,
+        d: flip_d,
+    }
+}
+
+/// Combines the tile flip flags of a tile Object with its rotation
+///
+/// Rotations are applied after the flips, as they are in Tiled
+pub fn get_object_tile_flip(object_tile: &tiled::ObjectTileData, direction: Direction) -> TileFlip {
+    let flip = get_tile_flip(object_tile.flip_h, object_tile.flip_v, object_tile.flip_d);
+    get_matrix_flip(multiply_flip_matrices(
+        get_flip_matrix(direction.tile_flip()),
+        get_flip_matrix(flip),
+    ))
+}
+
+/// Splits the TileFlip of an Object back into the tile flip flags of a tile Object
+/// with the rotation of the given direction
+///
+/// This is the inverse of get_object_tile_flip
+pub fn get_object_tile_flip_flags(flip: TileFlip, direction: Direction) -> TileFlip {
+    // quarter turns are undone by turning the other way
+    let undo_direction = match direction {
+        Direction::North => Direction::South,
+        Direction::South => Direction::North,
+        direction => direction,
+    };
+
+    get_matrix_flip(multiply_flip_matrices(
+        get_flip_matrix(undo_direction.tile_flip()),
+        get_flip_matrix(flip),
+    ))
+}
+
+type FlipMatrix = [[i32; 2]; 2];
+
+const FLIP_X: FlipMatrix = [[-1, 0], [0, 1]];
+const FLIP_Y: FlipMatrix = [[1, 0], [0, -1]];
+const FLIP_D: FlipMatrix = [[0, 1], [1, 0]];
+
+/// Any combination of quarter turns and flips can be expressed as a TileFlip
+fn get_matrix_flip(matrix: FlipMatrix) -> TileFlip {
+    let d = matrix[0][0] == 0;
+    let matrix = if d {
+        multiply_flip_matrices(matrix, FLIP_D)
+    } else {
+        matrix
+    };
+
+    TileFlip {
+        x: matrix[0][0] == -1,
+        y: matrix[1][1] == -1,
+        d,
+    }
+}
+
+fn get_flip_matrix(flip: TileFlip) -> FlipMatrix {
+    let mut matrix = [[1, 0], [0, 1]];
+    if flip.d {
+        matrix = multiply_flip_matrices(FLIP_D, matrix);
+    }
+    if flip.x {
+        matrix = multiply_flip_matrices(FLIP_X, matrix);
+    }
+    if flip.y {
+        matrix = multiply_flip_matrices(FLIP_Y, matrix);
+    }
+    matrix
+}
+
+fn multiply_flip_matrices(a: FlipMatrix, b: FlipMatrix) -> FlipMatrix {
+    let mut matrix = [[0; 2]; 2];
+    for (row, values) in matrix.iter_mut().enumerate() {
+        for (column, value) in values.iter_mut().enumerate() {
+            *value = a[row][0] * b[0][column] + a[row][1] * b[1][column];
+        }
+    }
+    matrix
+}
+
+/// A value that can be decoded from a Tiled custom property
+pub trait FromTiledProperty: Sized {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self>;
+}
+
+/// A Tiled custom class that can be decoded from its member properties
+///
+/// Only members that were set in Tiled are saved,
+/// so unset members have to fall back to the class defaults (see project::get_property_types)
+pub trait FromTiledClass: Sized {
+    /// The Tiled class name
+    const CLASS: &'static str;
+
+    fn from_properties(properties: &tiled::Properties) -> anyhow::Result<Self>;
+}
+
+/// A reference to another Object on the map
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectReference(pub u32);
+
+impl FromTiledProperty for bool {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        match value {
+            tiled::PropertyValue::BoolValue(value) => Ok(*value),
+            _ => anyhow::bail!("expected bool, got {:?}", value),
+        }
+    }
+}
+
+impl FromTiledProperty for i32 {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        match value {
+            tiled::PropertyValue::IntValue(value) => Ok(*value),
+            _ => anyhow::bail!("expected int, got {:?}", value),
+        }
+    }
+}
+
+impl FromTiledProperty for f32 {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        match value {
+            tiled::PropertyValue::FloatValue(value) => Ok(*value),
+            _ => anyhow::bail!("expected float, got {:?}", value),
+        }
+    }
+}
+
+impl FromTiledProperty for String {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        match value {
+            tiled::PropertyValue::StringValue(value) => Ok(value.clone()),
+            _ => anyhow::bail!("expected string, got {:?}", value),
+        }
+    }
+}
+
+impl FromTiledProperty for Color {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        match value {
+            tiled::PropertyValue::ColorValue(color) => Ok(Color::srgba_u8(
+                color.red,
+                color.green,
+                color.blue,
+                color.alpha,
+            )),
+            _ => anyhow::bail!("expected color, got {:?}", value),
+        }
+    }
+}
+
+impl FromTiledProperty for ObjectReference {
+    fn from_property(value: &tiled::PropertyValue) -> anyhow::Result<Self> {
+        match value {
+            tiled::PropertyValue::ObjectValue(0) => anyhow::bail!("object reference is not set"),
+            tiled::PropertyValue::ObjectValue(object_id) => Ok(Self(*object_id)),
+            _ => anyhow::bail!("expected object, got {:?}", value),
+        }
+    }
+}
+
+/// Decodes a Tiled enum property
+///
+/// Tiled enums are stored either as their value name or as their index into the values
+pub fn get_enum_property_value<T>(value: &tiled::PropertyValue) -> anyhow::Result<T>
+where
+    T: FromStr + IntoEnumIterator,
+{
+    match value {
+        tiled::PropertyValue::StringValue(name) => {
+            T::from_str(name).map_err(|_| anyhow::anyhow!("unknown enum value {}", name))
+        }
+        tiled::PropertyValue::IntValue(index) => usize::try_from(*index)
+            .ok()
+            .and_then(|index| T::iter().nth(index))
+            .ok_or_else(|| anyhow::anyhow!("unknown enum index {}", index)),
+        _ => anyhow::bail!("expected enum, got {:?}", value),
+    }
+}
+
+/// Gets an optional property
+pub fn get_property<T: FromTiledProperty>(
+    properties: &tiled::Properties,
     property: impl AsRef<str>,
-) -> anyhow::Result<i32> {
-    let Some(property_value) = object.properties.get(property.as_ref()) else {
+) -> anyhow::Result<Option<T>> {
+    let Some(value) = properties.get(property.as_ref()) else {
+        return Ok(None);
+    };
+
+    T::from_property(value)
+        .map(Some)
+        .map_err(|err| anyhow::anyhow!("has invalid property '{}': {}", property.as_ref(), err))
+}
+
+pub fn require_property<T: FromTiledProperty>(
+    properties: &tiled::Properties,
+    property: impl AsRef<str>,
+) -> anyhow::Result<T> {
+    get_property(properties, property.as_ref())?
+        .ok_or_else(|| anyhow::anyhow!("missing property '{}'", property.as_ref()))
+}
+
+/// Gets a required nested class property
+pub fn require_class_property<T: FromTiledClass>(
+    properties: &tiled::Properties,
+    property: impl AsRef<str>,
+) -> anyhow::Result<T> {
+    let Some(value) = properties.get(property.as_ref()) else {
+        anyhow::bail!("missing property '{}'", property.as_ref());
+    };
+
+    let tiled::PropertyValue::ClassValue {
+        property_type,
+        properties,
+    } = value
+    else {
         anyhow::bail!(
-            "Object {} missing property '{}'",
-            object.id(),
+            "has invalid property '{}': expected class {}, got {:?}",
             property.as_ref(),
+            T::CLASS,
+            value,
         );
     };
 
-    let tiled::PropertyValue::IntValue(value) = property_value else {
+    if property_type != T::CLASS {
         anyhow::bail!(
-            "Object {} has invalid property '{}' {:?}",
-            object.id(),
+            "has invalid property '{}': expected class {}, got {}",
             property.as_ref(),
-            property_value,
+            T::CLASS,
+            property_type,
         );
-    };
+    }
 
-    Ok(*value)
+    T::from_properties(properties)
+        .map_err(|err| anyhow::anyhow!("has invalid property '{}': {}", property.as_ref(), err))
 }
@@ -0,0 +1,737 @@
+//! Writing the game world back out as TMX
+//!
+//! Exported maps keep the layers and tilesets of the map they were loaded from,
+//! with the Tiles, Objects, and Items replaced by what is in the game world.
+//! Group layers are flattened and infinite maps are exported as finite maps
+//! covering the played area
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use bevy_ecs_tilemap::prelude::*;
+use strum::IntoEnumIterator;
+
+use super::{
+    get_layers, get_object_footprint, get_object_tile_flip, get_object_tile_flip_flags,
+    get_tile_flip, get_tilesets, FlattenedLayer, TiledMapBounds, ITEM_LAYER_CLASS, TILE_HEIGHT,
+    TILE_WIDTH,
+};
+use crate::assets::registry::Registry;
+use crate::data::{
+    direction::Direction,
+    items::ItemType,
+    objects::{ObjectData, AMOUNT_PROPERTY, RESOURCE_TYPE_PROPERTY},
+};
+use crate::tilemap::TileFootprint;
+
+const FLIP_H_FLAG: u32 = 0x80000000;
+const FLIP_V_FLAG: u32 = 0x40000000;
+const FLIP_D_FLAG: u32 = 0x20000000;
+
+/// A Tile in the game world
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedTile {
+    pub position: TilePos,
+    pub tileset: String,
+    pub tile_id: u32,
+    pub flip: TileFlip,
+}
+
+/// An Object in the game world
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedObject {
+    /// The bottom-left tile the Object covers
+    pub position: TilePos,
+    pub footprint: TileFootprint,
+    pub tileset: String,
+    pub tile_id: u32,
+
+    /// The flip of the Object tile, including its rotation
+    pub flip: TileFlip,
+    pub visible: bool,
+    pub data: ObjectData,
+}
+
+/// An Item in the game world
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedItem {
+    pub r#type: ItemType,
+
+    /// The bottom-left tile the Item covers
+    pub position: TilePos,
+    pub footprint: TileFootprint,
+
+    /// Sprite index into the Item sprite sheet
+    pub sprite_index: u32,
+}
+
+/// The contents of the game world, in bevy (bottom-up) tile coordinates
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExportedWorld {
+    /// Tiles by Tiled layer id
+    pub tiles: HashMap<u32, Vec<ExportedTile>>,
+
+    /// Objects by Tiled layer id
+    pub objects: HashMap<u32, Vec<ExportedObject>>,
+
+    pub items: Vec<ExportedItem>,
+}
+
+impl ExportedWorld {
+    /// Gets the world as it is spawned from a map
+    ///
+    /// Anything the game would refuse to spawn is left out,
+    /// maps are expected to have been validated
+    pub fn from_map(map: &tiled::Map) -> Self {
+        let bounds = TiledMapBounds::new(map);
+        let mut world = Self::default();
+
+        for FlattenedLayer { layer, .. } in get_layers(map) {
+            let layer_id = layer.id();
+            match layer.layer_type() {
+                tiled::LayerType::Tiles(tile_layer) => {
+                    world
+                        .tiles
+                        .insert(layer_id, get_layer_tiles(&bounds, &tile_layer));
+                }
+                tiled::LayerType::Objects(object_layer) => {
+                    world.objects.insert(
+                        layer_id,
+                        get_layer_objects(&bounds, layer_id, &object_layer),
+                    );
+                }
+                _ => (),
+            }
+        }
+
+        world
+    }
+}
+
+/// Gets every tile of a Tile layer that is within the bounds
+pub fn get_layer_tiles(bounds: &TiledMapBounds, layer: &tiled::TileLayer) -> Vec<ExportedTile> {
+    let mut tiles = Vec::new();
+    for x in 0..bounds.width {
+        for y in 0..bounds.height {
+            let position = TilePos { x, y };
+            let (tiled_x, tiled_y) = bounds.get_tiled_pos(&position);
+            let Some(layer_tile) = layer.get_tile(tiled_x, tiled_y) else {
+                continue;
+            };
+
+            tiles.push(ExportedTile {
+                position,
+                tileset: layer_tile.get_tileset().name.clone(),
+                tile_id: layer_tile.id(),
+                flip: get_tile_flip(layer_tile.flip_h, layer_tile.flip_v, layer_tile.flip_d),
+            });
+        }
+    }
+    tiles
+}
+
+fn get_layer_objects(
+    bounds: &TiledMapBounds,
+    layer_id: u32,
+    layer: &tiled::ObjectLayer,
+) -> Vec<ExportedObject> {
+    layer
+        .objects()
+        .filter_map(|object| {
+            let object_tile = object.get_tile()?;
+            let direction = Direction::from_rotation(object.rotation)?;
+            let (position, footprint) = get_object_footprint(bounds, &object)?;
+            let data = ObjectData::new(layer_id, &object).ok()?;
+
+            Some(ExportedObject {
+                position,
+                footprint,
+                tileset: object_tile.get_tileset().name.clone(),
+                tile_id: object_tile.id(),
+                flip: get_object_tile_flip(&object_tile, direction),
+                visible: object.visible,
+                data,
+            })
+        })
+        .collect()
+}
+
+/// Gets the paths of the external tilesets a map was loaded with, by tileset name
+///
+/// Embedded tilesets are not in the cache
+pub fn get_tileset_sources(cache: &tiled::DefaultResourceCache) -> HashMap<String, PathBuf> {
+    cache
+        .tilesets
+        .iter()
+        .map(|(path, tileset)| (tileset.name.clone(), path.clone()))
+        .collect()
+}
+
+/// Writes a game world out as TMX on top of the map it was loaded from
+pub struct MapExporter<'a> {
+    pub map: &'a tiled::Map,
+
+    /// External tileset paths by tileset name, see get_tileset_sources()
+    ///
+    /// Tilesets without a path are embedded in the exported map
+    pub tileset_sources: &'a HashMap<String, PathBuf>,
+
+    /// Item sprite sheets are embedded as tilesets
+    pub registry: &'a Registry,
+
+    /// The path from the directory of the exported map
+    /// to the directory the map's paths are relative to
+    pub asset_dir: &'a Path,
+}
+
+/// Where a tileset's tiles start in the exported map
+pub(super) struct ExportedTileset {
+    pub name: String,
+    pub first_gid: u32,
+}
+
+impl<'a> MapExporter<'a> {
+    pub fn export(&self, world: &ExportedWorld) -> String {
+        let mut tmx = String::new();
+        // writing to a String can't fail
+        self.write_tmx(&mut tmx, world).unwrap();
+        tmx
+    }
+
+    fn write_tmx(&self, tmx: &mut String, world: &ExportedWorld) -> std::fmt::Result {
+        let bounds = TiledMapBounds::new(self.map);
+        let layers = get_layers(self.map);
+
+        let item_layer_id = layers
+            .iter()
+            .map(|layer| layer.layer.id())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let item_object_id = world
+            .objects
+            .values()
+            .flatten()
+            .map(|object| object.data.get_id())
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        write_map_start(
+            tmx,
+            &bounds,
+            item_layer_id + 1,
+            item_object_id + world.items.len() as u32,
+        )?;
+        write_properties(tmx, 1, &self.map.properties)?;
+
+        let mut tilesets = Vec::new();
+        let mut next_gid = 1;
+        for tileset in get_tilesets(self.map) {
+            match self.tileset_sources.get(&tileset.name) {
+                Some(source) => write_tileset_source(tmx, next_gid, &self.get_path(source))?,
+                None => self.write_tileset(tmx, next_gid, tileset)?,
+            }
+
+            tilesets.push(ExportedTileset {
+                name: tileset.name.clone(),
+                first_gid: next_gid,
+            });
+            next_gid += get_gid_count(tileset);
+        }
+
+        let item_types = ItemType::iter()
+            .filter(|item_type| world.items.iter().any(|item| item.r#type == *item_type))
+            .collect::<Vec<_>>();
+        let mut item_tilesets = HashMap::new();
+        for item_type in item_types {
+            let sprite = &self.registry.get_item(item_type).sprite;
+            writeln!(
+                tmx,
+                r#" <tileset firstgid="{}" name="{}" tilewidth="{}" tileheight="{}" tilecount="{}" columns="{}">"#,
+                next_gid,
+                item_type,
+                TILE_WIDTH,
+                TILE_HEIGHT,
+                sprite.columns * sprite.rows,
+                sprite.columns
+            )?;
+            writeln!(
+                tmx,
+                r#"  <image source="{}" width="{}" height="{}"/>"#,
+                escape(&self.get_path(Path::new(&sprite.image))),
+                sprite.columns * TILE_WIDTH,
+                sprite.rows * TILE_HEIGHT
+            )?;
+            writeln!(tmx, " </tileset>")?;
+
+            item_tilesets.insert(item_type, next_gid);
+            next_gid += sprite.columns * sprite.rows;
+        }
+
+        for flattened_layer in &layers {
+            let layer_id = flattened_layer.layer.id();
+            match flattened_layer.layer.layer_type() {
+                tiled::LayerType::Tiles(_) => self.write_tile_layer(
+                    tmx,
+                    &bounds,
+                    flattened_layer,
+                    &tilesets,
+                    world
+                        .tiles
+                        .get(&layer_id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                )?,
+                tiled::LayerType::Objects(_) => self.write_object_layer(
+                    tmx,
+                    &bounds,
+                    flattened_layer,
+                    &tilesets,
+                    world
+                        .objects
+                        .get(&layer_id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                )?,
+                _ => (),
+            }
+        }
+
+        if !world.items.is_empty() {
+            writeln!(
+                tmx,
+                r#" <objectgroup id="{}" name="Items" class="{}">"#,
+                item_layer_id, ITEM_LAYER_CLASS
+            )?;
+            for (idx, item) in world.items.iter().enumerate() {
+                let (x, y) = get_object_position(&bounds, item.position);
+                writeln!(
+                    tmx,
+                    r#"  <object id="{}" type="{}" gid="{}" x="{}" y="{}" width="{}" height="{}"/>"#,
+                    item_object_id + idx as u32,
+                    item.r#type,
+                    item_tilesets[&item.r#type] + item.sprite_index,
+                    x,
+                    y,
+                    item.footprint.width * TILE_WIDTH,
+                    item.footprint.height * TILE_HEIGHT
+                )?;
+            }
+            writeln!(tmx, " </objectgroup>")?;
+        }
+
+        writeln!(tmx, "</map>")
+    }
+
+    /// Paths are written relative to the exported map
+    fn get_path(&self, path: &Path) -> String {
+        // Tiled always uses forward slashes
+        self.asset_dir
+            .join(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn write_tileset(
+        &self,
+        tmx: &mut String,
+        first_gid: u32,
+        tileset: &tiled::Tileset,
+    ) -> std::fmt::Result {
+        write!(
+            tmx,
+            r#" <tileset firstgid="{}" name="{}""#,
+            first_gid,
+            escape(&tileset.name)
+        )?;
+        if let Some(user_type) = &tileset.user_type {
+            write!(tmx, r#" class="{}""#, escape(user_type))?;
+        }
+        writeln!(
+            tmx,
+            r#" tilewidth="{}" tileheight="{}" spacing="{}" margin="{}" tilecount="{}" columns="{}">"#,
+            tileset.tile_width,
+            tileset.tile_height,
+            tileset.spacing,
+            tileset.margin,
+            tileset.tilecount,
+            tileset.columns
+        )?;
+
+        if tileset.offset_x != 0 || tileset.offset_y != 0 {
+            writeln!(
+                tmx,
+                r#"  <tileoffset x="{}" y="{}"/>"#,
+                tileset.offset_x, tileset.offset_y
+            )?;
+        }
+        if let Some(image) = &tileset.image {
+            self.write_image(tmx, 2, image)?;
+        }
+        write_properties(tmx, 2, &tileset.properties)?;
+
+        let mut tiles = tileset.tiles().collect::<Vec<_>>();
+        tiles.sort_by_key(|(tile_id, _)| *tile_id);
+        for (tile_id, tile) in tiles {
+            write!(tmx, r#"  <tile id="{}""#, tile_id)?;
+            if let Some(user_type) = &tile.user_type {
+                write!(tmx, r#" type="{}""#, escape(user_type))?;
+            }
+            writeln!(tmx, ">")?;
+
+            write_properties(tmx, 3, &tile.properties)?;
+            if let Some(image) = &tile.image {
+                self.write_image(tmx, 3, image)?;
+            }
+            if let Some(animation) = &tile.animation {
+                writeln!(tmx, "   <animation>")?;
+                for frame in animation {
+                    writeln!(
+                        tmx,
+                        r#"    <frame tileid="{}" duration="{}"/>"#,
+                        frame.tile_id, frame.duration
+                    )?;
+                }
+                writeln!(tmx, "   </animation>")?;
+            }
+            writeln!(tmx, "  </tile>")?;
+        }
+
+        writeln!(tmx, " </tileset>")
+    }
+
+    fn write_image(
+        &self,
+        tmx: &mut String,
+        indent: usize,
+        image: &tiled::Image,
+    ) -> std::fmt::Result {
+        writeln!(
+            tmx,
+            r#"{}<image source="{}" width="{}" height="{}"/>"#,
+            " ".repeat(indent),
+            escape(&self.get_path(&image.source)),
+            image.width,
+            image.height
+        )
+    }
+
+    fn write_tile_layer(
+        &self,
+        tmx: &mut String,
+        bounds: &TiledMapBounds,
+        flattened_layer: &FlattenedLayer,
+        tilesets: &[ExportedTileset],
+        tiles: &[ExportedTile],
+    ) -> std::fmt::Result {
+        write!(tmx, " <layer")?;
+        write_layer_attributes(tmx, flattened_layer)?;
+        writeln!(
+            tmx,
+            r#" width="{}" height="{}">"#,
+            bounds.width, bounds.height
+        )?;
+        write_properties(tmx, 2, &flattened_layer.layer.properties)?;
+        write_tile_data(tmx, bounds, tilesets, tiles)?;
+        writeln!(tmx, " </layer>")
+    }
+
+    fn write_object_layer(
+        &self,
+        tmx: &mut String,
+        bounds: &TiledMapBounds,
+        flattened_layer: &FlattenedLayer,
+        tilesets: &[ExportedTileset],
+        objects: &[ExportedObject],
+    ) -> std::fmt::Result {
+        write!(tmx, " <objectgroup")?;
+        write_layer_attributes(tmx, flattened_layer)?;
+        writeln!(tmx, ">")?;
+        write_properties(tmx, 2, &flattened_layer.layer.properties)?;
+
+        let mut objects = objects.iter().collect::<Vec<_>>();
+        objects.sort_by_key(|object| object.data.get_id());
+        for object in objects {
+            write_object(tmx, bounds, tilesets, object)?;
+        }
+
+        writeln!(tmx, " </objectgroup>")
+    }
+}
+
+/// Writes the start of the map element, the caller writes the tilesets and layers
+pub(super) fn write_map_start(
+    tmx: &mut String,
+    bounds: &TiledMapBounds,
+    next_layer_id: u32,
+    next_object_id: u32,
+) -> std::fmt::Result {
+    writeln!(tmx, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        tmx,
+        r#"<map version="1.10" tiledversion="1.11.0" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{}" tileheight="{}" infinite="0" nextlayerid="{}" nextobjectid="{}">"#,
+        bounds.width, bounds.height, TILE_WIDTH, TILE_HEIGHT, next_layer_id, next_object_id
+    )
+}
+
+/// Writes a reference to an external tileset, relative to the map
+pub(super) fn write_tileset_source(
+    tmx: &mut String,
+    first_gid: u32,
+    source: &str,
+) -> std::fmt::Result {
+    writeln!(
+        tmx,
+        r#" <tileset firstgid="{}" source="{}"/>"#,
+        first_gid,
+        escape(source)
+    )
+}
+
+/// Writes the data of a Tile layer, positions without a Tile are left empty
+pub(super) fn write_tile_data(
+    tmx: &mut String,
+    bounds: &TiledMapBounds,
+    tilesets: &[ExportedTileset],
+    tiles: &[ExportedTile],
+) -> std::fmt::Result {
+    // rows are top-down
+    let mut gids = vec![0; (bounds.width * bounds.height) as usize];
+    for tile in tiles {
+        let Some(gid) = get_gid(tilesets, &tile.tileset, tile.tile_id, tile.flip) else {
+            continue;
+        };
+        let row = bounds.height - 1 - tile.position.y;
+        gids[(row * bounds.width + tile.position.x) as usize] = gid;
+    }
+
+    writeln!(tmx, r#"  <data encoding="csv">"#)?;
+    let rows = gids
+        .chunks(bounds.width as usize)
+        .map(|row| {
+            row.iter()
+                .map(|gid| gid.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>();
+    writeln!(tmx, "{}", rows.join(",\n"))?;
+    writeln!(tmx, "</data>")
+}
+
+/// Writes a tile Object, Objects from unknown tilesets are skipped
+pub(super) fn write_object(
+    tmx: &mut String,
+    bounds: &TiledMapBounds,
+    tilesets: &[ExportedTileset],
+    object: &ExportedObject,
+) -> std::fmt::Result {
+    let direction = object.data.get_direction();
+    let flip = get_object_tile_flip_flags(object.flip, direction);
+    let Some(gid) = get_gid(tilesets, &object.tileset, object.tile_id, flip) else {
+        return Ok(());
+    };
+
+    // Tiled sizes are before rotation
+    let (width, height) = match direction {
+        Direction::East | Direction::West => (object.footprint.width, object.footprint.height),
+        Direction::North | Direction::South => (object.footprint.height, object.footprint.width),
+    };
+    let (width, height) = (width * TILE_WIDTH, height * TILE_HEIGHT);
+
+    // tile objects rotate around their bottom-left corner
+    let (left, bottom) = get_object_position(bounds, object.position);
+    let (x, y) = match direction {
+        Direction::East => (left, bottom),
+        Direction::South => (left, bottom - width),
+        Direction::West => (left + width, bottom - height),
+        Direction::North => (left + height, bottom),
+    };
+
+    write!(
+        tmx,
+        r#"  <object id="{}" type="{}" gid="{}" x="{}" y="{}" width="{}" height="{}""#,
+        object.data.get_id(),
+        object.data.get_type(),
+        gid,
+        x,
+        y,
+        width,
+        height
+    )?;
+    if direction != Direction::East {
+        write!(tmx, r#" rotation="{}""#, direction.rotation())?;
+    }
+    if !object.visible {
+        write!(tmx, r#" visible="0""#)?;
+    }
+    writeln!(tmx, ">")?;
+    write_object_data(tmx, &object.data)?;
+    writeln!(tmx, "  </object>")
+}
+
+/// Tile ids of image collections don't have to be contiguous
+pub(super) fn get_gid_count(tileset: &tiled::Tileset) -> u32 {
+    tileset
+        .tiles()
+        .map(|(tile_id, _)| tile_id + 1)
+        .max()
+        .unwrap_or_default()
+        .max(tileset.tilecount)
+}
+
+fn get_gid(
+    tilesets: &[ExportedTileset],
+    tileset: &str,
+    tile_id: u32,
+    flip: TileFlip,
+) -> Option<u32> {
+    let tileset = tilesets.iter().find(|other| other.name == tileset)?;
+
+    let mut gid = tileset.first_gid + tile_id;
+    if flip.x {
+        gid |= FLIP_H_FLAG;
+    }
+    if flip.y {
+        gid |= FLIP_V_FLAG;
+    }
+    if flip.d {
+        gid |= FLIP_D_FLAG;
+    }
+    Some(gid)
+}
+
+/// Gets the bottom-left corner of a footprint in Tiled (top-down) pixel coordinates
+fn get_object_position(bounds: &TiledMapBounds, position: TilePos) -> (u32, u32) {
+    (
+        position.x * TILE_WIDTH,
+        (bounds.height - position.y) * TILE_HEIGHT,
+    )
+}
+
+/// Writes the attributes shared by every layer type, group layers have been flattened into them
+fn write_layer_attributes(tmx: &mut String, flattened_layer: &FlattenedLayer) -> std::fmt::Result {
+    let layer = &flattened_layer.layer;
+    write!(
+        tmx,
+        r#" id="{}" name="{}""#,
+        layer.id(),
+        escape(&layer.name)
+    )?;
+    if let Some(user_type) = &layer.user_type {
+        write!(tmx, r#" class="{}""#, escape(user_type))?;
+    }
+    if flattened_layer.opacity != 1.0 {
+        write!(tmx, r#" opacity="{}""#, flattened_layer.opacity)?;
+    }
+    if !flattened_layer.visible {
+        write!(tmx, r#" visible="0""#)?;
+    }
+    if let Some(tint_color) = layer.tint_color {
+        write!(tmx, r#" tintcolor="{}""#, get_color(tint_color))?;
+    }
+    Ok(())
+}
+
+/// Objects are written back as their Tiled class and properties
+fn write_object_data(tmx: &mut String, data: &ObjectData) -> std::fmt::Result {
+    match data {
+        ObjectData::Resources { r#type, amount, .. } => {
+            writeln!(tmx, "   <properties>")?;
+            writeln!(
+                tmx,
+                r#"    <property name="{}" type="int" value="{}"/>"#,
+                AMOUNT_PROPERTY, amount
+            )?;
+            writeln!(
+                tmx,
+                r#"    <property name="{}" propertytype="ResourceType" value="{}"/>"#,
+                RESOURCE_TYPE_PROPERTY, r#type
+            )?;
+            writeln!(tmx, "   </properties>")
+        }
+    }
+}
+
+fn write_properties(
+    tmx: &mut String,
+    indent: usize,
+    properties: &tiled::Properties,
+) -> std::fmt::Result {
+    if properties.is_empty() {
+        return Ok(());
+    }
+
+    let padding = " ".repeat(indent);
+    writeln!(tmx, "{}<properties>", padding)?;
+
+    let mut names = properties.keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        write!(tmx, r#"{} <property name="{}""#, padding, escape(name))?;
+        match &properties[name] {
+            tiled::PropertyValue::BoolValue(value) => {
+                writeln!(tmx, r#" type="bool" value="{}"/>"#, value)?
+            }
+            tiled::PropertyValue::FloatValue(value) => {
+                writeln!(tmx, r#" type="float" value="{}"/>"#, value)?
+            }
+            tiled::PropertyValue::IntValue(value) => {
+                writeln!(tmx, r#" type="int" value="{}"/>"#, value)?
+            }
+            tiled::PropertyValue::ColorValue(value) => {
+                writeln!(tmx, r#" type="color" value="{}"/>"#, get_color(*value))?
+            }
+            tiled::PropertyValue::StringValue(value) => {
+                writeln!(tmx, r#" value="{}"/>"#, escape(value))?
+            }
+            tiled::PropertyValue::FileValue(value) => {
+                writeln!(tmx, r#" type="file" value="{}"/>"#, escape(value))?
+            }
+            tiled::PropertyValue::ObjectValue(value) => {
+                writeln!(tmx, r#" type="object" value="{}"/>"#, value)?
+            }
+            tiled::PropertyValue::ClassValue {
+                property_type,
+                properties,
+            } => {
+                writeln!(
+                    tmx,
+                    r#" type="class" propertytype="{}">"#,
+                    escape(property_type)
+                )?;
+                write_properties(tmx, indent + 2, properties)?;
+                writeln!(tmx, "{} </property>", padding)?;
+            }
+        }
+    }
+
+    writeln!(tmx, "{}</properties>", padding)
+}
+
+#[inline]
+fn get_color(color: tiled::Color) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        color.alpha, color.red, color.green, color.blue
+    )
+}
+
+/// Escapes text for use in an XML attribute
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
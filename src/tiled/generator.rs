@@ -0,0 +1,445 @@
+//! Seeded procedural maps
+//!
+//! Maps are generated as TMX so that they go through the same loading
+//! and validation as hand-authored maps. Generated maps use the
+//! tiles and objects tilesets next to the map assets
+
+use std::fmt::Write;
+use std::path::Path;
+
+use bevy_ecs_tilemap::prelude::*;
+use thiserror::Error;
+
+use super::export::{
+    get_gid_count, write_map_start, write_object, write_tile_data, write_tileset_source,
+    ExportedObject, ExportedTile, ExportedTileset,
+};
+use super::{is_tileset_tile, TiledMapBounds, MIN_TILEMAP_HEIGHT, MIN_TILEMAP_WIDTH};
+use crate::assets::registry::Registry;
+use crate::data::{
+    direction::Direction,
+    objects::ObjectData,
+    resources::ResourceType,
+    tiles::{Terrain, TileData},
+};
+use crate::tilemap::TileFootprint;
+
+/// Asset source that serves generated maps, see get_generated_map_path()
+pub const GENERATED_MAP_SOURCE: &str = "generated";
+
+const GENERATED_MAP_EXTENSION: &str = "tmx";
+
+/// Tileset paths, relative to the map assets
+pub const TILES_TILESET: &str = "tiles.tsx";
+pub const OBJECTS_TILESET: &str = "objects.tsx";
+
+/// The Resources tile in the objects tileset
+const RESOURCES_TILE: u32 = 0;
+
+const TERRAIN_LAYER_ID: u32 = 1;
+const RESOURCES_LAYER_ID: u32 = 2;
+
+/// Size of the terrain noise features, in tiles
+const TERRAIN_SCALE: f32 = 8.0;
+
+/// Size of the resource noise features, in tiles
+const RESOURCE_SCALE: f32 = 4.0;
+
+/// Noise seeds are offset from the map seed so each layer of noise is different
+const GRASS_NOISE: u64 = 1;
+const RESOURCE_NOISE: u64 = 2;
+const RESOURCE_TYPE_NOISE: u64 = 3;
+
+/// Controls the shape of a generated map
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapGeneratorSettings {
+    pub seed: u64,
+
+    /// Map size in tiles, at least MIN_TILEMAP_WIDTH x MIN_TILEMAP_HEIGHT
+    pub width: u32,
+    pub height: u32,
+
+    /// Terrain noise above this (0.0 - 1.0) is grass
+    pub grass_threshold: f32,
+
+    /// The Resources placed on the map, chosen by noise so that they cluster
+    pub resource_types: Vec<ResourceType>,
+
+    /// Resource noise above this (0.0 - 1.0) may have Resources
+    pub resource_threshold: f32,
+
+    /// Resources are placed at least this far apart, in tiles
+    pub resource_spacing: u32,
+
+    /// Resources are kept at least this far from the map edge, in tiles
+    pub edge_margin: u32,
+
+    /// At least this many Resources are placed, even below the threshold
+    pub min_resources: usize,
+    pub max_resources: usize,
+
+    /// Resources at the center of the map have the min amount,
+    /// Resources at the corners have the max amount
+    pub min_amount: u32,
+    pub max_amount: u32,
+}
+
+impl MapGeneratorSettings {
+    /// The default settings with the given seed,
+    /// placing every Resource type the Registry defines
+    pub fn new(seed: u64, registry: &Registry) -> Self {
+        Self {
+            seed,
+            width: 40,
+            height: 40,
+            grass_threshold: 0.55,
+            resource_types: registry
+                .resources
+                .iter()
+                .map(|definition| definition.id)
+                .collect(),
+            resource_threshold: 0.6,
+            resource_spacing: 5,
+            edge_margin: 2,
+            min_resources: 3,
+            max_resources: 12,
+            min_amount: 50,
+            max_amount: 300,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MapGeneratorError {
+    #[error("Could not load tileset: {0}")]
+    Tileset(#[from] tiled::Error),
+
+    #[error("Tileset {0} has no buildable Ground tiles")]
+    MissingTerrainTiles(String),
+
+    #[error("Tileset {0} has no Resources tile")]
+    MissingResourcesTile(String),
+}
+
+/// The tilesets generated maps are built from
+#[derive(Debug, Clone)]
+pub struct GeneratorTilesets {
+    pub tiles: tiled::Tileset,
+    pub objects: tiled::Tileset,
+
+    /// Most of the map is dirt, with patches of grass
+    dirt_tile: u32,
+    grass_tile: u32,
+}
+
+impl GeneratorTilesets {
+    /// Terrain is picked from the buildable Ground tiles of the tiles tileset,
+    /// the first is dirt and the second (if any) is grass
+    pub fn new(tiles: tiled::Tileset, objects: tiled::Tileset) -> Result<Self, MapGeneratorError> {
+        let mut terrain_tiles = (0..get_gid_count(&tiles))
+            .filter(|tile_id| is_tileset_tile(&tiles, *tile_id))
+            .filter(|tile_id| {
+                let tile_data = match tiles.get_tile(*tile_id) {
+                    Some(tile) => TileData::new(&tile.properties),
+                    None => Ok(TileData::default()),
+                };
+                tile_data.is_ok_and(|tile_data| {
+                    tile_data.buildable && tile_data.terrain == Terrain::Ground
+                })
+            });
+
+        let Some(dirt_tile) = terrain_tiles.next() else {
+            return Err(MapGeneratorError::MissingTerrainTiles(tiles.name));
+        };
+        let grass_tile = terrain_tiles.next().unwrap_or(dirt_tile);
+
+        if !is_tileset_tile(&objects, RESOURCES_TILE) {
+            return Err(MapGeneratorError::MissingResourcesTile(objects.name));
+        }
+
+        Ok(Self {
+            tiles,
+            objects,
+            dirt_tile,
+            grass_tile,
+        })
+    }
+
+    /// Loads the tilesets from the map assets directory
+    pub fn load<Cache: tiled::ResourceCache, Reader: tiled::ResourceReader>(
+        loader: &mut tiled::Loader<Cache, Reader>,
+        assets_dir: impl AsRef<Path>,
+    ) -> Result<Self, MapGeneratorError> {
+        let assets_dir = assets_dir.as_ref();
+        Self::new(
+            loader.load_tsx_tileset(assets_dir.join(TILES_TILESET))?,
+            loader.load_tsx_tileset(assets_dir.join(OBJECTS_TILESET))?,
+        )
+    }
+}
+
+/// Gets the asset path of the map generated from a seed
+#[inline]
+pub fn get_generated_map_path(seed: u64) -> String {
+    format!("{GENERATED_MAP_SOURCE}://{seed}.{GENERATED_MAP_EXTENSION}")
+}
+
+/// Gets the seed of a generated map from its asset path
+pub fn get_generated_map_seed(path: impl AsRef<str>) -> Option<u64> {
+    let file_name = path
+        .as_ref()
+        .strip_prefix(GENERATED_MAP_SOURCE)?
+        .strip_prefix("://")?;
+    get_generated_map_file_seed(file_name)
+}
+
+/// Gets the seed of a generated map from its file name (the asset path without the source)
+pub fn get_generated_map_file_seed(file_name: impl AsRef<str>) -> Option<u64> {
+    file_name
+        .as_ref()
+        .strip_suffix(GENERATED_MAP_EXTENSION)?
+        .strip_suffix('.')?
+        .parse()
+        .ok()
+}
+
+/// A Resources Object placed by the generator, in Tiled (top-down) tile coordinates
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GeneratedResource {
+    pub x: u32,
+    pub y: u32,
+    pub r#type: ResourceType,
+    pub amount: u32,
+}
+
+/// A generated map, before being written out as TMX
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedMap {
+    pub width: u32,
+    pub height: u32,
+
+    /// Tile ids from the tiles tileset, row-major in Tiled (top-down) order
+    pub tiles: Vec<u32>,
+
+    pub resources: Vec<GeneratedResource>,
+}
+
+/// SplitMix64, small and good enough for placing things on a map
+fn hash(seed: u64, value: u64) -> u64 {
+    let mut z = seed.wrapping_add(value.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Random value for a lattice point, 0.0 - 1.0
+fn lattice(seed: u64, x: i64, y: i64) -> f32 {
+    let value = hash(hash(seed, x as u64), y as u64);
+    (value >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Smoothed value noise, 0.0 - 1.0
+fn value_noise(seed: u64, x: f32, y: f32, scale: f32) -> f32 {
+    let (x, y) = (x / scale, y / scale);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let (sx, sy) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let top = lattice(seed, x0, y0) * (1.0 - sx) + lattice(seed, x0 + 1, y0) * sx;
+    let bottom = lattice(seed, x0, y0 + 1) * (1.0 - sx) + lattice(seed, x0 + 1, y0 + 1) * sx;
+    top * (1.0 - sy) + bottom * sy
+}
+
+/// Two octaves of value noise, 0.0 - 1.0
+fn noise(seed: u64, x: u32, y: u32, scale: f32) -> f32 {
+    let (x, y) = (x as f32, y as f32);
+    (value_noise(seed, x, y, scale) * 2.0 + value_noise(hash(seed, 1), x, y, scale * 0.5)) / 3.0
+}
+
+/// Generates a map from the settings
+///
+/// The same settings and tilesets always generate the same map
+pub fn generate_map(settings: &MapGeneratorSettings, tilesets: &GeneratorTilesets) -> GeneratedMap {
+    let width = settings.width.max(MIN_TILEMAP_WIDTH);
+    let height = settings.height.max(MIN_TILEMAP_HEIGHT);
+
+    let grass_seed = hash(settings.seed, GRASS_NOISE);
+    let tiles = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if noise(grass_seed, x, y, TERRAIN_SCALE) > settings.grass_threshold {
+                tilesets.grass_tile
+            } else {
+                tilesets.dirt_tile
+            }
+        })
+        .collect();
+
+    GeneratedMap {
+        width,
+        height,
+        tiles,
+        resources: place_resources(settings, width, height),
+    }
+}
+
+/// Places Resources at the strongest resource noise first,
+/// skipping anything too close to an already placed Resource
+fn place_resources(
+    settings: &MapGeneratorSettings,
+    width: u32,
+    height: u32,
+) -> Vec<GeneratedResource> {
+    if settings.resource_types.is_empty() {
+        return vec![];
+    }
+
+    let resource_seed = hash(settings.seed, RESOURCE_NOISE);
+    let type_seed = hash(settings.seed, RESOURCE_TYPE_NOISE);
+    let margin = settings.edge_margin.min(width / 2).min(height / 2);
+
+    let mut candidates = (margin..height - margin)
+        .flat_map(|y| (margin..width - margin).map(move |x| (x, y)))
+        .map(|(x, y)| (x, y, noise(resource_seed, x, y, RESOURCE_SCALE)))
+        .collect::<Vec<_>>();
+    // ties are broken by position so the order is stable
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then((a.1, a.0).cmp(&(b.1, b.0))));
+
+    let center = (width as f32 / 2.0, height as f32 / 2.0);
+    let max_distance = (center.0 * center.0 + center.1 * center.1).sqrt();
+    let min_spacing = (settings.resource_spacing * settings.resource_spacing) as i64;
+
+    let mut resources: Vec<GeneratedResource> = Vec::new();
+    for (x, y, value) in candidates {
+        if resources.len() >= settings.max_resources {
+            break;
+        }
+
+        if value < settings.resource_threshold && resources.len() >= settings.min_resources {
+            break;
+        }
+
+        let too_close = resources.iter().any(|other| {
+            let (dx, dy) = (x as i64 - other.x as i64, y as i64 - other.y as i64);
+            dx * dx + dy * dy < min_spacing
+        });
+        if too_close {
+            continue;
+        }
+
+        // further out is richer
+        let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+        let distance = (dx * dx + dy * dy).sqrt() / max_distance;
+        let amount = settings.min_amount as f32
+            + (settings.max_amount.saturating_sub(settings.min_amount)) as f32 * distance;
+        // round to a tidy number
+        let amount = ((amount / 10.0).round() as u32 * 10).max(1);
+
+        let type_index =
+            (noise(type_seed, x, y, TERRAIN_SCALE) * settings.resource_types.len() as f32) as usize;
+        let r#type = settings.resource_types[type_index.min(settings.resource_types.len() - 1)];
+
+        resources.push(GeneratedResource {
+            x,
+            y,
+            r#type,
+            amount,
+        });
+    }
+
+    resources
+}
+
+impl GeneratedMap {
+    /// Writes the map out as TMX
+    ///
+    /// Tilesets are referenced relative to the map
+    pub fn to_tmx(&self, tilesets: &GeneratorTilesets) -> String {
+        let mut tmx = String::new();
+        // writing to a String can't fail
+        self.write_tmx(&mut tmx, tilesets).unwrap();
+        tmx
+    }
+
+    fn write_tmx(&self, tmx: &mut String, tilesets: &GeneratorTilesets) -> std::fmt::Result {
+        let bounds = TiledMapBounds {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        };
+
+        let exported_tilesets = [
+            ExportedTileset {
+                name: tilesets.tiles.name.clone(),
+                first_gid: 1,
+            },
+            ExportedTileset {
+                name: tilesets.objects.name.clone(),
+                first_gid: 1 + get_gid_count(&tilesets.tiles),
+            },
+        ];
+
+        // rows are top-down
+        let tiles = self
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|(idx, tile_id)| ExportedTile {
+                position: TilePos {
+                    x: idx as u32 % self.width,
+                    y: self.height - 1 - idx as u32 / self.width,
+                },
+                tileset: tilesets.tiles.name.clone(),
+                tile_id: *tile_id,
+                flip: TileFlip::default(),
+            })
+            .collect::<Vec<_>>();
+
+        write_map_start(
+            tmx,
+            &bounds,
+            RESOURCES_LAYER_ID + 1,
+            self.resources.len() as u32 + 1,
+        )?;
+        write_tileset_source(tmx, exported_tilesets[0].first_gid, TILES_TILESET)?;
+        write_tileset_source(tmx, exported_tilesets[1].first_gid, OBJECTS_TILESET)?;
+
+        writeln!(
+            tmx,
+            r#" <layer id="{}" name="Terrain" width="{}" height="{}">"#,
+            TERRAIN_LAYER_ID, self.width, self.height
+        )?;
+        write_tile_data(tmx, &bounds, &exported_tilesets, &tiles)?;
+        writeln!(tmx, " </layer>")?;
+
+        writeln!(
+            tmx,
+            r#" <objectgroup id="{}" name="Resources">"#,
+            RESOURCES_LAYER_ID
+        )?;
+        for (idx, resource) in self.resources.iter().enumerate() {
+            let object = ExportedObject {
+                position: TilePos {
+                    x: resource.x,
+                    y: self.height - 1 - resource.y,
+                },
+                footprint: TileFootprint::default(),
+                tileset: tilesets.objects.name.clone(),
+                tile_id: RESOURCES_TILE,
+                flip: TileFlip::default(),
+                visible: true,
+                data: ObjectData::Resources {
+                    id: idx as u32 + 1,
+                    r#type: resource.r#type,
+                    amount: resource.amount,
+                    direction: Direction::default(),
+                },
+            };
+            write_object(tmx, &bounds, &exported_tilesets, &object)?;
+        }
+        writeln!(tmx, " </objectgroup>")?;
+        writeln!(tmx, "</map>")
+    }
+}
@@ -0,0 +1,186 @@
+//! Tiled project (.tiled-project) custom property types
+//!
+//! The game's enums and Object / Tile classes are exported to the project
+//! so that Tiled offers them as dropdowns instead of free-form strings
+
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use super::FromTiledClass;
+use crate::assets::registry::Registry;
+use crate::data::{
+    objects::{ObjectType, AMOUNT_PROPERTY, RESOURCE_TYPE_PROPERTY},
+    tiles::{Terrain, TileData, BUILDABLE_PROPERTY, MOVE_COST_PROPERTY, TERRAIN_PROPERTY},
+};
+
+/// Class property types show up in Tiled with this color
+const CLASS_COLOR: &str = "#ffa0a0a4";
+
+/// A single custom property type
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PropertyType {
+    Enum(EnumPropertyType),
+    Class(ClassPropertyType),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnumPropertyType {
+    pub id: u32,
+    pub name: String,
+    pub storage_type: String,
+    pub values: Vec<String>,
+    pub values_as_flags: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassPropertyType {
+    pub id: u32,
+    pub name: String,
+    pub color: String,
+    pub draw_fill: bool,
+
+    /// What the class can be used as (object, tile, property, etc)
+    pub use_as: Vec<String>,
+    pub members: Vec<ClassMember>,
+}
+
+/// A class member and its default value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassMember {
+    pub name: String,
+
+    /// The Tiled storage type (bool, int, string, etc)
+    pub r#type: String,
+
+    /// The custom property type for enum members
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub property_type: Option<String>,
+    pub value: serde_json::Value,
+}
+
+impl ClassMember {
+    fn new(name: &str, r#type: &str, value: impl Into<serde_json::Value>) -> Self {
+        Self {
+            name: name.to_owned(),
+            r#type: r#type.to_owned(),
+            property_type: None,
+            value: value.into(),
+        }
+    }
+
+    /// Enums are stored as their value name
+    fn new_enum(name: &str, property_type: &str, value: impl ToString) -> Self {
+        Self {
+            name: name.to_owned(),
+            r#type: "string".to_owned(),
+            property_type: Some(property_type.to_owned()),
+            value: value.to_string().into(),
+        }
+    }
+}
+
+fn create_enum(id: u32, name: &str, values: impl IntoIterator<Item = String>) -> PropertyType {
+    PropertyType::Enum(EnumPropertyType {
+        id,
+        name: name.to_owned(),
+        storage_type: "string".to_owned(),
+        values: values.into_iter().collect(),
+        values_as_flags: false,
+    })
+}
+
+fn create_class(id: u32, name: &str, use_as: &str, mut members: Vec<ClassMember>) -> PropertyType {
+    // Tiled lists members by name
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+
+    PropertyType::Class(ClassPropertyType {
+        id,
+        name: name.to_owned(),
+        color: CLASS_COLOR.to_owned(),
+        draw_fill: true,
+        use_as: vec![use_as.to_owned()],
+        members,
+    })
+}
+
+/// Gets the custom property types for the game
+///
+/// Resource types come from the Registry,
+/// everything else comes from the game data types
+pub fn get_property_types(registry: &Registry) -> Vec<PropertyType> {
+    let resource_types = registry
+        .resources
+        .iter()
+        .map(|definition| definition.id.to_string())
+        .collect::<Vec<_>>();
+
+    let tile_data = TileData::default();
+
+    vec![
+        create_enum(
+            1,
+            "ObjectType",
+            ObjectType::iter().map(|r#type| r#type.to_string()),
+        ),
+        create_enum(2, "ResourceType", resource_types.clone()),
+        create_enum(
+            3,
+            "Terrain",
+            Terrain::iter().map(|terrain| terrain.to_string()),
+        ),
+        create_class(
+            4,
+            &ObjectType::Resources.to_string(),
+            "object",
+            vec![
+                ClassMember::new(AMOUNT_PROPERTY, "int", 0),
+                ClassMember::new_enum(
+                    RESOURCE_TYPE_PROPERTY,
+                    "ResourceType",
+                    resource_types.first().cloned().unwrap_or_default(),
+                ),
+            ],
+        ),
+        create_class(
+            5,
+            TileData::CLASS,
+            "tile",
+            vec![
+                ClassMember::new(BUILDABLE_PROPERTY, "bool", tile_data.buildable),
+                ClassMember::new_enum(TERRAIN_PROPERTY, "Terrain", tile_data.terrain),
+                ClassMember::new(MOVE_COST_PROPERTY, "int", tile_data.move_cost),
+            ],
+        ),
+    ]
+}
+
+/// Replaces the custom property types in a parsed .tiled-project
+///
+/// Everything else in the project is left as-is
+pub fn update_project(project: &mut serde_json::Value, registry: &Registry) -> anyhow::Result<()> {
+    let Some(project) = project.as_object_mut() else {
+        anyhow::bail!("Tiled project is not a JSON object");
+    };
+
+    project.insert(
+        "propertyTypes".to_owned(),
+        serde_json::to_value(get_property_types(registry))?,
+    );
+
+    Ok(())
+}
+
+/// Formats a .tiled-project the same way Tiled does (4 space indent)
+pub fn write_project(project: &serde_json::Value) -> anyhow::Result<String> {
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    project.serialize(&mut serializer)?;
+    buffer.push(b'\n');
+
+    Ok(String::from_utf8(buffer)?)
+}
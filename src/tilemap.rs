@@ -1,10 +1,16 @@
-use bevy::{ecs::query::QueryData, prelude::*};
+use std::time::Duration;
+
+use bevy::{
+    ecs::{query::QueryData, system::EntityCommands},
+    prelude::*,
+};
 use bevy_ecs_tilemap::prelude::*;
 use bevy_mod_picking::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::data::objects::ObjectData;
-use crate::plugins::game::objects::Object;
-use crate::plugins::tiled::{TiledMapItemClickEvent, TiledMapObjectClickEvent};
+use crate::data::{objects::ObjectData, tiles::TileData};
+use crate::plugins::game::{objects::Object, tiles::Tile};
+use crate::plugins::tiled::{TiledLayerColor, TiledMapItemClickEvent, TiledMapObjectClickEvent};
 
 #[derive(QueryData)]
 #[query_data(derive(Debug))]
@@ -21,6 +27,7 @@ pub struct TileMapQuery {
     pub r#type: &'static TilemapType,
     pub storage: &'static TileStorage,
     pub transform: &'static Transform,
+    pub color: &'static TiledLayerColor,
 }
 
 #[derive(QueryData)]
@@ -31,6 +38,151 @@ pub struct TileMapQueryMut {
     pub r#type: &'static TilemapType,
     pub storage: &'static mut TileStorage,
     pub transform: &'static Transform,
+    pub color: &'static TiledLayerColor,
+}
+
+/// The tiles covered by an Object or Item
+///
+/// The owning tile sits at the bottom-left of the footprint
+/// and is registered in every covered TileStorage cell
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Component, Reflect, Serialize, Deserialize)]
+pub struct TileFootprint {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for TileFootprint {
+    fn default() -> Self {
+        Self {
+            width: 1,
+            height: 1,
+        }
+    }
+}
+
+impl TileFootprint {
+    #[inline]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// All of the positions covered by this footprint, starting at the origin
+    pub fn positions(&self, origin: TilePos) -> impl Iterator<Item = TilePos> {
+        let footprint = *self;
+        (0..footprint.height).flat_map(move |y| {
+            (0..footprint.width).map(move |x| TilePos {
+                x: origin.x + x,
+                y: origin.y + y,
+            })
+        })
+    }
+
+    /// The Tiles covered by this footprint, starting at the origin
+    ///
+    /// Positions that are off the map have no Tile
+    pub fn tiles<'a>(
+        &self,
+        origin: TilePos,
+        storage: &'a TileStorage,
+    ) -> impl Iterator<Item = Option<Entity>> + 'a {
+        let footprint = *self;
+        (0..footprint.height).flat_map(move |y| {
+            (0..footprint.width).map(move |x| {
+                let position = TilePos {
+                    x: origin.x.checked_add(x)?,
+                    y: origin.y.checked_add(y)?,
+                };
+                storage.checked_get(&position)
+            })
+        })
+    }
+
+    #[inline]
+    pub fn contains(&self, origin: TilePos, position: &TilePos) -> bool {
+        position.x >= origin.x
+            && position.y >= origin.y
+            && position.x < origin.x + self.width
+            && position.y < origin.y + self.height
+    }
+
+    #[inline]
+    pub fn fits(&self, origin: TilePos, map_size: &TilemapSize) -> bool {
+        origin
+            .x
+            .checked_add(self.width)
+            .is_some_and(|right| right <= map_size.x)
+            && origin
+                .y
+                .checked_add(self.height)
+                .is_some_and(|top| top <= map_size.y)
+    }
+}
+
+/// Tag for the extra tiles that render the rest of a multi-tile footprint
+///
+/// These are children of the owning tile and aren't in the TileStorage
+#[derive(Debug, Component)]
+pub struct TileFootprintPart;
+
+/// A single frame of a TileAnimation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TileAnimationFrame {
+    pub texture_index: u32,
+    pub duration: Duration,
+}
+
+/// Frame by frame tile animation
+///
+/// Unlike bevy_ecs_tilemap's AnimatedTile, frames don't have to be contiguous
+/// in the texture and each frame has its own duration (like Tiled tile animations)
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct TileAnimation {
+    /// The texture index of the animated tile itself,
+    /// shown while the animation is paused
+    pub texture_index: u32,
+
+    pub frames: Vec<TileAnimationFrame>,
+
+    pub elapsed: Duration,
+    pub paused: bool,
+}
+
+impl TileAnimation {
+    /// Returns None if there are no frames to animate
+    pub fn new(texture_index: u32, frames: Vec<TileAnimationFrame>) -> Option<Self> {
+        if frames.is_empty() || frames.iter().all(|frame| frame.duration.is_zero()) {
+            return None;
+        }
+
+        Some(Self {
+            texture_index,
+            frames,
+            elapsed: Duration::ZERO,
+            paused: false,
+        })
+    }
+
+    /// Gets the texture index to show at the current time
+    pub fn get_texture_index(&self) -> u32 {
+        if self.paused {
+            return self.texture_index;
+        }
+
+        let total = self
+            .frames
+            .iter()
+            .map(|frame| frame.duration)
+            .sum::<Duration>();
+        let mut elapsed = Duration::from_nanos((self.elapsed.as_nanos() % total.as_nanos()) as u64);
+        for frame in &self.frames {
+            if elapsed < frame.duration {
+                return frame.texture_index;
+            }
+            elapsed -= frame.duration;
+        }
+
+        self.texture_index
+    }
 }
 
 #[inline]
@@ -50,27 +202,55 @@ pub fn get_tile_position(
     TilePos::from_world_pos(&map_position, map_size, grid_size, map_type)
 }
 
+/// Gets the world space area covered by a tilemap
+///
+/// Tilemaps are expected to be unscaled and unrotated
+#[inline]
+pub fn get_tilemap_world_bounds(
+    map_size: &TilemapSize,
+    grid_size: &TilemapGridSize,
+    map_transform: &Transform,
+) -> Rect {
+    // tiles are centered on their position
+    let min = map_transform.translation.truncate() - Vec2::new(grid_size.x, grid_size.y) / 2.0;
+    let size = Vec2::new(
+        map_size.x as f32 * grid_size.x,
+        map_size.y as f32 * grid_size.y,
+    );
+    Rect::from_corners(min, min + size)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_tile(
     parent: &mut ChildBuilder,
     storage: &mut TileStorage,
     tilemap_id: Entity,
     position: TilePos,
     texture_index: u32,
+    flip: TileFlip,
+    color: Color,
+    data: TileData,
     visible: bool,
+    animation: Option<TileAnimation>,
 ) -> Entity {
-    let tile_entity = parent
-        .spawn((
-            // NOTE: objects intentionally do not have Transforms for performance (but is that really true??)
-            TileBundle {
-                position,
-                tilemap_id: TilemapId(tilemap_id),
-                texture_index: TileTextureIndex(texture_index),
-                visible: TileVisible(visible),
-                ..Default::default()
-            },
-            Name::new(format!("Tile ({},{})", position.x, position.y)),
-        ))
-        .id();
+    let mut tile_entity = parent.spawn((
+        // NOTE: objects intentionally do not have Transforms for performance (but is that really true??)
+        TileBundle {
+            position,
+            tilemap_id: TilemapId(tilemap_id),
+            texture_index: TileTextureIndex(texture_index),
+            flip,
+            color: TileColor(color),
+            visible: TileVisible(visible),
+            ..Default::default()
+        },
+        Name::new(format!("Tile ({},{})", position.x, position.y)),
+        Tile(data),
+    ));
+    if let Some(animation) = animation {
+        tile_entity.insert(animation);
+    }
+    let tile_entity = tile_entity.id();
 
     storage.set(&position, tile_entity);
 
@@ -93,59 +273,115 @@ pub fn spawn_object(
     storage: &mut TileStorage,
     tilemap_id: Entity,
     position: TilePos,
+    footprint: TileFootprint,
     texture_index: u32,
+    flip: TileFlip,
+    color: Color,
     visible: bool,
     data: ObjectData,
+    animation: Option<TileAnimation>,
 ) -> Entity {
-    let tile_entity = parent
-        .spawn((
-            // NOTE: objects intentionally do not have Transforms for performance (but is that really true??)
-            TileBundle {
-                position,
-                tilemap_id: TilemapId(tilemap_id),
-                texture_index: TileTextureIndex(texture_index),
-                visible: TileVisible(visible),
-                ..Default::default()
+    let mut tile_entity = parent.spawn((
+        // NOTE: objects intentionally do not have Transforms for performance (but is that really true??)
+        TileBundle {
+            position,
+            tilemap_id: TilemapId(tilemap_id),
+            texture_index: TileTextureIndex(texture_index),
+            flip,
+            color: TileColor(color),
+            visible: TileVisible(visible),
+            ..Default::default()
+        },
+        Name::new(format!("Object ({},{})", position.x, position.y)),
+        Object(data),
+        footprint,
+        PickableBundle::default(),
+        On::<Pointer<Click>>::run(
+            |event: Listener<Pointer<Click>>,
+             mut click_events: EventWriter<TiledMapObjectClickEvent>| {
+                click_events.send(TiledMapObjectClickEvent {
+                    listener: event.listener(),
+                    target: event.target,
+                    button: event.button,
+                });
             },
-            // TODO: AnimatedTile for animation
-            Name::new(format!("Object ({},{})", position.x, position.y)),
-            Object(data),
-            PickableBundle::default(),
-            On::<Pointer<Click>>::run(
-                |event: Listener<Pointer<Click>>,
-                 mut click_events: EventWriter<TiledMapObjectClickEvent>| {
-                    click_events.send(TiledMapObjectClickEvent {
-                        listener: event.listener(),
-                        target: event.target,
-                        button: event.button,
-                    });
-                },
-            ),
-        ))
-        .id();
+        ),
+    ));
+    if let Some(animation) = animation {
+        tile_entity.insert(animation);
+    }
+    spawn_footprint_parts(
+        &mut tile_entity,
+        tilemap_id,
+        position,
+        footprint,
+        texture_index,
+        flip,
+        visible,
+    );
+    let tile_entity = tile_entity.id();
 
-    storage.set(&position, tile_entity);
+    for position in footprint.positions(position) {
+        storage.set(&position, tile_entity);
+    }
 
     tile_entity
 }
 
+/// Moves an existing Object, keeping its runtime state
+#[allow(clippy::too_many_arguments)]
+pub fn update_object(
+    commands: &mut Commands,
+    object_id: Entity,
+    tilemap_id: Entity,
+    position: TilePos,
+    footprint: TileFootprint,
+    texture_index: u32,
+    flip: TileFlip,
+    visible: bool,
+) {
+    let mut tile_entity = commands.entity(object_id);
+    tile_entity.insert((
+        position,
+        TileTextureIndex(texture_index),
+        flip,
+        Name::new(format!("Object ({},{})", position.x, position.y)),
+        footprint,
+    ));
+
+    // the footprint may have changed
+    tile_entity.despawn_descendants();
+    spawn_footprint_parts(
+        &mut tile_entity,
+        tilemap_id,
+        position,
+        footprint,
+        texture_index,
+        flip,
+        visible,
+    );
+}
+
 pub fn despawn_object(
     commands: &mut Commands,
     storage: &mut TileStorage,
     object_id: Entity,
     position: TilePos,
+    footprint: TileFootprint,
 ) {
     commands.entity(object_id).despawn_recursive();
-    storage.remove(&position);
+    for position in footprint.positions(position) {
+        storage.remove(&position);
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
-#[allow(dead_code)]
 pub fn spawn_item<C>(
     parent: &mut ChildBuilder,
     storage: &mut TileStorage,
     tilemap_id: Entity,
     position: TilePos,
+    footprint: TileFootprint,
     texture_index: u32,
     visible: bool,
     tag: C,
@@ -153,44 +389,133 @@ pub fn spawn_item<C>(
 where
     C: Component,
 {
-    let tile_entity = parent
-        .spawn((
-            TileBundle {
-                position,
-                tilemap_id: TilemapId(tilemap_id),
-                texture_index: TileTextureIndex(texture_index),
-                visible: TileVisible(visible),
-                ..Default::default()
+    let mut tile_entity = parent.spawn((
+        TileBundle {
+            position,
+            tilemap_id: TilemapId(tilemap_id),
+            texture_index: TileTextureIndex(texture_index),
+            visible: TileVisible(visible),
+            ..Default::default()
+        },
+        Name::new(format!("Item ({},{})", position.x, position.y)),
+        tag,
+        footprint,
+        PickableBundle::default(),
+        On::<Pointer<Click>>::run(
+            |event: Listener<Pointer<Click>>,
+             mut click_events: EventWriter<TiledMapItemClickEvent>| {
+                click_events.send(TiledMapItemClickEvent {
+                    listener: event.listener(),
+                    target: event.target,
+                    button: event.button,
+                });
             },
-            // TODO: AnimatedTile for animation
-            Name::new(format!("Item ({},{})", position.x, position.y)),
-            tag,
-            PickableBundle::default(),
-            On::<Pointer<Click>>::run(
-                |event: Listener<Pointer<Click>>,
-                 mut click_events: EventWriter<TiledMapItemClickEvent>| {
-                    click_events.send(TiledMapItemClickEvent {
-                        listener: event.listener(),
-                        target: event.target,
-                        button: event.button,
-                    });
-                },
-            ),
-        ))
-        .id();
+        ),
+    ));
+    spawn_footprint_parts(
+        &mut tile_entity,
+        tilemap_id,
+        position,
+        footprint,
+        texture_index,
+        TileFlip::default(),
+        visible,
+    );
+    let tile_entity = tile_entity.id();
 
-    storage.set(&position, tile_entity);
+    for position in footprint.positions(position) {
+        storage.set(&position, tile_entity);
+    }
 
     tile_entity
 }
 
-#[allow(dead_code)]
 pub fn despawn_item(
     commands: &mut Commands,
     storage: &mut TileStorage,
     item_id: Entity,
     position: TilePos,
+    footprint: TileFootprint,
 ) {
     commands.entity(item_id).despawn_recursive();
-    storage.remove(&position);
+    for position in footprint.positions(position) {
+        storage.remove(&position);
+    }
+}
+
+// TODO: large Objects and Items repeat their tile across the footprint,
+// it would be nicer to pull a block of tiles out of the tileset instead
+#[allow(clippy::too_many_arguments)]
+fn spawn_footprint_parts(
+    owner: &mut EntityCommands,
+    tilemap_id: Entity,
+    position: TilePos,
+    footprint: TileFootprint,
+    texture_index: u32,
+    flip: TileFlip,
+    visible: bool,
+) {
+    owner.with_children(|parent| {
+        for part_position in footprint.positions(position).skip(1) {
+            parent.spawn((
+                TileBundle {
+                    position: part_position,
+                    tilemap_id: TilemapId(tilemap_id),
+                    texture_index: TileTextureIndex(texture_index),
+                    flip,
+                    visible: TileVisible(visible),
+                    ..Default::default()
+                },
+                Name::new(format!("Part ({},{})", part_position.x, part_position.y)),
+                TileFootprintPart,
+            ));
+        }
+    });
+}
+
+/// Keeps the parts of multi-tile footprints looking like their owner
+#[allow(clippy::type_complexity)]
+pub fn sync_footprint_parts(
+    owner_query: Query<
+        (&TileTextureIndex, &TileColor, &TileVisible, &Children),
+        (
+            With<TileFootprint>,
+            Or<(
+                Changed<TileTextureIndex>,
+                Changed<TileColor>,
+                Changed<TileVisible>,
+            )>,
+        ),
+    >,
+    mut part_query: Query<
+        (&mut TileTextureIndex, &mut TileColor, &mut TileVisible),
+        (With<TileFootprintPart>, Without<TileFootprint>),
+    >,
+) {
+    for (texture_index, color, visible, children) in &owner_query {
+        let mut parts = part_query.iter_many_mut(children);
+        while let Some((mut part_texture_index, mut part_color, mut part_visible)) =
+            parts.fetch_next()
+        {
+            *part_texture_index = *texture_index;
+            *part_color = *color;
+            *part_visible = *visible;
+        }
+    }
+}
+
+pub fn animate_tiles(
+    time: Res<Time>,
+    mut tile_query: Query<(&mut TileAnimation, &mut TileTextureIndex)>,
+) {
+    for (mut animation, mut texture_index) in &mut tile_query {
+        if !animation.paused {
+            animation.elapsed += time.delta();
+        }
+
+        let new_texture_index = animation.get_texture_index();
+        if texture_index.0 != new_texture_index {
+            texture_index.0 = new_texture_index;
+        }
+    }
 }
@@ -1,7 +1,7 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::EntityCommands, prelude::*};
 use bevy_mod_picking::prelude::*;
 
-use super::{label::*, *};
+use super::{image::*, label::*, *};
 use crate::plugins::ui::UiAssets;
 
 const BUTTON_WIDTH: usize = 150;
@@ -10,6 +10,9 @@ pub const BUTTON_NORMAL: Color = Color::srgb(0.15, 0.15, 0.15);
 pub const BUTTON_HOVER: Color = Color::srgb(0.25, 0.25, 0.25);
 pub const BUTTON_PRESSED: Color = Color::srgb(0.35, 0.75, 0.35);
 const BUTTON_FONT_SIZE: usize = 32;
+const SMALL_BUTTON_HEIGHT: usize = 24;
+const SMALL_BUTTON_FONT_SIZE: usize = 14;
+const IMAGE_BUTTON_PADDING: usize = 2;
 
 pub fn create_button(
     parent: &mut ChildBuilder,
@@ -45,3 +48,66 @@ pub fn create_button(
         })
         .id()
 }
+
+/// Creates a button sized to its text, for use inside of windows
+pub fn create_small_button<'a>(
+    parent: &'a mut ChildBuilder,
+    ui_assets: &Res<UiAssets>,
+    content: impl Into<String>,
+    on_click: On<Pointer<Click>>,
+) -> EntityCommands<'a> {
+    let mut button = parent.spawn((
+        ButtonBundle {
+            style: Style {
+                height: Val::Px(SMALL_BUTTON_HEIGHT as f32),
+                padding: UiRect::horizontal(Val::Px(8.0)),
+                margin: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            border_color: BorderColor(Color::BLACK),
+            background_color: BUTTON_NORMAL.into(),
+            ..default()
+        },
+        on_click,
+    ));
+    button.with_children(|parent| {
+        create_label(
+            parent,
+            ui_assets,
+            content.into(),
+            SMALL_BUTTON_FONT_SIZE as f32,
+            FONT_COLOR,
+        );
+    });
+    button
+}
+
+/// Creates a button showing a single texture atlas slice
+pub fn create_image_button_from_slice<'a>(
+    parent: &'a mut ChildBuilder,
+    image: Handle<Image>,
+    atlas: Handle<TextureAtlasLayout>,
+    index: usize,
+    on_click: On<Pointer<Click>>,
+) -> EntityCommands<'a> {
+    let mut button = parent.spawn((
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(IMAGE_BUTTON_PADDING as f32)),
+                margin: UiRect::all(Val::Px(1.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BUTTON_NORMAL.into(),
+            ..default()
+        },
+        on_click,
+    ));
+    button.with_children(|parent| {
+        create_image_from_slice(parent, image, atlas, index);
+    });
+    button
+}
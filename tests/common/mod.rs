@@ -0,0 +1,28 @@
+//! Helpers shared by the integration tests
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use bevy_jam_factory::assets::registry::{Registry, REGISTRY_PATH};
+
+const ASSETS_PATH: &str = "assets";
+
+pub fn assets_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(ASSETS_PATH)
+}
+
+/// Loads and validates the registry, once per test binary
+///
+/// Resource types have to be defined by the registry before anything else can use them
+pub fn load_registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let path = assets_path().join(REGISTRY_PATH);
+        let registry = ron::from_str::<Registry>(&std::fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|err| panic!("{} failed to load: {}", path.display(), err));
+        registry
+            .validate()
+            .unwrap_or_else(|err| panic!("{} is invalid: {}", path.display(), err));
+        registry
+    })
+}
@@ -0,0 +1,72 @@
+//! Item footprints that hang off the map must be rejected, not wrap or panic
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_jam_factory::TileFootprint;
+
+const MAP_SIZE: TilemapSize = TilemapSize { x: 4, y: 4 };
+
+fn filled_storage() -> TileStorage {
+    let mut storage = TileStorage::empty(MAP_SIZE);
+    for y in 0..MAP_SIZE.y {
+        for x in 0..MAP_SIZE.x {
+            storage.set(&TilePos { x, y }, Entity::from_raw(y * MAP_SIZE.x + x));
+        }
+    }
+    storage
+}
+
+#[test]
+fn footprint_fits_inside_map() {
+    let footprint = TileFootprint::new(2, 2);
+    let storage = filled_storage();
+
+    let origin = TilePos { x: 2, y: 2 };
+    assert!(footprint.fits(origin, &MAP_SIZE));
+    assert!(footprint.tiles(origin, &storage).all(|tile| tile.is_some()));
+}
+
+#[test]
+fn off_map_footprint_is_rejected() {
+    let footprint = TileFootprint::new(2, 2);
+    let storage = filled_storage();
+
+    for origin in [
+        TilePos { x: 3, y: 0 },
+        TilePos { x: 0, y: 3 },
+        TilePos { x: 3, y: 3 },
+        TilePos { x: 4, y: 4 },
+    ] {
+        assert!(!footprint.fits(origin, &MAP_SIZE), "{:?}", origin);
+        assert!(
+            footprint.tiles(origin, &storage).any(|tile| tile.is_none()),
+            "{:?}",
+            origin
+        );
+    }
+}
+
+#[test]
+fn off_map_footprint_does_not_wrap_rows() {
+    let footprint = TileFootprint::new(2, 1);
+    let storage = filled_storage();
+
+    // the second tile would be the start of the next row if it wrapped
+    let tiles = footprint
+        .tiles(TilePos { x: 3, y: 0 }, &storage)
+        .collect::<Vec<_>>();
+    assert_eq!(tiles, vec![storage.get(&TilePos { x: 3, y: 0 }), None]);
+}
+
+#[test]
+fn huge_origin_does_not_overflow() {
+    let footprint = TileFootprint::new(2, 2);
+    let storage = filled_storage();
+
+    let origin = TilePos {
+        x: u32::MAX,
+        y: u32::MAX,
+    };
+    assert!(!footprint.fits(origin, &MAP_SIZE));
+    assert!(footprint.tiles(origin, &storage).all(|tile| tile.is_none()));
+}
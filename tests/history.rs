@@ -0,0 +1,112 @@
+//! Undo / redo has to put things back in the reverse order they were changed
+
+use bevy_ecs_tilemap::prelude::*;
+use bevy_jam_factory::data::{
+    direction::Direction,
+    history::{Action, ActionHistory, Change, HISTORY_LIMIT},
+    items::{conveyor::ConveyorData, ItemType},
+    save::SavedItem,
+};
+
+fn place_conveyor(x: u32) -> Action {
+    let mut action = Action::new(format!("Place Conveyor {}", x));
+    action.push(Change::Inventory(ItemType::Conveyor, -1));
+    action.push(Change::SpawnItem(SavedItem::Conveyor {
+        position: TilePos { x, y: 0 },
+        data: ConveyorData::new(Direction::default()),
+    }));
+    action
+}
+
+#[test]
+fn inverse_reverses_changes() {
+    let inverse = place_conveyor(1).inverse();
+
+    assert_eq!(inverse.changes.len(), 2);
+    assert!(matches!(
+        &inverse.changes[0],
+        Change::DespawnItem(SavedItem::Conveyor { position, .. }) if *position == TilePos { x: 1, y: 0 }
+    ));
+    assert!(matches!(
+        inverse.changes[1],
+        Change::Inventory(ItemType::Conveyor, 1)
+    ));
+
+    // inverting twice is the original action
+    let original = inverse.inverse();
+    assert!(matches!(
+        original.changes[0],
+        Change::Inventory(ItemType::Conveyor, -1)
+    ));
+    assert!(matches!(original.changes[1], Change::SpawnItem(_)));
+}
+
+#[test]
+fn undo_then_redo() {
+    let mut history = ActionHistory::default();
+    assert!(!history.can_undo());
+
+    history.record(place_conveyor(1));
+    history.record(place_conveyor(2));
+
+    let undo = history.pop_undo().unwrap();
+    assert_eq!(undo.name, "Place Conveyor 2");
+    history.undone(&undo.inverse());
+    assert!(history.can_redo());
+
+    let redo = history.pop_redo().unwrap();
+    assert_eq!(redo.name, "Place Conveyor 2");
+    assert!(matches!(redo.changes[1], Change::SpawnItem(_)));
+    history.redone(redo);
+
+    assert!(!history.can_redo());
+    assert_eq!(history.pop_undo().unwrap().name, "Place Conveyor 2");
+}
+
+#[test]
+fn cancelled_undo_is_kept() {
+    let mut history = ActionHistory::default();
+    history.record(place_conveyor(1));
+
+    let undo = history.pop_undo().unwrap();
+    history.cancel_undo(undo);
+
+    assert!(history.can_undo());
+    assert!(!history.can_redo());
+}
+
+#[test]
+fn recording_drops_redo() {
+    let mut history = ActionHistory::default();
+    history.record(place_conveyor(1));
+
+    let undo = history.pop_undo().unwrap();
+    history.undone(&undo.inverse());
+    assert!(history.can_redo());
+
+    history.record(place_conveyor(2));
+    assert!(!history.can_redo());
+
+    // nothing happened, nothing to undo
+    history.record(Action::new("Deconstruct"));
+    assert_eq!(history.pop_undo().unwrap().name, "Place Conveyor 2");
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn history_is_limited() {
+    let mut history = ActionHistory::default();
+    for x in 0..HISTORY_LIMIT as u32 + 10 {
+        history.record(place_conveyor(x));
+    }
+
+    let mut undone = 0;
+    let mut oldest = None;
+    while let Some(action) = history.pop_undo() {
+        oldest = Some(action.name);
+        undone += 1;
+    }
+
+    assert_eq!(undone, HISTORY_LIMIT);
+    assert_eq!(oldest.unwrap(), "Place Conveyor 10");
+}
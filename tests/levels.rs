@@ -0,0 +1,32 @@
+//! Every level in the manifest must be playable
+
+use bevy_jam_factory::{assets::levels::Levels, tiled::validate_map};
+
+mod common;
+use common::{assets_path, load_registry};
+
+const LEVELS_PATH: &str = "game.levels.ron";
+
+#[test]
+fn every_level_map_is_valid() {
+    let assets = assets_path();
+
+    // Resource types in the starting inventories are defined by the registry
+    let registry = load_registry();
+
+    let levels =
+        ron::from_str::<Levels>(&std::fs::read_to_string(assets.join(LEVELS_PATH)).unwrap())
+            .unwrap();
+    levels.validate().unwrap();
+
+    // generated maps are checked by the map_generator tests
+    for level in levels.levels.iter().filter(|level| !level.generated) {
+        let path = assets.join(&level.map);
+        let map = tiled::Loader::new()
+            .load_tmx_map(&path)
+            .unwrap_or_else(|err| panic!("Level {} failed to load: {}", level.name, err));
+
+        let errors = validate_map(&map, registry);
+        assert!(errors.is_empty(), "Level {}: {:?}", level.name, errors);
+    }
+}
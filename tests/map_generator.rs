@@ -0,0 +1,119 @@
+//! Generated maps must be playable and reproducible from their seed
+
+use std::io::Cursor;
+use std::path::Path;
+
+use bevy_jam_factory::tiled::{
+    generator::{
+        generate_map, get_generated_map_path, get_generated_map_seed, GeneratorTilesets,
+        MapGeneratorSettings,
+    },
+    validate_map, MIN_TILEMAP_HEIGHT, MIN_TILEMAP_WIDTH,
+};
+
+mod common;
+use common::{assets_path, load_registry};
+
+/// Generated maps are loaded from next to the tilesets they use
+const MAP_FILE: &str = "generated.tmx";
+
+fn load_tilesets() -> GeneratorTilesets {
+    GeneratorTilesets::load(&mut tiled::Loader::new(), assets_path()).unwrap()
+}
+
+fn load_generated_map(settings: &MapGeneratorSettings, tilesets: &GeneratorTilesets) -> tiled::Map {
+    let assets = assets_path();
+    let tmx = generate_map(settings, tilesets).to_tmx(tilesets);
+    let map_path = assets.join(MAP_FILE);
+
+    let mut loader = tiled::Loader::with_reader(|path: &Path| -> std::io::Result<_> {
+        if path == map_path {
+            Ok(Cursor::new(tmx.clone().into_bytes()))
+        } else {
+            Ok(Cursor::new(std::fs::read(path)?))
+        }
+    });
+    loader
+        .load_tmx_map(assets.join(MAP_FILE))
+        .unwrap_or_else(|err| panic!("seed {} failed to load: {}", settings.seed, err))
+}
+
+#[test]
+fn generated_maps_are_valid() {
+    let registry = load_registry();
+    let tilesets = load_tilesets();
+
+    for seed in 0..20 {
+        let map = load_generated_map(&MapGeneratorSettings::new(seed, registry), &tilesets);
+        let errors = validate_map(&map, registry);
+        assert!(errors.is_empty(), "seed {}: {:?}", seed, errors);
+    }
+}
+
+#[test]
+fn generated_maps_are_reproducible() {
+    let registry = load_registry();
+    let tilesets = load_tilesets();
+
+    let settings = MapGeneratorSettings::new(1234, registry);
+    assert_eq!(
+        generate_map(&settings, &tilesets),
+        generate_map(&settings, &tilesets)
+    );
+    assert_ne!(
+        generate_map(&settings, &tilesets),
+        generate_map(&MapGeneratorSettings::new(4321, registry), &tilesets)
+    );
+}
+
+#[test]
+fn generated_maps_respect_the_minimum_size() {
+    let map = generate_map(
+        &MapGeneratorSettings {
+            width: 1,
+            height: 1,
+            ..MapGeneratorSettings::new(1, load_registry())
+        },
+        &load_tilesets(),
+    );
+    assert_eq!(map.width, MIN_TILEMAP_WIDTH);
+    assert_eq!(map.height, MIN_TILEMAP_HEIGHT);
+    assert_eq!(map.tiles.len(), (map.width * map.height) as usize);
+}
+
+#[test]
+fn generated_resources_are_spaced_out() {
+    let settings = MapGeneratorSettings::new(99, load_registry());
+    let map = generate_map(&settings, &load_tilesets());
+    assert!(map.resources.len() >= settings.min_resources);
+    assert!(map.resources.len() <= settings.max_resources);
+
+    let spacing = settings.resource_spacing as i64;
+    for (idx, resource) in map.resources.iter().enumerate() {
+        for other in &map.resources[..idx] {
+            let (dx, dy) = (
+                resource.x as i64 - other.x as i64,
+                resource.y as i64 - other.y as i64,
+            );
+            assert!(dx * dx + dy * dy >= spacing * spacing);
+        }
+    }
+}
+
+#[test]
+fn generated_resources_use_registry_types() {
+    let registry = load_registry();
+    let settings = MapGeneratorSettings::new(7, registry);
+    assert_eq!(settings.resource_types.len(), registry.resources.len());
+
+    let map = generate_map(&settings, &load_tilesets());
+    for resource in &map.resources {
+        assert!(registry.get_resource(resource.r#type).is_some());
+    }
+}
+
+#[test]
+fn generated_map_paths_round_trip() {
+    assert_eq!(get_generated_map_seed(get_generated_map_path(42)), Some(42));
+    assert_eq!(get_generated_map_seed("map.tmx"), None);
+}
@@ -0,0 +1,58 @@
+//! Maps the game can't play must be rejected up front
+
+use std::io::Cursor;
+use std::path::Path;
+
+use bevy_jam_factory::tiled::{validate_map, TiledMapValidationError};
+
+mod common;
+use common::{assets_path, load_registry};
+
+const MAP_FILE: &str = "map.tmx";
+
+/// Loads map.tmx after editing its TMX
+fn load_edited_map(edit: impl FnOnce(String) -> String) -> tiled::Map {
+    let assets = assets_path();
+    let map_path = assets.join(MAP_FILE);
+    let tmx = edit(std::fs::read_to_string(&map_path).unwrap());
+
+    let mut loader = tiled::Loader::with_reader(|path: &Path| -> std::io::Result<_> {
+        if path == map_path {
+            Ok(Cursor::new(tmx.clone().into_bytes()))
+        } else {
+            Ok(Cursor::new(std::fs::read(path)?))
+        }
+    });
+    loader.load_tmx_map(assets.join(MAP_FILE)).unwrap()
+}
+
+#[test]
+fn grouped_tile_layers_are_rejected() {
+    let map = load_edited_map(|tmx| {
+        let start = tmx.find("  <layer ").unwrap();
+        let end = tmx.find("</layer>").unwrap() + "</layer>\n".len();
+        let second = tmx[start..end].replace(r#"id="1""#, r#"id="99""#);
+        format!("{}{}{}", &tmx[..end], second, &tmx[end..])
+    });
+
+    let errors = validate_map(&map, load_registry());
+    assert_eq!(
+        errors,
+        vec![TiledMapValidationError::InvalidTileLayerCount { count: 2 }]
+    );
+}
+
+#[test]
+fn missing_object_layer_is_rejected() {
+    let map = load_edited_map(|tmx| {
+        let start = tmx.find("  <objectgroup ").unwrap();
+        let end = tmx.find("</objectgroup>").unwrap() + "</objectgroup>\n".len();
+        format!("{}{}", &tmx[..start], &tmx[end..])
+    });
+
+    let errors = validate_map(&map, load_registry());
+    assert_eq!(
+        errors,
+        vec![TiledMapValidationError::InvalidObjectLayerCount { count: 0 }]
+    );
+}
@@ -0,0 +1,188 @@
+//! Every save ever written by a released version of the game must keep loading
+//!
+//! Add a fixture to tests/fixtures/saves whenever SAVE_VERSION is bumped
+
+use std::path::Path;
+
+use bevy_ecs_tilemap::prelude::*;
+use bevy_jam_factory::data::{
+    direction::Direction,
+    objects::ObjectData,
+    resources::ResourceType,
+    save::{SaveData, SaveError, SavedItem, SAVE_VERSION},
+};
+
+mod common;
+use common::load_registry;
+
+const FIXTURES_PATH: &str = "tests/fixtures/saves";
+
+fn read_fixture(name: &str) -> SaveData {
+    // Resource types have to be defined by the registry before saves can use them
+    load_registry();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join(FIXTURES_PATH)
+        .join(name);
+    SaveData::read(&path).unwrap_or_else(|err| panic!("{} failed to load: {}", path.display(), err))
+}
+
+#[test]
+fn every_fixture_loads() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES_PATH);
+
+    let mut fixtures = 0;
+    for entry in std::fs::read_dir(&path).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|extension| extension == "ron") {
+            let save = read_fixture(path.file_name().unwrap().to_str().unwrap());
+            assert_eq!(save.version, SAVE_VERSION, "{}", path.display());
+            fixtures += 1;
+        }
+    }
+
+    // one fixture per-version
+    assert_eq!(fixtures, SAVE_VERSION);
+}
+
+#[test]
+fn v1_migrates_placement_direction() {
+    let save = read_fixture("v1.ron");
+    assert_eq!(save.placement_direction, Direction::default());
+    assert_eq!(save.objects.len(), 2);
+    assert_eq!(save.items.len(), 3);
+    assert_eq!(save.camera, (-120.0, 64.0));
+}
+
+#[test]
+fn v1_migrates_items_and_inventory() {
+    let save = read_fixture("v1.ron");
+    let iron = ResourceType::get("Iron").unwrap();
+    assert_eq!(save.inventory.get_resources().get(&iron), Some(&100));
+
+    let SavedItem::Harvester { data, .. } = &save.items[0] else {
+        panic!("expected a Harvester, got {:?}", save.items[0]);
+    };
+    assert_eq!((data.r#type, data.current, data.remaining), (iron, 12, 88));
+
+    let SavedItem::Conveyor { data, .. } = &save.items[1] else {
+        panic!("expected a Conveyor, got {:?}", save.items[1]);
+    };
+    assert_eq!(data.direction, Direction::North);
+    assert_eq!(data.peek(), Some(iron));
+
+    let SavedItem::Crafter { data, .. } = &save.items[2] else {
+        panic!("expected a Crafter, got {:?}", save.items[2]);
+    };
+    let recipe = data.get_recipe().unwrap();
+    assert_eq!(recipe.name, "Iron Plate");
+    assert_eq!(recipe.get_input_amount(iron), 2);
+    assert_eq!(recipe.output, ResourceType::get("IronPlate").unwrap());
+}
+
+#[test]
+fn v2_keeps_placement_direction() {
+    let save = read_fixture("v2.ron");
+    assert_eq!(save.placement_direction, Direction::South);
+}
+
+#[test]
+fn v2_migrates_object_tilesets() {
+    let save = read_fixture("v2.ron");
+    assert!(save.objects.iter().all(|object| object.tileset.is_none()));
+}
+
+#[test]
+fn v3_keeps_object_tilesets() {
+    let save = read_fixture("v3.ron");
+    assert_eq!(save.placement_direction, Direction::South);
+    assert!(save
+        .objects
+        .iter()
+        .all(|object| object.tileset.as_deref() == Some("objects")));
+}
+
+#[test]
+fn v3_migrates_object_orientation() {
+    let save = read_fixture("v3.ron");
+    assert!(save.objects.iter().all(|object| {
+        object.flip == TileFlip::default() && object.data.get_direction() == Direction::East
+    }));
+}
+
+#[test]
+fn v4_keeps_object_orientation() {
+    let save = read_fixture("v4.ron");
+    let object = &save.objects[1];
+    assert_eq!(
+        object.flip,
+        TileFlip {
+            x: true,
+            y: false,
+            d: true,
+        }
+    );
+    assert_eq!(object.data.get_direction(), Direction::South);
+}
+
+#[test]
+fn v4_migrates_map() {
+    let save = read_fixture("v4.ron");
+    assert_eq!(save.map, "map.tmx");
+}
+
+#[test]
+fn v5_keeps_map() {
+    let save = read_fixture("v5.ron");
+    assert_eq!(save.map, "quarry.tmx");
+}
+
+#[test]
+fn v5_migrates_resource_types() {
+    let save = read_fixture("v5.ron");
+    let iron = ResourceType::get("Iron").unwrap();
+    assert_eq!(save.inventory.get_resources().get(&iron), Some(&100));
+    let ObjectData::Resources { r#type, .. } = save.objects[0].data;
+    assert_eq!(r#type, iron);
+}
+
+#[test]
+fn v6_keeps_resource_types() {
+    let save = read_fixture("v6.ron");
+    let iron = ResourceType::get("Iron").unwrap();
+    assert_eq!(save.inventory.get_resources().get(&iron), Some(&100));
+    let ObjectData::Resources { r#type, .. } = save.objects[0].data;
+    assert_eq!(r#type, iron);
+}
+
+#[test]
+fn unknown_resource_types_are_rejected() {
+    load_registry();
+
+    let contents = std::fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join(FIXTURES_PATH)
+            .join("v6.ron"),
+    )
+    .unwrap()
+    .replace("\"IronPlate\"", "\"Unobtainium\"");
+    assert!(matches!(
+        SaveData::parse(contents),
+        Err(SaveError::Deserialize(_))
+    ));
+    assert!(ResourceType::get("Unobtainium").is_none());
+}
+
+#[test]
+fn unknown_versions_are_rejected() {
+    let contents = format!("(version: {})", SAVE_VERSION + 1);
+    assert!(matches!(
+        SaveData::parse(contents),
+        Err(SaveError::UnsupportedVersion(_))
+    ));
+
+    assert!(matches!(
+        SaveData::parse("(version: 0)"),
+        Err(SaveError::UnsupportedVersion(0))
+    ));
+}
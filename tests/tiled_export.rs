@@ -0,0 +1,144 @@
+//! Exported maps must load back as the world they were exported from
+
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use bevy_ecs_tilemap::prelude::*;
+use bevy_jam_factory::{
+    data::{direction::Direction, items::ItemType},
+    tiled::{
+        export::{get_tileset_sources, ExportedItem, ExportedWorld, MapExporter},
+        get_layers, validate_map, ITEM_LAYER_CLASS,
+    },
+};
+use strum::IntoEnumIterator;
+
+mod common;
+use common::{assets_path, load_registry};
+
+const EXPORT_PATH: &str = "export.tmx";
+
+/// Exports a world on top of a map and loads the export back
+///
+/// The export is read from next to the map, so the paths it writes can be used as is
+fn round_trip(map_path: &str, world: impl FnOnce(&tiled::Map) -> ExportedWorld) -> tiled::Map {
+    let registry = load_registry();
+
+    let mut loader = tiled::Loader::new();
+    let map = loader.load_tmx_map(assets_path().join(map_path)).unwrap();
+    let tileset_sources = get_tileset_sources(loader.cache());
+
+    let tmx = MapExporter {
+        map: &map,
+        tileset_sources: &tileset_sources,
+        registry,
+        asset_dir: Path::new(""),
+    }
+    .export(&world(&map));
+
+    let export_path = assets_path().join(EXPORT_PATH);
+    let mut loader = tiled::Loader::with_reader(|path: &Path| -> std::io::Result<Box<dyn Read>> {
+        if path == export_path {
+            return Ok(Box::new(Cursor::new(tmx.clone().into_bytes())));
+        }
+        Ok(Box::new(std::fs::File::open(path)?))
+    });
+    loader
+        .load_tmx_map(&export_path)
+        .unwrap_or_else(|err| panic!("Export of {} failed to load: {}\n{}", map_path, err, tmx))
+}
+
+#[test]
+fn map_round_trips() {
+    let registry = load_registry();
+
+    for map_path in ["map.tmx", "quarry.tmx"] {
+        let map = tiled::Loader::new()
+            .load_tmx_map(assets_path().join(map_path))
+            .unwrap();
+        let exported = round_trip(map_path, ExportedWorld::from_map);
+
+        assert_eq!(
+            ExportedWorld::from_map(&map),
+            ExportedWorld::from_map(&exported),
+            "{}",
+            map_path
+        );
+
+        let layers = get_layers(&map);
+        let exported_layers = get_layers(&exported);
+        assert_eq!(layers.len(), exported_layers.len(), "{}", map_path);
+        for (layer, exported_layer) in layers.iter().zip(&exported_layers) {
+            assert_eq!(layer.layer.id(), exported_layer.layer.id());
+            assert_eq!(layer.layer.name, exported_layer.layer.name);
+            assert_eq!(layer.layer.properties, exported_layer.layer.properties);
+            assert_eq!(layer.opacity, exported_layer.opacity);
+            assert_eq!(layer.visible, exported_layer.visible);
+        }
+
+        let errors = validate_map(&exported, registry);
+        assert!(errors.is_empty(), "{}: {:?}", map_path, errors);
+    }
+}
+
+#[test]
+fn object_orientation_round_trips() {
+    let flips = (0..8)
+        .map(|bits| TileFlip {
+            x: bits & 1 != 0,
+            y: bits & 2 != 0,
+            d: bits & 4 != 0,
+        })
+        .collect::<Vec<_>>();
+
+    let mut world = None;
+    let exported = round_trip("map.tmx", |map| {
+        let mut orientations = Direction::iter()
+            .flat_map(|direction| flips.iter().map(move |flip| (direction, *flip)))
+            .cycle();
+
+        let mut oriented = ExportedWorld::from_map(map);
+        for object in oriented.objects.values_mut().flatten() {
+            let (direction, flip) = orientations.next().unwrap();
+            object.data.set_direction(direction);
+            object.flip = flip;
+        }
+
+        world = Some(oriented.clone());
+        oriented
+    });
+
+    assert_eq!(world.unwrap(), ExportedWorld::from_map(&exported));
+}
+
+#[test]
+fn items_are_exported_but_not_loaded() {
+    let exported = round_trip("map.tmx", |map| {
+        let mut world = ExportedWorld::from_map(map);
+        // map.tmx Objects are single tiles
+        let footprint = world.objects.values().flatten().next().unwrap().footprint;
+        world.items = ItemType::iter()
+            .enumerate()
+            .map(|(idx, r#type)| ExportedItem {
+                r#type,
+                position: TilePos {
+                    x: idx as u32,
+                    y: 0,
+                },
+                footprint,
+                sprite_index: 0,
+            })
+            .collect();
+        world
+    });
+
+    let item_layer = exported
+        .layers()
+        .find(|layer| layer.user_type.as_deref() == Some(ITEM_LAYER_CLASS))
+        .and_then(|layer| layer.as_object_layer())
+        .expect("Items should be exported");
+    assert_eq!(item_layer.objects().len(), ItemType::iter().count());
+
+    assert!(ExportedWorld::from_map(&exported).items.is_empty());
+    assert!(validate_map(&exported, load_registry()).is_empty());
+}
@@ -0,0 +1,30 @@
+//! The Tiled project must offer the same property types the game reads
+//!
+//! Run `cargo run --bin export-tiled-types` to regenerate it
+
+use std::path::Path;
+
+use bevy_jam_factory::tiled::project::{update_project, write_project};
+
+mod common;
+use common::load_registry;
+
+const PROJECT_PATH: &str = "maps/tilemap.tiled-project";
+
+#[test]
+fn project_property_types_are_up_to_date() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let registry = load_registry();
+
+    let contents = std::fs::read_to_string(root.join(PROJECT_PATH)).unwrap();
+    let mut project = serde_json::from_str::<serde_json::Value>(&contents).unwrap();
+    update_project(&mut project, registry).unwrap();
+
+    assert_eq!(
+        contents,
+        write_project(&project).unwrap(),
+        "{} is out of date",
+        PROJECT_PATH
+    );
+}